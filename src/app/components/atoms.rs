@@ -3,3 +3,4 @@ pub mod text_field;
 pub mod button;
 pub mod mouse_sensor;
 pub mod size_observer;
+pub mod key_listener;
@@ -0,0 +1,92 @@
+//! A component that expands to nothing and listens to the keyboard on the whole window, for keyboard shortcuts
+
+use yew::prelude::*;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyPressEvent {
+    /// The key as given by the browser, like "z" or "Z" or " "
+    pub key: String,
+    pub ctrl: bool,
+    pub shift: bool,
+
+    /// True if the key went down, false if it went up
+    pub pressed: bool,
+
+    /// True if the key went to a text field. Shortcuts should leave these alone so that typing still works
+    pub in_text_field: bool,
+    pub event: KeyboardEvent
+}
+
+impl KeyPressEvent {
+    fn new(event: KeyboardEvent, pressed: bool) -> Self {
+        let in_text_field = event.target().is_some_and(|x| {
+            x.dyn_ref::<HtmlInputElement>().is_some() || x.dyn_ref::<HtmlTextAreaElement>().is_some()
+        });
+
+        KeyPressEvent {
+            key: event.key(),
+            // Cmd on macs does what ctrl does everywhere else
+            ctrl: event.ctrl_key() || event.meta_key(),
+            shift: event.shift_key(),
+            pressed,
+            in_text_field,
+            event
+        }
+    }
+
+    /// Returns true if this is the key going down with ctrl held, and with or without shift as asked. 'key' is compared ignoring case
+    pub fn is_shortcut(&self, key: &str, shift: bool) -> bool {
+        self.pressed && self.ctrl && self.shift == shift && !self.in_text_field && self.key.eq_ignore_ascii_case(key)
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct KeyListenerProps {
+    pub cb: Option<Callback<KeyPressEvent>>
+}
+
+pub struct KeyListener;
+
+pub enum KeyListenerMessage {
+    KeyEvent(KeyPressEvent),
+}
+
+impl Component for KeyListener {
+    type Message = KeyListenerMessage;
+    type Properties = KeyListenerProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let window = web_sys::window().unwrap();
+
+        for (name, pressed) in [("keydown", true), ("keyup", false)] {
+            let link = ctx.link().clone();
+            let listener = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+                link.send_message(KeyListenerMessage::KeyEvent(KeyPressEvent::new(event, pressed)));
+            }) as Box<dyn FnMut(KeyboardEvent)>);
+
+            window
+                .add_event_listener_with_callback(name, listener.as_ref().unchecked_ref())
+                .unwrap();
+
+            listener.forget();
+        }
+
+        KeyListener
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let cb = (&ctx.props().cb).clone().unwrap_or(Callback::from(|_| ()));
+        match msg {
+            KeyListenerMessage::KeyEvent(event) => cb.emit(event)
+        };
+
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        html!{}
+    }
+}
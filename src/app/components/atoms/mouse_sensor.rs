@@ -28,6 +28,9 @@ pub enum MouseClickType {
     MouseUp,
     MouseLeave,
     MouseOver,
+
+    /// The mouse event is a wheel event, which has how far the wheel turned
+    Wheel,
 }
 
 #[derive(PartialEq, Properties)]
@@ -51,6 +54,18 @@ fn get_callback(props: &MouseSensorProps, t: MouseClickType) -> Callback<MouseEv
     })
 }
 
+fn get_wheel_callback(props: &MouseSensorProps) -> Callback<WheelEvent> {
+    let cb = props.cb.clone();
+    Callback::from(move |event: WheelEvent| {
+        let (sx, sy) = (event.screen_x(), event.screen_y());
+        cb.emit(MouseClickEvent {
+            click_type: MouseClickType::Wheel,
+            screen_pos: (sx, sy),
+            mouse_event: (*event).clone()
+        });
+    })
+}
+
 #[function_component(MouseSensor)]
 pub fn mouse_sensor(props: &MouseSensorProps) -> Html {
     let id = props.id.clone();
@@ -67,6 +82,7 @@ pub fn mouse_sensor(props: &MouseSensorProps) -> Html {
             onmouseout={get_callback(props, MouseClickType::MouseOut)}
             onmouseover={get_callback(props, MouseClickType::MouseOver)}
             onmouseup={get_callback(props, MouseClickType::MouseUp)}
+            onwheel={get_wheel_callback(props)}
             >
         </button>
     }
@@ -4,12 +4,16 @@
 //!
 //! This component is responsible for the main operations of the wrapper app around tikzpaint. This calculates the svg,
 //! and handles all clicks and translate them into actions on the figure.
+//!
+//! The view is zoomed with the mouse wheel and panned by dragging with the middle button or with space held.
+//! F zooms to fit everything in the figure and 0 goes back to the starting view
 
 use gloo::console::log;
 use stylist::Style;
 use stylist::css;
 use yew::prelude::*;
 use web_sys::HtmlElement;
+use web_sys::WheelEvent;
 use wasm_bindgen::JsCast;
 use crate::figures::*;
 use crate::app::*;
@@ -83,6 +87,38 @@ pub enum CanvasManagerMessage {
     ChangedFigureRerenderLast
 }
 
+/// Asks the canvas manager to draw whatever the figure complex says has changed
+fn request_render(link: &html::Scope<CanvasManager>, render_type: FigureRenderType) {
+    match render_type {
+        FigureRenderType::Render => { link.send_message(CanvasManagerMessage::ChangedFigure) },
+        FigureRenderType::Rerender => { link.send_message(CanvasManagerMessage::ChangedFigureRerender) },
+        FigureRenderType::RerenderLast => { link.send_message(CanvasManagerMessage::ChangedFigureRerenderLast) },
+        FigureRenderType::DoNothing => {}
+        FigureRenderType::Error(s) => {log!(format!("Failed to render figure: {}", s))}
+    }
+}
+
+/// Turning the wheel by one pixel zooms by this much, as a power of e
+const ZOOM_SPEED: f64 = 0.0015;
+
+/// Wheels that turn by lines count this many pixels a line
+const PIXELS_PER_LINE: f64 = 20.;
+
+/// Zoom to fit leaves this many pixels around the figure
+const FIT_PADDING: f64 = 40.;
+
+/// Keeps track of panning the view with the mouse
+#[derive(Default)]
+struct PanState {
+    /// Where the mouse was last while panning, or None if we are not panning
+    last: Option<(i32, i32)>,
+
+    space_held: bool,
+
+    /// Panning with the left button and space ends in a click, which should not go to the figure
+    swallow_click: bool,
+}
+
 macro_rules! mborrow {
     ($x: ident) => {
         (*(*$x.clone()).borrow_mut())
@@ -95,30 +131,78 @@ pub struct CanvasManager {
     fig: Rc<RefCell<FigureComplex>>,
     transform: Rc<RefCell<Transform>>,
     canvas: HtmlCanvas,
+    pan: Rc<RefCell<PanState>>,
 }
 
 impl CanvasManager {
     fn get_canvas_sensor_cb(&self, props: &CanvasManagerProps, ctx: &Context<Self>) -> Callback<CanvasSensorEvent> {
         let f = self.fig.clone();
         let tf = self.transform.clone();
+        let pan = self.pan.clone();
         let link = ctx.link().clone();
         let debug_mode = is_true(props.debug);
 
         // Handles main canvas sensor events
         let canvas_sensor_cb = Callback::from(move |event: CanvasSensorEvent| {
             let (x, y) = event.mouse_click_event.screen_pos;
+            let mouse_event = &event.mouse_click_event.mouse_event;
+
+            // The wheel and panning change the view and never go to the figure
+            match event.mouse_click_event.click_type {
+                MouseClickType::Wheel => {
+                    if let Some(e) = mouse_event.dyn_ref::<WheelEvent>() {
+                        // Otherwise the page scrolls, or zooms as a whole with ctrl held, along with the canvas
+                        e.prevent_default();
+                        let dy = if e.delta_mode() == WheelEvent::DOM_DELTA_LINE { e.delta_y() * PIXELS_PER_LINE } else { e.delta_y() };
+                        mborrow!(tf).zoom_at((-dy * ZOOM_SPEED).exp(), x, y);
+                        link.send_message(CanvasManagerMessage::ChangedFigureRerender);
+                    }
+                    return;
+                },
+
+                MouseClickType::MouseDown if mouse_event.button() == 1 || pan.borrow().space_held => {
+                    mborrow!(pan).last = Some((x, y));
+                    return;
+                },
+
+                MouseClickType::MouseMove if pan.borrow().last.is_some() => {
+                    // Safe unwrap - we have just checked
+                    let (lx, ly) = pan.borrow().last.unwrap();
+                    mborrow!(tf).pan(x - lx, y - ly);
+                    mborrow!(pan).last = Some((x, y));
+                    link.send_message(CanvasManagerMessage::ChangedFigureRerender);
+                    return;
+                },
+
+                MouseClickType::MouseUp if pan.borrow().last.is_some() => {
+                    let mut p = pan.borrow_mut();
+                    p.last = None;
+                    p.swallow_click = mouse_event.button() == 0;
+                    return;
+                },
+
+                MouseClickType::LeftClick if pan.borrow().swallow_click => {
+                    mborrow!(pan).swallow_click = false;
+                    return;
+                },
+
+                _ => ()
+            }
+
             let v = tf.borrow().world_to_local(x, y);
+            let shift = event.mouse_click_event.mouse_event.shift_key();
+            mborrow!(f).set_view_scale(tf.borrow().scale);
             // Suppose we need to spawn a point. We need do perform the following:
             // 1. Get the coordinates of the click. Transform that into the canvas coordinates
             // 2. Spawn a point at the canvas coordinates
             // 3. Pass the figure to the renderer and perform the rendering of the svg
             let render_type = match event.mouse_click_event.click_type {
                 MouseClickType::LeftClick => {
-                    mborrow!(f).click(v)
+                    mborrow!(f).click(v, shift)
                 },
 
                 MouseClickType::MouseDown => {
-                    mborrow!(f).start_dragging(v)
+                    mborrow!(f).start_dragging(v, shift)
                 },
 
                 MouseClickType::MouseMove => {
@@ -134,36 +218,101 @@ impl CanvasManager {
                     mborrow!(f).stop_dragging(v)
                 },
 
+                // The mouse can be released where the canvas does not see it, so dragging out of the canvas ends the gesture
+                MouseClickType::MouseLeave if event.dragging => {
+                    mborrow!(f).stop_dragging(v)
+                },
+
                 _ => FigureRenderType::DoNothing
             };
 
             // Render the canvas
-            match render_type {
-                FigureRenderType::Render => { link.send_message(CanvasManagerMessage::ChangedFigure) },
-                FigureRenderType::Rerender => { link.send_message(CanvasManagerMessage::ChangedFigureRerender) },
-                FigureRenderType::RerenderLast => { link.send_message(CanvasManagerMessage::ChangedFigureRerenderLast) },
-                FigureRenderType::DoNothing => {}
-                FigureRenderType::Error(s) => {log!(format!("Failed to render figure: {}", s))}
-            }
+            request_render(&link, render_type);
         });
 
         return canvas_sensor_cb;
     }
 
     fn get_header_cb(&self, props: &CanvasManagerProps, ctx: &Context<Self>) -> Callback<HeaderBarEvent> {
+        let f = self.fig.clone();
+        let link = ctx.link().clone();
+
         // Handles header bar events
         let header_cb = Callback::from(move |event: HeaderBarEvent| {
+            let render_type = match event.button_type {
+                HeaderBarType::Undo => mborrow!(f).undo(),
+                HeaderBarType::Redo => mborrow!(f).redo(),
+                _ => FigureRenderType::DoNothing
+            };
 
+            request_render(&link, render_type);
         });
 
         return header_cb;
     }
 
+    fn get_key_cb(&self, props: &CanvasManagerProps, ctx: &Context<Self>) -> Callback<KeyPressEvent> {
+        let f = self.fig.clone();
+        let tf = self.transform.clone();
+        let pan = self.pan.clone();
+        let link = ctx.link().clone();
+
+        // Handles keyboard shortcuts: ctrl+Z undos, ctrl+shift+Z redos, space held pans, F zooms to fit and 0 resets the view
+        let key_cb = Callback::from(move |event: KeyPressEvent| {
+            if event.in_text_field {
+                return;
+            }
+
+            if event.key == " " {
+                mborrow!(pan).space_held = event.pressed;
+
+                // Otherwise the page scrolls
+                event.event.prevent_default();
+                return;
+            }
+
+            if event.pressed && !event.ctrl {
+                if event.key.eq_ignore_ascii_case("f") {
+                    let bb = f.borrow().figure().bounding_box();
+                    if let Some(bb) = bb {
+                        mborrow!(tf).zoom_to_fit(bb, FIT_PADDING);
+                        link.send_message(CanvasManagerMessage::ChangedFigureRerender);
+                    }
+                    return;
+                }
+
+                if event.key == "0" {
+                    mborrow!(tf).reset_view();
+                    link.send_message(CanvasManagerMessage::ChangedFigureRerender);
+                    return;
+                }
+            }
+
+            let render_type = if event.is_shortcut("z", false) {
+                mborrow!(f).undo()
+            }
+            else if event.is_shortcut("z", true) {
+                mborrow!(f).redo()
+            }
+            else {
+                return;
+            };
+
+            // Keep the browser from undoing things on its own
+            event.event.prevent_default();
+            request_render(&link, render_type);
+        });
+
+        return key_cb;
+    }
+
     fn get_sidebar_cb(&self, props: &CanvasManagerProps, ctx: &Context<Self>) -> Callback<SideBarEvent> {
         let f = self.fig.clone();
+        let link = ctx.link().clone();
         let sidebar_cb = Callback::from(move |event: SideBarEvent| {
-            mborrow!(f).set_state(event.button_type);
+            let render_type = mborrow!(f).set_state(event.button_type);
             log!(format!("Setting side bar type to {:?}", event.button_type));
+            request_render(&link, render_type);
         });
 
         return sidebar_cb;
@@ -184,17 +333,16 @@ impl CanvasManager {
             };
 
             // Draw the thing
-            let draw_result = mborrow!(f).draw_with_text(text);
-
-            if let Err(e) = draw_result {
-                return TerminalResetEvent {
+            let render_type = match mborrow!(f).draw_with_text(text) {
+                Ok(x) => x,
+                Err(e) => return TerminalResetEvent {
                     event_type: TerminalResetType::StaySame,
                     error_msg: Some(e.msg)
                 }
-            }
+            };
 
-            // Tells the figure complex to render the new object
-            link.send_message(CanvasManagerMessage::ChangedTerminal);
+            // Tells the figure complex to render the new object, or everything again if objects have moved
+            request_render(&link, render_type);
 
             return TerminalResetEvent {
                 event_type: TerminalResetType::Reset,
@@ -242,7 +390,7 @@ impl CanvasManager {
                         log!("Performing a canvas rerender because dimensions of the windows have changed");
                     }
 
-                    let action = mborrow!(f).rerender(csh.clone());
+                    let action = mborrow!(f).rerender(&csh);
 
                     if let Err(e) = action {
                         log!(format!("Failed to redraw canvas. Reason: {:?}", e));
@@ -296,6 +444,7 @@ impl Component for CanvasManager {
             fig: Rc::new(RefCell::new(fig_state)),
             transform: t_ptr.clone(),
             canvas: HtmlCanvas::new(t_ptr.clone()),
+            pan: Rc::new(RefCell::new(PanState::default())),
         }
     }
 
@@ -312,17 +461,17 @@ impl Component for CanvasManager {
             // This means an event on the terminal or the sensor
             CanvasManagerMessage::ChangedFigure | CanvasManagerMessage::ChangedTerminal => {
                 // This triggers a simple render where we put the newly drawn stuff in
-                fig.render(self.canvas.clone())
+                fig.render(&self.canvas)
             }
 
             CanvasManagerMessage::ChangedFigureRerender => {
                 // This triggers a simple render where we put the newly drawn stuff in
-                fig.rerender(self.canvas.clone())
+                fig.rerender(&self.canvas)
             },
 
             CanvasManagerMessage::ChangedFigureRerenderLast => {
                 // This triggers a simple render where we put the newly drawn stuff in
-                fig.rerender_last(self.canvas.clone())
+                fig.rerender_last(&self.canvas)
             }
         };
 
@@ -347,6 +496,7 @@ impl Component for CanvasManager {
         // Get all callbacks
         let canvas_sensor_cb = self.get_canvas_sensor_cb(props, ctx);
         let header_cb = self.get_header_cb(props, ctx);
+        let key_cb = self.get_key_cb(props, ctx);
         let sidebar_cb = self.get_sidebar_cb(props, ctx);
         let terminal_cb = self.get_terminal_cb(props, ctx);
         let resize_cb = self.get_resize_cb(props, ctx);
@@ -373,6 +523,7 @@ impl Component for CanvasManager {
                     {terminal_text}
                 </Terminal>
                 <WindowResizeListener cb={resize_cb}/>
+                <KeyListener cb={key_cb}/>
                 <div class={class_id}>
                     <CanvasSensor id={"canvas-sensor"} top={h} left={w} cb={canvas_sensor_cb}/>
                     <CanvasRenderer id={"canvas-renderer"} tf={tf} canvas={csh} cb={canvas_renderer_cb}/>
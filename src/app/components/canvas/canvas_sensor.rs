@@ -33,7 +33,7 @@ pub fn main_canvas(props: &CanvasSensorProps) -> Html {
     let mouse_sensor_cb = Callback::from(move |event: MouseClickEvent| {
         match event.click_type {
             MouseClickType::MouseDown => {dragging_state.set(true)},
-            MouseClickType::MouseUp => {dragging_state.set(false)},
+            MouseClickType::MouseUp | MouseClickType::MouseLeave => {dragging_state.set(false)},
             _ => ()
         }
 
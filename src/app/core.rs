@@ -1,3 +0,0 @@
-pub mod figure_object_complex;
-pub mod parser;
-pub mod terminal_text_renderer;
@@ -0,0 +1,317 @@
+//! Command line tool that compiles a script of terminal commands into a Tikz figure or an svg, without the browser.
+//! The script is in the same language as the terminal in the app, one command per line. Blank lines are skipped
+//!
+//! Usage: tikzpaint [OPTIONS] [FILE]
+//! Reads from stdin if FILE is missing or is "-"
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+use regex::{Captures, Regex};
+
+use tikzpaint_rs::core::{set_logger, TikzOutputOptions, TikzUnit};
+use tikzpaint_rs::engine::FigureComplex;
+
+const USAGE: &str = "Usage: tikzpaint [OPTIONS] [FILE]
+
+Compiles a file of tikzpaint commands, one per line, into a figure. Reads from stdin if FILE is missing or is \"-\"
+
+Options:
+    -f, --format <tikz|svg>     Output format (default: tikz)
+    -s, --standalone            Wrap the Tikz figure in a LaTeX document that compiles on its own
+        --figure                Wrap the Tikz figure in a figure environment
+        --caption <TEXT>        Caption of the figure environment
+        --label <TEXT>          Label of the figure environment
+        --bounding-box          Fix the size of the Tikz figure to the extent of the objects with \\useasboundingbox
+    -p, --precision <N>         Round every number in the output to N decimal places (default for Tikz: 4)
+        --unit <cm|mm|pt>       Length of one unit on the figure in the Tikz output (default: cm)
+        --unit-suffix           Write the unit on every length instead of setting it on the tikzpicture
+    -o, --output <FILE>         Write to FILE instead of stdout
+    -v, --verbose               Print the log messages of the parser to stderr
+    -h, --help                  Print this message";
+
+#[derive(PartialEq, Debug)]
+enum OutputFormat {
+    Tikz,
+    Svg
+}
+
+#[derive(Debug)]
+struct Options {
+    format: OutputFormat,
+    standalone: bool,
+    figure: bool,
+    caption: Option<String>,
+    label: Option<String>,
+    bounding_box: bool,
+    precision: Option<usize>,
+    unit: TikzUnit,
+    unit_suffix: bool,
+    input: Option<String>,
+    output: Option<String>,
+    verbose: bool,
+    help: bool,
+}
+
+/// Parses the command line arguments, not including the program name
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut opts = Options {
+        format: OutputFormat::Tikz,
+        standalone: false,
+        figure: false,
+        caption: None,
+        label: None,
+        bounding_box: false,
+        precision: None,
+        unit: TikzUnit::Cm,
+        unit_suffix: false,
+        input: None,
+        output: None,
+        verbose: false,
+        help: false,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" | "--format" => {
+                opts.format = match args.next().as_deref() {
+                    Some("tikz") => OutputFormat::Tikz,
+                    Some("svg") => OutputFormat::Svg,
+                    Some(x) => return Err(format!("Unknown output format: {x}")),
+                    None => return Err(format!("Missing value for {arg}"))
+                };
+            },
+
+            "-p" | "--precision" => {
+                let p = args.next().ok_or(format!("Missing value for {arg}"))?;
+                let p = p.parse().map_err(|_| format!("Invalid precision: {p}"))?;
+                opts.precision = Some(p);
+            },
+
+            "--unit" => {
+                opts.unit = match args.next().as_deref() {
+                    Some("cm") => TikzUnit::Cm,
+                    Some("mm") => TikzUnit::Mm,
+                    Some("pt") => TikzUnit::Pt,
+                    Some(x) => return Err(format!("Unknown unit: {x}")),
+                    None => return Err(format!("Missing value for {arg}"))
+                };
+            },
+
+            "-o" | "--output" => {
+                opts.output = Some(args.next().ok_or(format!("Missing value for {arg}"))?);
+            },
+
+            "--caption" => {
+                opts.caption = Some(args.next().ok_or(format!("Missing value for {arg}"))?);
+            },
+
+            "--label" => {
+                opts.label = Some(args.next().ok_or(format!("Missing value for {arg}"))?);
+            },
+
+            "-s" | "--standalone" => opts.standalone = true,
+            "--figure" => opts.figure = true,
+            "--bounding-box" => opts.bounding_box = true,
+            "--unit-suffix" => opts.unit_suffix = true,
+            "-v" | "--verbose" => opts.verbose = true,
+            "-h" | "--help" => opts.help = true,
+
+            x if x.starts_with("-") && x != "-" => return Err(format!("Unknown option: {x}")),
+
+            _ => {
+                if opts.input.is_some() {
+                    return Err(format!("Unexpected argument: {arg}"));
+                }
+                opts.input = Some(arg);
+            }
+        }
+    }
+
+    if opts.standalone && opts.figure {
+        return Err("--standalone and --figure cannot be used together".to_string());
+    }
+
+    return Ok(opts);
+}
+
+/// Rounds every decimal number in the text to the given number of decimal places, dropping trailing zeros
+fn round_numbers(s: &str, precision: usize) -> String {
+    let re = Regex::new(r"-?\d+\.\d+").unwrap();
+    re.replace_all(s, |caps: &Captures| {
+        let x: f64 = caps[0].parse().unwrap();
+        let st = format!("{:.*}", precision, x);
+        let st = if st.contains(".") {
+            st.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            st
+        };
+
+        // Small negative numbers can round to -0
+        if st == "-0" {
+            return "0".to_string();
+        }
+        st
+    }).to_string()
+}
+
+/// Runs every command in the script and returns the compiled figure
+fn compile(script: &str, opts: &Options) -> Result<String, String> {
+    let mut fc = FigureComplex::new();
+
+    for (i, line) in script.lines().enumerate() {
+        let cmd = line.trim();
+        if cmd.len() == 0 {
+            continue;
+        }
+
+        fc.draw_with_text(cmd.to_string()).map_err(|e| {
+            format!("line {}: {}", i + 1, e.msg)
+        })?;
+    }
+
+    if let Some(caption) = &opts.caption {
+        fc.figure_mut().set_caption(caption.as_str());
+    }
+
+    if let Some(label) = &opts.label {
+        fc.figure_mut().set_label(label.as_str());
+    }
+
+    let output = match opts.format {
+        OutputFormat::Tikz => {
+            let options = TikzOutputOptions {
+                precision: opts.precision.or(TikzOutputOptions::default().precision),
+                unit: opts.unit,
+                unit_suffix: opts.unit_suffix,
+                ..Default::default()
+            };
+
+            let mut fig = fc.figure().to_tikz().options(options);
+            match fc.figure().bounding_box() {
+                Some(bb) if opts.bounding_box => fig = fig.use_as_bounding_box(bb),
+                _ => ()
+            };
+
+            if opts.standalone {
+                fig.standalone()
+            } else if opts.figure {
+                fig.figure_environment()
+            } else {
+                fig.output()
+            }
+        },
+        OutputFormat::Svg => {
+            let svg = fc.figure().to_svg().output();
+            match opts.precision {
+                Some(p) => round_numbers(&svg, p),
+                None => svg
+            }
+        }
+    };
+
+    return Ok(output);
+}
+
+fn run(opts: Options) -> Result<(), String> {
+    let script = match opts.input.as_deref() {
+        None | Some("-") => {
+            let mut s = String::new();
+            io::stdin().read_to_string(&mut s).map_err(|e| format!("Failed to read stdin: {e}"))?;
+            s
+        },
+        Some(path) => fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?
+    };
+
+    let output = compile(&script, &opts)?;
+
+    match opts.output.as_deref() {
+        Some(path) => fs::write(path, output).map_err(|e| format!("Failed to write {path}: {e}"))?,
+        None => io::stdout().write_all(output.as_bytes()).map_err(|e| format!("Failed to write to stdout: {e}"))?
+    };
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let opts = match parse_args(std::env::args().skip(1)) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("tikzpaint: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    if opts.help {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    if opts.verbose {
+        set_logger(|x| eprintln!("{x}"));
+    }
+
+    if let Err(e) = run(opts) {
+        eprintln!("tikzpaint: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    return ExitCode::SUCCESS;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Result<Options, String> {
+        parse_args(s.split_whitespace().map(|x| x.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let opts = args("-f svg --precision 2 -s in.txt").unwrap();
+        assert_eq!(opts.format, OutputFormat::Svg);
+        assert_eq!(opts.precision, Some(2));
+        assert!(opts.standalone);
+        assert_eq!(opts.input.as_deref(), Some("in.txt"));
+
+        assert!(args("-f pdf").is_err());
+        assert!(args("--precision").is_err());
+        assert!(args("a.txt b.txt").is_err());
+        assert!(args("--standalone --figure").is_err());
+        assert!(args("--unit km").is_err());
+        assert_eq!(args("--unit mm").unwrap().unit, TikzUnit::Mm);
+    }
+
+    #[test]
+    fn test_round_numbers() {
+        assert_eq!(round_numbers("(0.333333, 2.5) circle (0.8pt)", 2), "(0.33, 2.5) circle (0.8pt)");
+        assert_eq!(round_numbers("(-0.0001, 1.999)", 2), "(0, 2)");
+        assert_eq!(round_numbers("(3, 5)", 2), "(3, 5)");
+    }
+
+    #[test]
+    fn test_compile() {
+        let opts = args("").unwrap();
+        let out = compile("point(3, 5)\n\npoint(1, 2)\n", &opts).unwrap();
+        assert!(out.contains("\\filldraw[color=black] (3, 5) circle (0.8pt);"), "{out}");
+        assert!(out.contains("\\filldraw[color=black] (1, 2) circle (0.8pt);"), "{out}");
+
+        let opts = args("-p 2 --unit pt --unit-suffix").unwrap();
+        let out = compile("point(1/3, 1)\n", &opts).unwrap();
+        assert!(out.contains("(0.33pt, 1pt)"), "{out}");
+
+        let opts = args("--figure --caption Points --label fig:points").unwrap();
+        let out = compile("point(3, 5)\n", &opts).unwrap();
+        assert!(out.starts_with("\\begin{figure}"), "{out}");
+        assert!(out.contains("\\caption{Points}\n    \\label{fig:points}"), "{out}");
+
+        let opts = args("--bounding-box").unwrap();
+        let out = compile("point(0, 0)\npoint(2, 1)\n", &opts).unwrap();
+        assert!(out.contains("\\useasboundingbox (-0.0281, -0.0281) rectangle (2.0281, 1.0281);"), "{out}");
+
+        let err = compile("point(3, 5)\npoint(3, \n", &opts).unwrap_err();
+        assert!(err.starts_with("line 2:"), "{err}");
+    }
+}
@@ -1,12 +1,40 @@
 mod util;
 mod tikz_renderer;
+mod preamble;
+mod tikz_options;
+mod svg_renderer;
+#[cfg(feature = "web")]
 mod html_canvas;
+mod logging;
+mod render_target;
+mod stroke_style;
+mod fill_style;
+mod color;
+mod recording_canvas;
 mod transform;
 mod coordinates;
+mod bounding_box;
+mod position;
+mod spatial_index;
 pub mod calc;
 
 pub use tikz_renderer::*;
+pub use preamble::*;
+pub use tikz_options::*;
+pub use svg_renderer::*;
 pub use util::*;
+#[cfg(feature = "web")]
 pub use html_canvas::*;
+pub use logging::{Logger, set_logger, log_message};
+pub(crate) use logging::log;
+pub use render_target::*;
+pub use stroke_style::*;
+pub use fill_style::*;
+pub use color::*;
+pub use recording_canvas::*;
 pub use transform::*;
 pub use coordinates::Coordinates;
+pub use bounding_box::BoundingBox;
+pub use position::*;
+pub use spatial_index::SpatialIndex;
+pub use calc::{Affine2, ParametricCurve, LineSegment, QuadraticBezier, CubicBezier, EllipticArc, FillRule};
@@ -0,0 +1,217 @@
+//! An axis aligned rectangle on the plane, used to describe the extent of objects
+
+use crate::core::Coordinates;
+use crate::core::calc::is_zero;
+
+fn max(a: f64, b: f64) -> f64 {
+    if a > b {a} else {b}
+}
+
+fn min(a: f64, b: f64) -> f64 {
+    if a < b {a} else {b}
+}
+
+/// Returns the roots of a t^2 + b t + c strictly between 0 and 1. Falls back to the linear equation when a is zero
+fn roots_in_unit_interval(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let roots = if is_zero(a) {
+        if is_zero(b) { vec![] } else { vec![-c / b] }
+    } else {
+        let disc = b * b - 4. * a * c;
+        if disc < 0. {
+            vec![]
+        } else {
+            let sq = disc.sqrt();
+            vec![(-b + sq) / (2. * a), (-b - sq) / (2. * a)]
+        }
+    };
+
+    roots.into_iter().filter(|t| *t > 0. && *t < 1.).collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BoundingBox {
+    min: Coordinates,
+    max: Coordinates,
+}
+
+impl BoundingBox {
+    /// Creates the smallest bounding box containing both corners
+    pub fn new(corner_1: Coordinates, corner_2: Coordinates) -> Self {
+        Self {
+            min: Coordinates::new(min(corner_1[0], corner_2[0]), min(corner_1[1], corner_2[1])),
+            max: Coordinates::new(max(corner_1[0], corner_2[0]), max(corner_1[1], corner_2[1])),
+        }
+    }
+
+    /// Creates a degenerate bounding box that contains a single point
+    pub fn from_point(p: Coordinates) -> Self {
+        Self::new(p, p)
+    }
+
+    /// Creates the smallest bounding box containing every point. Returns None if there are no points
+    pub fn from_points(v: &[Coordinates]) -> Option<Self> {
+        let mut it = v.iter();
+        let first = Self::from_point(*it.next()?);
+        Some(it.fold(first, |bb, p| bb.include(*p)))
+    }
+
+    /// Creates the smallest bounding box containing the quadratic Bezier curve. Besides the endpoints, the curve
+    /// can only stick out where its derivative is zero in x or in y
+    pub fn from_quadratic(p0: Coordinates, p1: Coordinates, p2: Coordinates) -> Self {
+        let mut bb = Self::new(p0, p2);
+        for i in 0..2 {
+            let denom = p0[i] - 2. * p1[i] + p2[i];
+            if is_zero(denom) {
+                continue;
+            }
+
+            let t = (p0[i] - p1[i]) / denom;
+            if t > 0. && t < 1. {
+                let s = 1. - t;
+                bb = bb.include(p0 * (s * s) + p1 * (2. * s * t) + p2 * (t * t));
+            }
+        }
+        return bb;
+    }
+
+    /// Creates the smallest bounding box containing the cubic Bezier curve, using the roots of the derivative in x and in y
+    pub fn from_cubic(p0: Coordinates, p1: Coordinates, p2: Coordinates, p3: Coordinates) -> Self {
+        let mut bb = Self::new(p0, p3);
+        for i in 0..2 {
+            // The derivative divided by 3 is a t^2 + b t + c
+            let a = -p0[i] + 3. * p1[i] - 3. * p2[i] + p3[i];
+            let b = 2. * (p0[i] - 2. * p1[i] + p2[i]);
+            let c = p1[i] - p0[i];
+
+            for t in roots_in_unit_interval(a, b, c) {
+                let s = 1. - t;
+                bb = bb.include(p0 * (s * s * s) + p1 * (3. * s * s * t) + p2 * (3. * s * t * t) + p3 * (t * t * t));
+            }
+        }
+        return bb;
+    }
+
+    /// Bottom left corner
+    pub fn min(&self) -> Coordinates {
+        self.min
+    }
+
+    /// Top right corner
+    pub fn max(&self) -> Coordinates {
+        self.max
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max[0] - self.min[0]
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max[1] - self.min[1]
+    }
+
+    /// Returns the smallest bounding box containing both self and the point
+    pub fn include(&self, p: Coordinates) -> Self {
+        self.union(&Self::from_point(p))
+    }
+
+    /// Returns the smallest bounding box containing both bounding boxes
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: Coordinates::new(min(self.min[0], other.min[0]), min(self.min[1], other.min[1])),
+            max: Coordinates::new(max(self.max[0], other.max[0]), max(self.max[1], other.max[1])),
+        }
+    }
+
+    pub fn center(&self) -> Coordinates {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Returns the distance from p to the closest point in the bounding box, which is zero if p is inside
+    pub fn distance_to(&self, p: Coordinates) -> f64 {
+        let dx = max(max(self.min[0] - p[0], p[0] - self.max[0]), 0.);
+        let dy = max(max(self.min[1] - p[1], p[1] - self.max[1]), 0.);
+        dx.hypot(dy)
+    }
+
+    /// Returns the overlap of the two bounding boxes, or None if they do not overlap
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let lo = Coordinates::new(max(self.min[0], other.min[0]), max(self.min[1], other.min[1]));
+        let hi = Coordinates::new(min(self.max[0], other.max[0]), min(self.max[1], other.max[1]));
+        if lo[0] > hi[0] || lo[1] > hi[1] {
+            return None;
+        }
+        Some(Self { min: lo, max: hi })
+    }
+
+    /// Returns true if the other bounding box lies entirely inside this one
+    pub fn contains(&self, other: &Self) -> bool {
+        self.min[0] <= other.min[0] && self.min[1] <= other.min[1] && other.max[0] <= self.max[0] && other.max[1] <= self.max[1]
+    }
+
+    /// Grows the bounding box by 'margin' in every direction
+    pub fn expand(&self, margin: f64) -> Self {
+        let m = Coordinates::new(margin, margin);
+        Self {
+            min: self.min - m,
+            max: self.max + m,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::calc;
+
+    #[test]
+    fn test_new() {
+        let bb = BoundingBox::new(Coordinates::new(3, -1), Coordinates::new(1, 2));
+        assert_eq!(bb.min(), Coordinates::new(1, -1));
+        assert_eq!(bb.max(), Coordinates::new(3, 2));
+        assert_eq!(bb.width(), 2.);
+        assert_eq!(bb.height(), 3.);
+    }
+
+    #[test]
+    fn test_union() {
+        let bb = BoundingBox::from_points(&[Coordinates::new(0, 0), Coordinates::new(1, 1)]).unwrap();
+        let bb2 = bb.union(&BoundingBox::from_point(Coordinates::new(-2, 0.5)));
+        assert_eq!(bb2, BoundingBox::new(Coordinates::new(-2, 0), Coordinates::new(1, 1)));
+        assert_eq!(BoundingBox::from_points(&[]), None);
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = BoundingBox::new(Coordinates::new(0, 0), Coordinates::new(2, 2));
+        let b = BoundingBox::new(Coordinates::new(1, -1), Coordinates::new(3, 1));
+        assert_eq!(a.intersection(&b), Some(BoundingBox::new(Coordinates::new(1, 0), Coordinates::new(2, 1))));
+        assert_eq!(a.intersection(&BoundingBox::from_point(Coordinates::new(3, 3))), None);
+        assert!(a.contains(&BoundingBox::new(Coordinates::new(0, 1), Coordinates::new(1, 2))));
+        assert!(!a.contains(&b));
+    }
+
+    #[test]
+    fn test_distance() {
+        let bb = BoundingBox::new(Coordinates::new(0, 0), Coordinates::new(2, 1));
+        assert_eq!(bb.center(), Coordinates::new(1, 0.5));
+        assert_eq!(bb.distance_to(Coordinates::new(1, 0.5)), 0.);
+        assert_eq!(bb.distance_to(Coordinates::new(1, 3)), 2.);
+        assert_eq!(bb.distance_to(Coordinates::new(5, 5)), 5.);
+    }
+
+    #[test]
+    fn test_bezier() {
+        // The top of the parabola is halfway between the endpoints, at half the height of the control point
+        let bb = BoundingBox::from_quadratic(Coordinates::new(0, 0), Coordinates::new(1, 2), Coordinates::new(2, 0));
+        assert_eq!(bb, BoundingBox::new(Coordinates::new(0, 0), Coordinates::new(2, 1)));
+
+        // A symmetric cubic reaches 3/4 of the height of its control points
+        let bb = BoundingBox::from_cubic(Coordinates::new(0, 0), Coordinates::new(0, 1), Coordinates::new(1, 1), Coordinates::new(1, 0));
+        assert_eq!(bb, BoundingBox::new(Coordinates::new(0, 0), Coordinates::new(1, 0.75)));
+
+        // This one overshoots the endpoints in x on both sides
+        let bb = BoundingBox::from_cubic(Coordinates::new(0, 0), Coordinates::new(-1, 1), Coordinates::new(2, 1), Coordinates::new(1, 0));
+        assert!(bb.min()[0] < 0. && bb.max()[0] > 1. && bb.min()[0] > -1.);
+        assert!(calc::eq(&(bb.min()[0] + bb.max()[0]), &1.));
+    }
+}
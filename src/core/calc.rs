@@ -1,4 +1,9 @@
 mod hiya;
+mod affine;
+mod geometry;
+
+pub use affine::*;
+pub use geometry::*;
 
 pub const EPS: f64 = 1e-10;
 
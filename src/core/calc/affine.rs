@@ -0,0 +1,231 @@
+//! Affine transformations of the plane, which are a linear map followed by a translation
+
+use std::fmt::Debug;
+use std::ops::Mul;
+
+use crate::core::Coordinates;
+use crate::core::calc::*;
+
+/// Maps (x, y) to (a x + b y + tx, c x + d y + ty)
+#[derive(Clone, Copy)]
+pub struct Affine2 {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub tx: f64,
+    pub ty: f64,
+}
+
+impl Affine2 {
+    pub fn new(a: f64, b: f64, c: f64, d: f64, tx: f64, ty: f64) -> Self {
+        Self { a, b, c, d, tx, ty }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(1., 0., 0., 1., 0., 0.)
+    }
+
+    pub fn translate(by: Coordinates) -> Self {
+        Self::new(1., 0., 0., 1., by[0], by[1])
+    }
+
+    /// Rotates counterclockwise around the origin. The angle is in radians
+    pub fn rotate(angle: f64) -> Self {
+        let (s, c) = angle.sin_cos();
+        Self::new(c, -s, s, c, 0., 0.)
+    }
+
+    /// Rotates counterclockwise around the center. The angle is in radians
+    pub fn rotate_around(center: Coordinates, angle: f64) -> Self {
+        Self::translate(center * -1.).then(&Self::rotate(angle)).then(&Self::translate(center))
+    }
+
+    /// Scales the x and y coordinates separately, about the origin
+    pub fn scale(sx: f64, sy: f64) -> Self {
+        Self::new(sx, 0., 0., sy, 0., 0.)
+    }
+
+    /// Maps (x, y) to (x + kx y, ky x + y)
+    pub fn shear(kx: f64, ky: f64) -> Self {
+        Self::new(1., kx, ky, 1., 0., 0.)
+    }
+
+    /// Reflects across the line through the origin going in the direction
+    pub fn reflect(direction: Coordinates) -> Self {
+        let t = 2. * direction[1].atan2(direction[0]);
+        let (s, c) = t.sin_cos();
+        Self::new(c, s, s, -c, 0., 0.)
+    }
+
+    /// Returns the transform that applies this transform first and then the other one
+    pub fn then(&self, other: &Affine2) -> Self {
+        *other * *self
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// Returns the inverse transform, or None if the transform squashes the plane onto a line or a point
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.determinant();
+        if is_zero(det) {
+            return None;
+        }
+
+        let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+        Some(Self::new(a, b, c, d, -(a * self.tx + b * self.ty), -(c * self.tx + d * self.ty)))
+    }
+
+    /// Transforms a point
+    pub fn apply(&self, p: Coordinates) -> Coordinates {
+        self.apply_vector(p) + self.translation()
+    }
+
+    /// Transforms a displacement, which is not affected by the translation
+    pub fn apply_vector(&self, v: Coordinates) -> Coordinates {
+        Coordinates::new(self.a * v[0] + self.b * v[1], self.c * v[0] + self.d * v[1])
+    }
+
+    pub fn translation(&self) -> Coordinates {
+        Coordinates::new(self.tx, self.ty)
+    }
+
+    /// Returns the transform without its translation
+    pub fn linear(&self) -> Self {
+        Self::new(self.a, self.b, self.c, self.d, 0., 0.)
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    /// True if lines parallel to the x-axis stay parallel to the x-axis, and the same for the y-axis
+    pub fn preserves_axes(&self) -> bool {
+        is_zero(self.b) && is_zero(self.c)
+    }
+
+    /// True if lines parallel to the x-axis become parallel to the y-axis and the other way round
+    pub fn swaps_axes(&self) -> bool {
+        is_zero(self.a) && is_zero(self.d)
+    }
+
+    /// Writes the transform as a scaling, then a counterclockwise rotation (in radians), then a shift, and
+    /// returns (shift, rotation, scale). Returns None if the transform stretches, shears or reflects
+    pub fn as_similarity(&self) -> Option<(Coordinates, f64, f64)> {
+        if !eq(&self.a, &self.d) || !eq(&self.b, &-self.c) {
+            return None;
+        }
+
+        let scale = self.a.hypot(self.c);
+        if is_zero(scale) {
+            return None;
+        }
+
+        Some((self.translation(), self.c.atan2(self.a), scale))
+    }
+
+    /// Writes the linear part as R(phi) diag(s1, s2) R(theta), where R is a counterclockwise rotation (in radians),
+    /// and returns (phi, s1, s2, theta). The first singular value s1 is at least |s2|, and s2 is negative if the transform reflects
+    pub fn decompose(&self) -> (f64, f64, f64, f64) {
+        let e = (self.a + self.d) / 2.;
+        let f = (self.a - self.d) / 2.;
+        let g = (self.c + self.b) / 2.;
+        let h = (self.c - self.b) / 2.;
+
+        let q = e.hypot(h);
+        let r = f.hypot(g);
+        let a1 = g.atan2(f);
+        let a2 = h.atan2(e);
+        ((a2 + a1) / 2., q + r, q - r, (a2 - a1) / 2.)
+    }
+}
+
+/// Multiplication is composition, so (s * t) applies t first and then s
+impl Mul for Affine2 {
+    type Output = Affine2;
+
+    fn mul(self, o: Self) -> Self::Output {
+        Affine2::new(
+            self.a * o.a + self.b * o.c,
+            self.a * o.b + self.b * o.d,
+            self.c * o.a + self.d * o.c,
+            self.c * o.b + self.d * o.d,
+            self.a * o.tx + self.b * o.ty + self.tx,
+            self.c * o.tx + self.d * o.ty + self.ty
+        )
+    }
+}
+
+impl PartialEq for Affine2 {
+    /// Every entry is the same within a certain threshold
+    fn eq(&self, o: &Self) -> bool {
+        eq(&self.a, &o.a) && eq(&self.b, &o.b) && eq(&self.c, &o.c) && eq(&self.d, &o.d) && eq(&self.tx, &o.tx) && eq(&self.ty, &o.ty)
+    }
+}
+
+impl Debug for Affine2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[[{}, {}, {}], [{}, {}, {}]]", self.a, self.b, self.tx, self.c, self.d, self.ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_apply() {
+        let p = Coordinates::new(1, 0);
+        assert_eq!(Affine2::rotate(FRAC_PI_2).apply(p), Coordinates::new(0, 1));
+        assert_eq!(Affine2::translate(Coordinates::new(1, 2)).apply(p), Coordinates::new(2, 2));
+        assert_eq!(Affine2::scale(2., 3.).apply(Coordinates::new(1, 1)), Coordinates::new(2, 3));
+        assert_eq!(Affine2::shear(1., 0.).apply(Coordinates::new(1, 1)), Coordinates::new(2, 1));
+        assert_eq!(Affine2::reflect(Coordinates::new(1, 1)).apply(p), Coordinates::new(0, 1));
+        assert_eq!(Affine2::rotate_around(Coordinates::new(1, 1), FRAC_PI_2).apply(p), Coordinates::new(2, 1));
+
+        // Vectors do not move with the translation
+        assert_eq!(Affine2::translate(Coordinates::new(1, 2)).apply_vector(p), p);
+    }
+
+    #[test]
+    fn test_compose() {
+        let r = Affine2::rotate(FRAC_PI_2);
+        let t = Affine2::translate(Coordinates::new(1, 0));
+        let p = Coordinates::new(1, 0);
+        assert_eq!(r.then(&t).apply(p), Coordinates::new(1, 1));
+        assert_eq!((r * t).apply(p), Coordinates::new(0, 2));
+    }
+
+    #[test]
+    fn test_invert() {
+        let tf = Affine2::rotate(0.3).then(&Affine2::shear(0.5, 0.)).then(&Affine2::translate(Coordinates::new(-1, 2)));
+        assert_eq!(tf * tf.invert().unwrap(), Affine2::identity());
+        assert_eq!(tf.invert().unwrap() * tf, Affine2::identity());
+        assert!(Affine2::scale(1., 0.).invert().is_none());
+    }
+
+    #[test]
+    fn test_similarity() {
+        let tf = Affine2::scale(2., 2.).then(&Affine2::rotate(FRAC_PI_2)).then(&Affine2::translate(Coordinates::new(1, 0)));
+        let (shift, angle, scale) = tf.as_similarity().unwrap();
+        assert_eq!(shift, Coordinates::new(1, 0));
+        assert!(eq(&angle, &FRAC_PI_2) && eq(&scale, &2.));
+
+        assert!(Affine2::scale(1., 2.).as_similarity().is_none());
+        assert!(Affine2::reflect(Coordinates::new(1, 0)).as_similarity().is_none());
+    }
+
+    #[test]
+    fn test_decompose() {
+        for tf in [Affine2::shear(0.7, 0.2), Affine2::reflect(Coordinates::new(1, 2)).then(&Affine2::scale(3., 1.)), Affine2::rotate(2.)] {
+            let (phi, s1, s2, theta) = tf.decompose();
+            let m = Affine2::rotate(phi) * Affine2::scale(s1, s2) * Affine2::rotate(theta);
+            assert_eq!(m, tf.linear());
+            assert!(s1 >= s2.abs());
+            assert_eq!(s2 < 0., tf.determinant() < 0.);
+        }
+    }
+}
@@ -0,0 +1,546 @@
+//! Bezier curves and elliptic arcs as parametric curves on t from 0 to 1. This is the math behind the curved segments of paths
+
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::core::{BoundingBox, Coordinates};
+use crate::core::calc::*;
+
+/// Nodes and weights of the 5 point Gauss-Legendre quadrature on [-1, 1]
+const GAUSS_LEGENDRE: [(f64, f64); 5] = [
+    (0., 0.5688888888888889),
+    (-0.5384693101056831, 0.4786286704993665),
+    (0.5384693101056831, 0.4786286704993665),
+    (-0.906179845938664, 0.2369268850561891),
+    (0.906179845938664, 0.2369268850561891),
+];
+
+/// How many times an interval can be halved when computing lengths or flattening curves
+const MAX_DEPTH: usize = 16;
+
+fn dot(a: Coordinates, b: Coordinates) -> f64 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+/// Returns the distance from p to the line segment between a and b
+pub fn distance_to_segment(p: Coordinates, a: Coordinates, b: Coordinates) -> f64 {
+    LineSegment::new(a, b).closest_point(p).1
+}
+
+/// Decides which points are inside a shape from the number of times its outline winds around them
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FillRule {
+    /// Inside if the outline winds around the point at all. This is the default in Tikz, svg and the Html canvas
+    NonZero,
+
+    /// Inside if the outline crosses a ray from the point an odd number of times
+    EvenOdd,
+}
+
+impl FillRule {
+    pub fn contains(&self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// Returns the number of times the closed polygon winds counterclockwise around p. The last corner is joined back to the first
+pub fn winding_number(p: Coordinates, polygon: &[Coordinates]) -> i32 {
+    let mut winding = 0;
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+
+        // Which side of the edge p is on, positive if p is to the left of a -> b
+        let side = (b[0] - a[0]) * (p[1] - a[1]) - (p[0] - a[0]) * (b[1] - a[1]);
+        if a[1] <= p[1] && b[1] > p[1] && side > 0. {
+            winding += 1;
+        }
+        else if a[1] > p[1] && b[1] <= p[1] && side < 0. {
+            winding -= 1;
+        }
+    }
+    return winding;
+}
+
+/// A curve going from point(0) to point(1)
+pub trait ParametricCurve {
+    /// Returns the point on the curve at t
+    fn point(&self, t: f64) -> Coordinates;
+
+    /// Returns the derivative of the curve with respect to t
+    fn derivative(&self, t: f64) -> Coordinates;
+
+    /// Cuts the curve at t into the part before and the part after. Both parts are parametrized from 0 to 1 again
+    fn split(&self, t: f64) -> (Self, Self) where Self: Sized;
+
+    fn start(&self) -> Coordinates {
+        self.point(0.)
+    }
+
+    fn end(&self) -> Coordinates {
+        self.point(1.)
+    }
+
+    /// Returns the length of the curve between the two parameters. This is integrated numerically, halving the
+    /// interval until the estimate settles
+    fn length_between(&self, t0: f64, t1: f64) -> f64 {
+        let gauss = |a: f64, b: f64| {
+            let (m, h) = ((a + b) / 2., (b - a) / 2.);
+            GAUSS_LEGENDRE.iter().map(|(x, w)| w * self.derivative(m + h * x).magnitude()).sum::<f64>() * h
+        };
+
+        fn refine(gauss: &dyn Fn(f64, f64) -> f64, a: f64, b: f64, whole: f64, depth: usize) -> f64 {
+            let m = (a + b) / 2.;
+            let (left, right) = (gauss(a, m), gauss(m, b));
+            if depth >= MAX_DEPTH || (left + right - whole).abs() <= EPS {
+                return left + right;
+            }
+            refine(gauss, a, m, left, depth + 1) + refine(gauss, m, b, right, depth + 1)
+        }
+
+        refine(&gauss, t0, t1, gauss(t0, t1), 0)
+    }
+
+    fn length(&self) -> f64 {
+        self.length_between(0., 1.)
+    }
+
+    /// Returns the parameter of the point that is 'distance' along the curve from the start. The distance is clamped to the length of the curve
+    fn t_at_length(&self, distance: f64) -> f64 {
+        let total = self.length();
+        if distance <= 0. || is_zero(total) {
+            return 0.;
+        }
+        if distance >= total {
+            return 1.;
+        }
+
+        // Newton's method, falling back to bisection whenever a step leaves the bracket
+        let (mut lo, mut hi) = (0., 1.);
+        let mut t = distance / total;
+        for _ in 0..50 {
+            let err = self.length_between(0., t) - distance;
+            if err.abs() <= EPS {
+                break;
+            }
+
+            if err > 0. { hi = t; } else { lo = t; }
+            let speed = self.derivative(t).magnitude();
+            let next = if is_zero(speed) { (lo + hi) / 2. } else { t - err / speed };
+            t = if next > lo && next < hi { next } else { (lo + hi) / 2. };
+        }
+        return t;
+    }
+
+    /// Returns the point that is 'distance' along the curve from the start
+    fn point_at_length(&self, distance: f64) -> Coordinates {
+        self.point(self.t_at_length(distance))
+    }
+
+    /// Returns the parameter of the point on the curve closest to p, and the distance to it. The curve is sampled to
+    /// find the closest stretch, which is then narrowed down with a golden section search
+    fn closest_point(&self, p: Coordinates) -> (f64, f64) {
+        const SAMPLES: usize = 32;
+        let dist = |t: f64| (self.point(t) - p).magnitude();
+
+        let best = (0..=SAMPLES).min_by(|i, j| dist(*i as f64 / SAMPLES as f64).total_cmp(&dist(*j as f64 / SAMPLES as f64))).unwrap();
+        let (mut lo, mut hi) = (best.saturating_sub(1) as f64 / SAMPLES as f64, (best + 1).min(SAMPLES) as f64 / SAMPLES as f64);
+
+        let r = (5f64.sqrt() - 1.) / 2.;
+        while hi - lo > EPS {
+            let (a, b) = (hi - r * (hi - lo), lo + r * (hi - lo));
+            if dist(a) < dist(b) { hi = b; } else { lo = a; }
+        }
+
+        let t = (lo + hi) / 2.;
+        return (t, dist(t));
+    }
+
+    /// Approximates the curve with line segments, such that the curve never gets further than 'tolerance' from the segments.
+    /// Returns the corners of the polyline, including both ends of the curve
+    fn flatten(&self, tolerance: f64) -> Vec<Coordinates> {
+        fn subdivide<C: ParametricCurve + ?Sized>(c: &C, t0: f64, t1: f64, tolerance: f64, depth: usize, out: &mut Vec<Coordinates>) {
+            let (a, b) = (c.point(t0), c.point(t1));
+
+            // A single midpoint misses curves shaped like an S, so check the quarters too
+            let flat = [0.25, 0.5, 0.75].iter()
+                .all(|s| distance_to_segment(c.point(t0 + (t1 - t0) * s), a, b) <= tolerance);
+
+            if flat || depth >= MAX_DEPTH {
+                out.push(b);
+                return;
+            }
+
+            let m = (t0 + t1) / 2.;
+            subdivide(c, t0, m, tolerance, depth + 1, out);
+            subdivide(c, m, t1, tolerance, depth + 1, out);
+        }
+
+        let mut out = vec![self.start()];
+        subdivide(self, 0., 1., tolerance, 0, &mut out);
+        return out;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LineSegment {
+    pub from: Coordinates,
+    pub to: Coordinates,
+}
+
+impl LineSegment {
+    pub fn new(from: Coordinates, to: Coordinates) -> Self {
+        Self { from, to }
+    }
+}
+
+impl ParametricCurve for LineSegment {
+    fn point(&self, t: f64) -> Coordinates {
+        self.from + (self.to - self.from) * t
+    }
+
+    fn derivative(&self, _t: f64) -> Coordinates {
+        self.to - self.from
+    }
+
+    fn split(&self, t: f64) -> (Self, Self) {
+        let m = self.point(t);
+        (Self::new(self.from, m), Self::new(m, self.to))
+    }
+
+    fn length_between(&self, t0: f64, t1: f64) -> f64 {
+        (self.to - self.from).magnitude() * (t1 - t0)
+    }
+
+    fn closest_point(&self, p: Coordinates) -> (f64, f64) {
+        let ab = self.to - self.from;
+        let len2 = dot(ab, ab);
+        let t = if is_zero(len2) { 0. } else { (dot(p - self.from, ab) / len2).clamp(0., 1.) };
+        (t, (p - self.point(t)).magnitude())
+    }
+
+    fn flatten(&self, _tolerance: f64) -> Vec<Coordinates> {
+        vec![self.from, self.to]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct QuadraticBezier {
+    pub p0: Coordinates,
+    pub p1: Coordinates,
+    pub p2: Coordinates,
+}
+
+impl QuadraticBezier {
+    pub fn new(p0: Coordinates, p1: Coordinates, p2: Coordinates) -> Self {
+        Self { p0, p1, p2 }
+    }
+
+    /// Returns the same curve as a cubic Bezier curve
+    pub fn to_cubic(&self) -> CubicBezier {
+        CubicBezier::new(self.p0, self.p0 + (self.p1 - self.p0) * (2. / 3.), self.p2 + (self.p1 - self.p2) * (2. / 3.), self.p2)
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::from_quadratic(self.p0, self.p1, self.p2)
+    }
+}
+
+impl ParametricCurve for QuadraticBezier {
+    fn point(&self, t: f64) -> Coordinates {
+        let s = 1. - t;
+        self.p0 * (s * s) + self.p1 * (2. * s * t) + self.p2 * (t * t)
+    }
+
+    fn derivative(&self, t: f64) -> Coordinates {
+        ((self.p1 - self.p0) * (1. - t) + (self.p2 - self.p1) * t) * 2.
+    }
+
+    /// De Casteljau's algorithm
+    fn split(&self, t: f64) -> (Self, Self) {
+        let a = self.p0 + (self.p1 - self.p0) * t;
+        let b = self.p1 + (self.p2 - self.p1) * t;
+        let m = a + (b - a) * t;
+        (Self::new(self.p0, a, m), Self::new(m, b, self.p2))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CubicBezier {
+    pub p0: Coordinates,
+    pub p1: Coordinates,
+    pub p2: Coordinates,
+    pub p3: Coordinates,
+}
+
+impl CubicBezier {
+    pub fn new(p0: Coordinates, p1: Coordinates, p2: Coordinates, p3: Coordinates) -> Self {
+        Self { p0, p1, p2, p3 }
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::from_cubic(self.p0, self.p1, self.p2, self.p3)
+    }
+}
+
+impl ParametricCurve for CubicBezier {
+    fn point(&self, t: f64) -> Coordinates {
+        let s = 1. - t;
+        self.p0 * (s * s * s) + self.p1 * (3. * s * s * t) + self.p2 * (3. * s * t * t) + self.p3 * (t * t * t)
+    }
+
+    fn derivative(&self, t: f64) -> Coordinates {
+        let s = 1. - t;
+        ((self.p1 - self.p0) * (s * s) + (self.p2 - self.p1) * (2. * s * t) + (self.p3 - self.p2) * (t * t)) * 3.
+    }
+
+    /// De Casteljau's algorithm
+    fn split(&self, t: f64) -> (Self, Self) {
+        let lerp = |a: Coordinates, b: Coordinates| a + (b - a) * t;
+        let (a, b, c) = (lerp(self.p0, self.p1), lerp(self.p1, self.p2), lerp(self.p2, self.p3));
+        let (d, e) = (lerp(a, b), lerp(b, c));
+        let m = lerp(d, e);
+        (Self::new(self.p0, a, d, m), Self::new(m, e, c, self.p3))
+    }
+}
+
+/// Part of an ellipse whose x-axis is rotated by 'rotation' counterclockwise. The curve goes from the start angle
+/// to the end angle, clockwise if the end angle is smaller. All angles are in radians
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct EllipticArc {
+    pub center: Coordinates,
+    pub x_radius: f64,
+    pub y_radius: f64,
+    pub rotation: f64,
+    pub start_angle: f64,
+    pub end_angle: f64,
+}
+
+impl EllipticArc {
+    pub fn new(center: Coordinates, x_radius: f64, y_radius: f64, rotation: f64, start_angle: f64, end_angle: f64) -> Self {
+        Self { center, x_radius, y_radius, rotation, start_angle, end_angle }
+    }
+
+    fn angle(&self, t: f64) -> f64 {
+        self.start_angle + (self.end_angle - self.start_angle) * t
+    }
+
+    /// Returns the point on the whole ellipse at the angle
+    fn point_at_angle(&self, angle: f64) -> Coordinates {
+        self.center + Affine2::rotate(self.rotation).apply_vector(Coordinates::new(self.x_radius * angle.cos(), self.y_radius * angle.sin()))
+    }
+
+    /// Returns the derivative of the ellipse with respect to the angle
+    fn tangent_at_angle(&self, angle: f64) -> Coordinates {
+        Affine2::rotate(self.rotation).apply_vector(Coordinates::new(-self.x_radius * angle.sin(), self.y_radius * angle.cos()))
+    }
+
+    /// Returns the smallest bounding box of the arc. Apart from the endpoints, the arc can only stick out at the
+    /// points where the ellipse is furthest left, right, up or down
+    pub fn bounding_box(&self) -> BoundingBox {
+        let (s, c) = self.rotation.sin_cos();
+        let x_extreme = (-self.y_radius * s).atan2(self.x_radius * c);
+        let y_extreme = (self.y_radius * c).atan2(self.x_radius * s);
+
+        let (lo, hi) = if self.start_angle < self.end_angle { (self.start_angle, self.end_angle) } else { (self.end_angle, self.start_angle) };
+        let mut bb = BoundingBox::new(self.start(), self.end());
+
+        for angle in [x_extreme, x_extreme + PI, y_extreme, y_extreme + PI] {
+            // The first angle equal to this one (modulo a full turn) that comes after the start of the sweep
+            let angle = angle + TAU * ((lo - angle) / TAU).ceil();
+            if angle <= hi {
+                bb = bb.include(self.point_at_angle(angle));
+            }
+        }
+
+        return bb;
+    }
+
+    /// Approximates the arc with cubic Bezier curves, each covering at most a quarter turn. The error is below
+    /// 0.03% of the radius for circles
+    pub fn to_cubics(&self) -> Vec<CubicBezier> {
+        let sweep = self.end_angle - self.start_angle;
+        let n = ((sweep.abs() / FRAC_PI_2).ceil() as usize).max(1);
+        let step = sweep / n as f64;
+
+        // The control points go along the tangent by 4/3 tan(step/4), which makes the midpoint of each piece exact
+        let k = 4. / 3. * (step / 4.).tan();
+
+        (0..n).map(|i| {
+            let a = self.start_angle + step * i as f64;
+            let b = a + step;
+            let (p0, p3) = (self.point_at_angle(a), self.point_at_angle(b));
+            CubicBezier::new(p0, p0 + self.tangent_at_angle(a) * k, p3 - self.tangent_at_angle(b) * k, p3)
+        }).collect()
+    }
+}
+
+impl ParametricCurve for EllipticArc {
+    fn point(&self, t: f64) -> Coordinates {
+        self.point_at_angle(self.angle(t))
+    }
+
+    fn derivative(&self, t: f64) -> Coordinates {
+        self.tangent_at_angle(self.angle(t)) * (self.end_angle - self.start_angle)
+    }
+
+    fn split(&self, t: f64) -> (Self, Self) {
+        let m = self.angle(t);
+        (Self { end_angle: m, ..*self }, Self { start_angle: m, ..*self })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() <= tolerance
+    }
+
+    fn s_curve() -> CubicBezier {
+        CubicBezier::new(Coordinates::new(0, 0), Coordinates::new(1, 2), Coordinates::new(2, -2), Coordinates::new(3, 0))
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let q = QuadraticBezier::new(Coordinates::new(0, 0), Coordinates::new(1, 2), Coordinates::new(2, 0));
+        assert_eq!(q.point(0.5), Coordinates::new(1, 1));
+        assert_eq!(q.derivative(0.5), Coordinates::new(2, 0));
+
+        // Raising the degree does not change the curve
+        let c = q.to_cubic();
+        for t in [0., 0.3, 0.5, 0.9, 1.] {
+            assert_eq!(c.point(t), q.point(t));
+            assert_eq!(c.derivative(t), q.derivative(t));
+        }
+
+        let arc = EllipticArc::new(Coordinates::new(1, 0), 2., 1., FRAC_PI_2, 0., PI);
+        assert_eq!(arc.start(), Coordinates::new(1, 2));
+        assert_eq!(arc.point(0.5), Coordinates::new(0, 0));
+        assert_eq!(arc.derivative(0.), Coordinates::new(-PI, 0));
+    }
+
+    #[test]
+    fn test_split() {
+        let c = s_curve();
+        let (a, b) = c.split(0.3);
+        assert_eq!(a.end(), c.point(0.3));
+        assert_eq!(a.point(0.5), c.point(0.15));
+        assert_eq!(b.point(0.5), c.point(0.65));
+
+        let q = QuadraticBezier::new(Coordinates::new(0, 0), Coordinates::new(1, 2), Coordinates::new(2, 0));
+        let (a, b) = q.split(0.5);
+        assert_eq!((a.end(), b.start()), (q.point(0.5), q.point(0.5)));
+        assert_eq!(b.point(0.5), q.point(0.75));
+
+        let arc = EllipticArc::new(Coordinates::new(0, 0), 2., 1., 0.3, 1., -2.);
+        let (a, b) = arc.split(0.25);
+        assert_eq!(a.point(0.5), arc.point(0.125));
+        assert_eq!(b.point(1. / 3.), arc.point(0.5));
+    }
+
+    #[test]
+    fn test_length() {
+        let line = CubicBezier::new(Coordinates::new(0, 0), Coordinates::new(1, 0), Coordinates::new(2, 0), Coordinates::new(3, 4));
+        assert!(line.length() > 5.);
+
+        let circle = EllipticArc::new(Coordinates::new(0, 0), 2., 2., 0., 0., -TAU);
+        assert!(close(circle.length(), 4. * PI, 1e-9));
+
+        // Equal distances along a circle are equal angles
+        let t = circle.t_at_length(PI);
+        assert!(close(t, 0.25, 1e-9));
+        assert_eq!(circle.point_at_length(PI), Coordinates::new(0, -2));
+
+        // The halves of the curve at half its length have the same length
+        let c = s_curve();
+        let (a, b) = c.split(c.t_at_length(c.length() / 2.));
+        assert!(close(a.length(), b.length(), 1e-8));
+        assert_eq!(c.t_at_length(-1.), 0.);
+        assert_eq!(c.t_at_length(100.), 1.);
+    }
+
+    #[test]
+    fn test_flatten() {
+        let c = s_curve();
+        for tolerance in [0.1, 0.01, 0.001] {
+            let poly = c.flatten(tolerance);
+            assert_eq!(poly[0], c.start());
+            assert_eq!(poly[poly.len() - 1], c.end());
+
+            // Sample the curve and check every sample is close to the polyline
+            for i in 0..=200 {
+                let p = c.point(i as f64 / 200.);
+                let d = poly.windows(2).map(|w| distance_to_segment(p, w[0], w[1])).fold(f64::INFINITY, f64::min);
+                assert!(d <= tolerance, "{d} > {tolerance}");
+            }
+        }
+
+        // Smaller tolerances need more segments
+        assert!(c.flatten(0.001).len() > c.flatten(0.1).len());
+        assert_eq!(CubicBezier::new(Coordinates::new(0, 0), Coordinates::new(1, 0), Coordinates::new(2, 0), Coordinates::new(3, 0)).flatten(0.1).len(), 2);
+    }
+
+    #[test]
+    fn test_closest_point() {
+        let circle = EllipticArc::new(Coordinates::new(0, 0), 1., 1., 0., 0., TAU);
+        let (t, d) = circle.closest_point(Coordinates::new(0, 3));
+        assert!(close(t, 0.25, 1e-8) && close(d, 2., 1e-10));
+
+        // The closest point on the S is somewhere in the middle, not at a sample
+        let c = s_curve();
+        let (t, d) = c.closest_point(Coordinates::new(1.5, 0.3));
+        assert!(close((c.point(t) - Coordinates::new(1.5, 0.3)).magnitude(), d, 1e-12));
+        let dot = (c.point(t)[0] - 1.5) * c.derivative(t)[0] + (c.point(t)[1] - 0.3) * c.derivative(t)[1];
+        assert!(close(dot, 0., 1e-6), "{dot}");
+    }
+
+    #[test]
+    fn test_winding() {
+        let square = [Coordinates::new(0, 0), Coordinates::new(2, 0), Coordinates::new(2, 2), Coordinates::new(0, 2)];
+        assert_eq!(winding_number(Coordinates::new(1, 1), &square), 1);
+        assert_eq!(winding_number(Coordinates::new(3, 1), &square), 0);
+
+        // Going round twice, the inside counts for the nonzero rule but not for the even odd rule
+        let twice = [square, square].concat();
+        let w = winding_number(Coordinates::new(1, 1), &twice);
+        assert_eq!(w, 2);
+        assert!(FillRule::NonZero.contains(w) && !FillRule::EvenOdd.contains(w));
+
+        let reversed = square.iter().rev().cloned().collect::<Vec<Coordinates>>();
+        assert_eq!(winding_number(Coordinates::new(1, 1), &reversed), -1);
+    }
+
+    #[test]
+    fn test_arc_to_cubics() {
+        let arc = EllipticArc::new(Coordinates::new(1, 1), 3., 1., 0.5, 0.2, 4.);
+        let cubics = arc.to_cubics();
+        assert_eq!(cubics.len(), 3);
+        assert_eq!(cubics[0].start(), arc.start());
+        assert_eq!(cubics[2].end(), arc.end());
+
+        // Stays within a small fraction of the radius everywhere
+        for c in cubics.iter() {
+            for i in 0..=20 {
+                let p = c.point(i as f64 / 20.);
+                let local = Affine2::rotate(-arc.rotation).apply_vector(p - arc.center);
+                let r = (local[0] / arc.x_radius).hypot(local[1] / arc.y_radius);
+                assert!(close(r, 1., 1e-3), "{r}");
+            }
+        }
+
+        assert_eq!(EllipticArc::new(Coordinates::new(0, 0), 1., 1., 0., 0., -FRAC_PI_2).to_cubics().len(), 1);
+    }
+
+    #[test]
+    fn test_arc_bounding_box() {
+        let arc = EllipticArc::new(Coordinates::new(0, 0), 1., 1., 0., 0., PI);
+        assert_eq!(arc.bounding_box(), BoundingBox::new(Coordinates::new(-1, 0), Coordinates::new(1, 1)));
+
+        // Going the other way round covers the bottom half
+        let arc = EllipticArc::new(Coordinates::new(0, 0), 1., 1., 0., 0., -PI);
+        assert_eq!(arc.bounding_box(), BoundingBox::new(Coordinates::new(-1, -1), Coordinates::new(1, 0)));
+    }
+}
@@ -0,0 +1,368 @@
+//! Colors that behave like the ones in xcolor. A color can be written as an xcolor expression like red!40!white,
+//! and we keep the expression around so that the Tikz code reads the same as what the user wrote
+
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+use crate::core::*;
+
+/// The colors that xcolor always defines, in RGB
+const XCOLOR_COLORS: [(&str, u8, u8, u8); 19] = [
+    ("red", 255, 0, 0),
+    ("green", 0, 255, 0),
+    ("blue", 0, 0, 255),
+    ("cyan", 0, 255, 255),
+    ("magenta", 255, 0, 255),
+    ("yellow", 255, 255, 0),
+    ("black", 0, 0, 0),
+    ("white", 255, 255, 255),
+    ("darkgray", 64, 64, 64),
+    ("gray", 128, 128, 128),
+    ("lightgray", 191, 191, 191),
+    ("brown", 191, 128, 64),
+    ("lime", 191, 255, 0),
+    ("olive", 128, 128, 0),
+    ("orange", 255, 128, 0),
+    ("pink", 255, 191, 191),
+    ("purple", 191, 0, 64),
+    ("teal", 0, 128, 128),
+    ("violet", 128, 0, 128),
+];
+
+/// Some more names that people expect to work, with their svg values. xcolor does not know them
+/// without extra package options, so using one of them puts a \definecolor in the preamble
+const EXTRA_COLORS: [(&str, u8, u8, u8); 16] = [
+    ("navy", 0, 0, 128),
+    ("maroon", 128, 0, 0),
+    ("gold", 255, 215, 0),
+    ("silver", 192, 192, 192),
+    ("skyblue", 135, 206, 235),
+    ("indigo", 75, 0, 130),
+    ("crimson", 220, 20, 60),
+    ("coral", 255, 127, 80),
+    ("salmon", 250, 128, 114),
+    ("turquoise", 64, 224, 208),
+    ("forestgreen", 34, 139, 34),
+    ("chocolate", 210, 105, 30),
+    ("tan", 210, 180, 140),
+    ("khaki", 240, 230, 140),
+    ("beige", 245, 245, 220),
+    ("lavender", 230, 230, 250),
+];
+
+pub struct ColorError {
+    pub msg: String
+}
+
+impl ColorError {
+    fn new<S: Into<String>>(msg: S) -> Self {
+        Self {
+            msg: msg.into()
+        }
+    }
+}
+
+impl Display for ColorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid color: {}", self.msg)
+    }
+}
+
+impl Debug for ColorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (self as &dyn Display).fmt(f)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+
+    /// Opacity from 0 (invisible) to 1
+    pub alpha: f64,
+
+    /// The xcolor expression this color came from, which is what we write in the Tikz code
+    expr: Option<String>,
+
+    /// Names in the expression that xcolor does not know about
+    definitions: Vec<ColorDefinition>,
+}
+
+/// Two colors are the same if they look the same, no matter how they are written
+impl PartialEq for Color {
+    fn eq(&self, other: &Self) -> bool {
+        self.r == other.r && self.g == other.g && self.b == other.b && self.alpha == other.alpha
+    }
+}
+
+impl Color {
+    pub fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 1.)
+    }
+
+    pub fn rgba(r: u8, g: u8, b: u8, alpha: f64) -> Self {
+        Self {
+            r,
+            g,
+            b,
+            alpha: alpha.clamp(0., 1.),
+            expr: None,
+            definitions: vec![],
+        }
+    }
+
+    /// Hue is in degrees, saturation and lightness go from 0 to 1
+    pub fn hsl(h: f64, s: f64, l: f64) -> Self {
+        let s = s.clamp(0., 1.);
+        let l = l.clamp(0., 1.);
+        let c = (1. - (2. * l - 1.).abs()) * s;
+        Self::from_hue(h, c, l - c / 2.)
+    }
+
+    /// Hue is in degrees, saturation and brightness go from 0 to 1. This is the Hsb model of xcolor
+    pub fn hsb(h: f64, s: f64, b: f64) -> Self {
+        let s = s.clamp(0., 1.);
+        let b = b.clamp(0., 1.);
+        let c = b * s;
+        Self::from_hue(h, c, b - c)
+    }
+
+    /// The part shared by HSL and HSB, where c is the chroma and m is added to every channel
+    fn from_hue(h: f64, c: f64, m: f64) -> Self {
+        let h = h.rem_euclid(360.) / 60.;
+        let x = c * (1. - (h % 2. - 1.).abs());
+        let (r, g, b) = match h as u8 {
+            0 => (c, x, 0.),
+            1 => (x, c, 0.),
+            2 => (0., c, x),
+            3 => (0., x, c),
+            4 => (x, 0., c),
+            _ => (c, 0., x),
+        };
+
+        let f = |v: f64| ((v + m) * 255.).round() as u8;
+        Self::rgb(f(r), f(g), f(b))
+    }
+
+    /// Looks up a color by name. Works for the colors built into xcolor and some extra svg names
+    pub fn named(name: &str) -> Option<Self> {
+        if let Some(&(_, r, g, b)) = XCOLOR_COLORS.iter().find(|x| x.0 == name) {
+            let mut c = Self::rgb(r, g, b);
+            c.expr = Some(name.to_string());
+            return Some(c);
+        }
+
+        if let Some(&(_, r, g, b)) = EXTRA_COLORS.iter().find(|x| x.0 == name) {
+            return Some(Self::rgb(r, g, b).with_name(name));
+        }
+
+        None
+    }
+
+    /// Gives the color a name. The Tikz code refers to it by the name and defines it in the preamble
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        let name = name.into();
+        self.definitions = vec![ColorDefinition {
+            name: name.clone(),
+            model: "RGB".to_string(),
+            spec: format!("{},{},{}", self.r, self.g, self.b)
+        }];
+        self.expr = Some(name);
+        return self;
+    }
+
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha.clamp(0., 1.);
+        return self;
+    }
+
+    /// Mixes the two colors, with t being the proportion of the other color
+    pub fn mix(&self, other: &Color, t: f64) -> Color {
+        let m = |a: u8, b: u8| (a as f64 * (1. - t) + b as f64 * t).round() as u8;
+        let alpha = self.alpha * (1. - t) + other.alpha * t;
+        Color::rgba(m(self.r, other.r), m(self.g, other.g), m(self.b, other.b), alpha)
+    }
+
+    /// The complement of the color, which is what a leading minus does in xcolor
+    pub fn complement(&self) -> Color {
+        Color::rgba(255 - self.r, 255 - self.g, 255 - self.b, self.alpha)
+    }
+
+    /// Parses a color. This can be an xcolor expression like red, blue!30, -yellow or black!50!green!30!white,
+    /// or a hex code like #ff8000
+    pub fn parse(s: &str) -> Result<Self, ColorError> {
+        let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+
+        if let Some(hex) = s.strip_prefix('#') {
+            return Self::parse_hex(hex);
+        }
+
+        let (negate, expr) = match s.strip_prefix('-') {
+            Some(x) => (true, x),
+            None => (false, s.as_str())
+        };
+
+        let mut parts = expr.split('!');
+        let mut definitions = vec![];
+        let mut lookup = |name: Option<&str>| -> Result<Color, ColorError> {
+            let name = match name {
+                Some(x) if x.len() > 0 => x,
+                _ => return Err(ColorError::new(format!("missing color name in {s}")))
+            };
+            let c = Self::named(name).ok_or(ColorError::new(format!("unknown color {name}")))?;
+            definitions.extend(c.definitions.iter().cloned());
+            Ok(c)
+        };
+
+        // Each step mixes p% of what we have so far with the next color, which is white if it is left out at the end
+        let mut color = lookup(parts.next())?;
+        while let Some(p) = parts.next() {
+            let p: f64 = p.parse().map_err(|_| ColorError::new(format!("invalid percentage {p} in {s}")))?;
+            if !(0. ..=100.).contains(&p) {
+                return Err(ColorError::new(format!("percentage {p} is not between 0 and 100 in {s}")));
+            }
+
+            let next = match parts.next() {
+                Some(x) => lookup(Some(x))?,
+                None => Color::rgb(255, 255, 255)
+            };
+            color = color.mix(&next, 1. - p / 100.);
+        }
+
+        if negate {
+            color = color.complement();
+        }
+
+        color.expr = Some(s.clone());
+        color.definitions = definitions;
+        return Ok(color);
+    }
+
+    fn parse_hex(hex: &str) -> Result<Self, ColorError> {
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ColorError::new(format!("#{hex} is not a hex code")));
+        }
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+        let alpha = if hex.len() == 8 { channel(6) as f64 / 255. } else { 1. };
+        Ok(Self::rgba(channel(0), channel(2), channel(4), alpha))
+    }
+
+    /// The xcolor expression this color was made from, if any
+    pub fn expression(&self) -> Option<&str> {
+        self.expr.as_deref()
+    }
+}
+
+impl FromStr for Color {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl IsSVGPropertyField for Color {
+    fn to_svg(&self) -> String {
+        let Color { r, g, b, alpha, .. } = self;
+        if *alpha < 1. {
+            return format!("rgba({r},{g},{b},{alpha})");
+        }
+        return format!("rgb({r},{g},{b})");
+    }
+}
+
+impl IsTikzPropertyField for Color {
+    /// This function returns the string in the commands and any side effects on the
+    /// preamble - for example using patterns and defining colors. Tikz colors have no alpha,
+    /// so the opacity has to be set with the opacity options by whoever uses the color
+    fn to_tikz(&self) -> (String, Preamble) {
+        if let Some(expr) = &self.expr {
+            let mut preamble = Preamble::new();
+            for x in self.definitions.iter() {
+                preamble = preamble.color(x.name.as_str(), x.model.as_str(), x.spec.as_str());
+            }
+            return (expr.clone(), preamble);
+        }
+
+        let Color { r, g, b, .. } = self;
+        let st = format!("{{rgb,255:red,{r};green,{g};blue,{b}}}");
+        return (st, Preamble::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named() {
+        assert_eq!(Color::named("red"), Some(Color::rgb(255, 0, 0)));
+        assert_eq!(Color::named("nocolor"), None);
+
+        let (s, p) = Color::named("orange").unwrap().to_tikz();
+        assert_eq!(s, "orange");
+        assert!(p.is_empty());
+
+        let (s, p) = Color::named("navy").unwrap().to_tikz();
+        assert_eq!(s, "navy");
+        assert_eq!(p.lines(), vec!["\\definecolor{navy}{RGB}{0,0,128}"]);
+
+        let (s, p) = Color::rgb(1, 2, 3).with_name("mycolor").to_tikz();
+        assert_eq!(s, "mycolor");
+        assert_eq!(p.lines(), vec!["\\definecolor{mycolor}{RGB}{1,2,3}"]);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Color::parse("blue!20").unwrap(), Color::rgb(204, 204, 255));
+        assert_eq!(Color::parse("red!40!white").unwrap(), Color::rgb(255, 153, 153));
+        assert_eq!(Color::parse("black!50!green").unwrap(), Color::rgb(0, 128, 0));
+        assert_eq!(Color::parse("-red").unwrap(), Color::rgb(0, 255, 255));
+        assert_eq!(Color::parse("red!50!blue!50!white").unwrap(), Color::rgb(192, 128, 192));
+        assert_eq!("#ff8000".parse::<Color>().unwrap(), Color::rgb(255, 128, 0));
+        assert_eq!(Color::parse("#00000080").unwrap().alpha, 128. / 255.);
+
+        assert!(Color::parse("red!").is_err());
+        assert!(Color::parse("red!150").is_err());
+        assert!(Color::parse("red!x!blue").is_err());
+        assert!(Color::parse("notacolor").is_err());
+        assert!(Color::parse("#12345").is_err());
+    }
+
+    #[test]
+    fn test_parse_tikz() {
+        let (s, p) = Color::parse("red!40 ! white").unwrap().to_tikz();
+        assert_eq!(s, "red!40!white");
+        assert!(p.is_empty());
+
+        let (s, p) = Color::parse("gold!20!navy").unwrap().to_tikz();
+        assert_eq!(s, "gold!20!navy");
+        assert_eq!(p.lines(), vec![
+            "\\definecolor{gold}{RGB}{255,215,0}",
+            "\\definecolor{navy}{RGB}{0,0,128}",
+        ]);
+
+        // Hex codes have no name in xcolor
+        assert_eq!(Color::parse("#ff0000").unwrap().to_tikz().0, "{rgb,255:red,255;green,0;blue,0}");
+    }
+
+    #[test]
+    fn test_hsl_hsb() {
+        assert_eq!(Color::hsl(0., 1., 0.5), Color::rgb(255, 0, 0));
+        assert_eq!(Color::hsl(120., 1., 0.25), Color::rgb(0, 128, 0));
+        assert_eq!(Color::hsl(240., 0., 1.), Color::rgb(255, 255, 255));
+        assert_eq!(Color::hsb(60., 1., 1.), Color::rgb(255, 255, 0));
+        assert_eq!(Color::hsb(-60., 0.5, 1.), Color::rgb(255, 128, 255));
+    }
+
+    #[test]
+    fn test_alpha() {
+        let c = Color::rgb(255, 0, 0).with_alpha(0.5);
+        assert_eq!(c.to_svg(), "rgba(255,0,0,0.5)");
+        assert_eq!(Color::rgb(255, 0, 0).to_svg(), "rgb(255,0,0)");
+        assert_eq!(c.mix(&Color::rgb(0, 0, 0), 0.5), Color::rgba(128, 0, 0, 0.75));
+    }
+}
@@ -25,7 +25,7 @@ impl Coordinates {
     /// # Examples
     ///
     /// ```
-    /// use tikzpaint_rs::figures::Coordinates;
+    /// use tikzpaint_rs::core::Coordinates;
     /// let coord = Coordinates::new(1, 2);
     /// assert_eq!(1., coord.get(0).unwrap());
     /// assert_eq!(2., coord.get(1).unwrap());
@@ -48,7 +48,7 @@ impl Coordinates {
     /// # Examples
     ///
     /// ```
-    /// use tikzpaint_rs::figures::Coordinates;
+    /// use tikzpaint_rs::core::Coordinates;
     /// let coord = Coordinates::new(1, -2).scale(6);
     /// let coord2 = Coordinates::new(6, -12);
     /// assert!(coord == coord2);
@@ -67,10 +67,10 @@ impl Coordinates {
     /// # Examples
     ///
     /// ```
-    /// use tikzpaint_rs::figures::Coordinates;
-    /// let coord = Coordinates::new(vec![1, 13]);
-    /// let mag = (14 as f64).sqrt();
-    /// assert!(coord.magnitude() - mag <= 1e-8);
+    /// use tikzpaint_rs::core::Coordinates;
+    /// let coord = Coordinates::new(1, 13);
+    /// let mag = (170 as f64).sqrt();
+    /// assert!((coord.magnitude() - mag).abs() <= 1e-8);
     /// ```
     pub fn magnitude(&self) -> f64
     {
@@ -83,9 +83,9 @@ impl Coordinates {
     /// # Examples
     ///
     /// ```
-    /// use tikzpaint_rs::figures::Coordinates;
-    /// let coord = Coordinates::new(vec![3, 4]).normalize();
-    /// let coord2 = Coordinates::new(vec![0.6, 0.8]);
+    /// use tikzpaint_rs::core::Coordinates;
+    /// let coord = Coordinates::new(3, 4).normalize();
+    /// let coord2 = Coordinates::new(0.6, 0.8);
     /// assert!(coord == coord2);
     /// ```
     pub fn normalize(self) -> Self {
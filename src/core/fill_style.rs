@@ -0,0 +1,184 @@
+//! What the inside of a shape is filled with: a solid color, a pattern from the Tikz patterns library or a shading
+
+use crate::core::*;
+
+/// Patterns from the Tikz patterns library
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FillPattern {
+    HorizontalLines,
+    VerticalLines,
+    NorthEastLines,
+    NorthWestLines,
+    Grid,
+    CrossHatch,
+    Dots,
+}
+
+impl FillPattern {
+    pub fn tikz_name(&self) -> &'static str {
+        match self {
+            FillPattern::HorizontalLines => "horizontal lines",
+            FillPattern::VerticalLines => "vertical lines",
+            FillPattern::NorthEastLines => "north east lines",
+            FillPattern::NorthWestLines => "north west lines",
+            FillPattern::Grid => "grid",
+            FillPattern::CrossHatch => "crosshatch",
+            FillPattern::Dots => "dots",
+        }
+    }
+}
+
+/// Size of one tile of a pattern in pt, which is the spacing between the lines or dots in Tikz
+pub const PATTERN_TILE_SIZE: f64 = 3.;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum FillStyle {
+    /// A solid color. Opacity goes from 0 (invisible) to 1
+    Solid{color: Color, opacity: f64},
+
+    /// Lines or dots drawn in the color
+    Pattern{pattern: FillPattern, color: Color},
+
+    /// Goes from one color to the other along the direction at the angle (in degrees, counterclockwise from the x-axis)
+    LinearShading{from: Color, to: Color, angle: f64},
+
+    /// Goes from the inner color at the center to the outer color
+    RadialShading{inner: Color, outer: Color},
+}
+
+impl FillStyle {
+    pub fn solid(color: Color) -> Self {
+        FillStyle::Solid { color, opacity: 1. }
+    }
+
+    /// Returns the Tikz options of this fill and what it needs in the preamble
+    pub fn tikz_options(&self, opts: &TikzOutputOptions) -> (Vec<String>, Preamble) {
+        let mut preamble = Preamble::new();
+        let mut color = |c: &Color| {
+            let (s, p) = c.to_tikz();
+            preamble.merge(&p);
+            s
+        };
+
+        let v = match self {
+            FillStyle::Solid { color: c, opacity } => {
+                let mut v = vec![format!("fill={}", color(c))];
+                let opacity = opacity * c.alpha;
+                if opacity < 1. {
+                    v.push(format!("fill opacity={}", opts.number(opacity)));
+                }
+                v
+            },
+
+            FillStyle::Pattern { pattern, color: c } => {
+                vec![format!("pattern={}", pattern.tikz_name()), format!("pattern color={}", color(c))]
+            },
+
+            FillStyle::LinearShading { from, to, angle } => {
+                // Axis shadings go from the bottom to the top, so we rotate that onto our direction
+                let mut v = vec![format!("bottom color={}", color(from)), format!("top color={}", color(to))];
+                if *angle != 90. {
+                    v.push(format!("shading angle={}", opts.number(angle - 90.)));
+                }
+                v
+            },
+
+            FillStyle::RadialShading { inner, outer } => {
+                vec![format!("inner color={}", color(inner)), format!("outer color={}", color(outer))]
+            },
+        };
+
+        if let FillStyle::Pattern { .. } = self {
+            preamble = preamble.library(TikzLibrary::Patterns);
+        }
+
+        return (v, preamble);
+    }
+
+    /// Returns the fill attributes of an svg element. Svg needs definitions elsewhere in the document for
+    /// patterns and gradients, so we approximate those with a solid color for now
+    pub fn svgify(&self) -> String {
+        match self {
+            FillStyle::Solid { color, opacity } => {
+                if *opacity < 1. {
+                    return format!("fill=\"{}\" fill-opacity=\"{}\"", color.to_svg(), opacity.to_svg());
+                }
+                format!("fill=\"{}\"", color.to_svg())
+            },
+            FillStyle::Pattern { color, .. } => format!("fill=\"{}\" fill-opacity=\"0.5\"", color.to_svg()),
+            FillStyle::LinearShading { from, to, .. } => format!("fill=\"{}\"", from.mix(to, 0.5).to_svg()),
+            FillStyle::RadialShading { inner, outer } => format!("fill=\"{}\"", inner.mix(outer, 0.5).to_svg()),
+        }
+    }
+
+    /// Returns the two end points of a linear shading that covers the bounding box. The shading
+    /// starts at the first point and ends at the second one
+    pub fn shading_axis(angle: f64, extent: &BoundingBox) -> (Coordinates, Coordinates) {
+        let t = angle.to_radians();
+        let d = Coordinates::new(t.cos(), t.sin());
+        let center = (extent.min() + extent.max()) * 0.5;
+
+        // Half the length of the bounding box projected onto the direction
+        let half = (extent.width() * d[0].abs() + extent.height() * d[1].abs()) / 2.;
+        (center - d * half, center + d * half)
+    }
+}
+
+impl IsTikzPropertyField for FillStyle {
+    fn to_tikz(&self) -> (String, Preamble) {
+        let (v, p) = self.tikz_options(&TikzOutputOptions::default());
+        (v.join(", "), p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tikz_solid() {
+        let (s, p) = FillStyle::solid(Color::rgb(255, 0, 0)).to_tikz();
+        assert_eq!(s, "fill={rgb,255:red,255;green,0;blue,0}");
+        assert!(p.is_empty());
+
+        let (s, _) = FillStyle::Solid { color: Color::rgb(0, 0, 0), opacity: 0.25 }.to_tikz();
+        assert!(s.ends_with(", fill opacity=0.25"), "{s}");
+
+        let (s, p) = FillStyle::Solid { color: Color::named("navy").unwrap().with_alpha(0.5), opacity: 0.5 }.to_tikz();
+        assert_eq!(s, "fill=navy, fill opacity=0.25");
+        assert_eq!(p.lines(), vec!["\\definecolor{navy}{RGB}{0,0,128}"]);
+
+        let (s, _) = FillStyle::Solid { color: Color::rgb(0, 0, 0), opacity: 0.1 + 0.2 }.to_tikz();
+        assert!(s.ends_with(", fill opacity=0.3"), "{s}");
+    }
+
+    #[test]
+    fn test_tikz_pattern() {
+        let (s, p) = FillStyle::Pattern { pattern: FillPattern::NorthEastLines, color: Color::rgb(0, 0, 0) }.to_tikz();
+        assert!(s.starts_with("pattern=north east lines, pattern color="), "{s}");
+        assert_eq!(p.lines(), vec!["\\usetikzlibrary{patterns}"]);
+    }
+
+    #[test]
+    fn test_tikz_shading() {
+        let (s, p) = FillStyle::LinearShading { from: Color::rgb(0, 0, 0), to: Color::rgb(255, 255, 255), angle: 0. }.to_tikz();
+        assert!(s.ends_with("shading angle=-90"), "{s}");
+        assert!(p.is_empty());
+
+        let (s, _) = FillStyle::LinearShading { from: Color::rgb(0, 0, 0), to: Color::rgb(255, 255, 255), angle: 90. }.to_tikz();
+        assert!(!s.contains("shading angle"), "{s}");
+
+        let (s, _) = FillStyle::RadialShading { inner: Color::rgb(0, 0, 0), outer: Color::rgb(255, 255, 255) }.to_tikz();
+        assert!(s.starts_with("inner color=") && s.contains(", outer color="), "{s}");
+    }
+
+    #[test]
+    fn test_shading_axis() {
+        let bb = BoundingBox::new(Coordinates::new(0, 0), Coordinates::new(4, 2));
+        assert_eq!(FillStyle::shading_axis(0., &bb), (Coordinates::new(0, 1), Coordinates::new(4, 1)));
+
+        let (a, b) = FillStyle::shading_axis(90., &bb);
+        assert!((a - Coordinates::new(2, 0)).magnitude() < 1e-10);
+        assert!((b - Coordinates::new(2, 2)).magnitude() < 1e-10);
+    }
+}
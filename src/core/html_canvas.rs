@@ -1,29 +1,15 @@
 //! Handler for drawing on HTML canvas
 
 use std::cell::RefCell;
-use std::error::Error;
-use std::fmt::Debug;
 use std::rc::Rc;
 
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{window, HtmlCanvasElement, CanvasRenderingContext2d};
+use web_sys::{window, HtmlCanvasElement, CanvasRenderingContext2d, CanvasWindingRule};
 use yew::prelude::*;
 use gloo::console::log;
 
-use crate::core::{Transform, Coordinates};
-
-const PI: f64 = 3.1415926535897932384626433;
-
-pub struct DrawError {
-    msg: String
-}
-
-impl Debug for DrawError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Draw Error -- {}", self.msg)
-    }
-}
+use crate::core::{Transform, Coordinates, RenderTarget, DrawError, StrokeStyle, FillStyle, FillPattern, FillRule, BoundingBox, Color, IsSVGPropertyField, PT, PATTERN_TILE_SIZE};
 
 trait ConvertError {
     fn cvt(self) -> Result<(), DrawError>;
@@ -32,19 +18,43 @@ trait ConvertError {
 impl ConvertError for Result<(), JsValue> {
     fn cvt(self) -> Result<(), DrawError> {
         if let Err(e) = self {
-            return Err(DrawError{msg: format!("{:?}", e)});
+            return Err(DrawError::new(format!("{:?}", e)));
         }
 
         Ok(())
     }
 }
 
-fn max(a: f64, b: f64) -> f64 {
-    if a > b {a} else {b}
+/// The transform of the scope we are drawing in. Points p in local coordinates go to m * p + (tx, ty),
+/// where m = a + bi is multiplied as a complex number, so that it rotates and scales
+#[derive(PartialEq, Clone, Copy)]
+struct ScopeTransform {
+    a: f64,
+    b: f64,
+    tx: f64,
+    ty: f64
 }
 
-fn min(a: f64, b: f64) -> f64 {
-    if a < b {a} else {b}
+impl ScopeTransform {
+    fn identity() -> Self {
+        Self { a: 1., b: 0., tx: 0., ty: 0. }
+    }
+
+    /// Returns the transform that applies 'scale', 'rotate' (in radians) and 'shift' in that order, then this transform
+    fn then(&self, shift: Coordinates, rotate: f64, scale: f64) -> Self {
+        let (c, d) = (scale * rotate.cos(), scale * rotate.sin());
+        Self {
+            a: self.a * c - self.b * d,
+            b: self.a * d + self.b * c,
+            tx: self.a * shift[0] - self.b * shift[1] + self.tx,
+            ty: self.b * shift[0] + self.a * shift[1] + self.ty
+        }
+    }
+
+    /// How much lengths are scaled by
+    fn scale(&self) -> f64 {
+        self.a.hypot(self.b)
+    }
 }
 
 /// Make a canvas state handle to not screw up Rc Refcell patterns
@@ -52,7 +62,10 @@ fn min(a: f64, b: f64) -> f64 {
 #[derive(PartialEq, Clone)]
 pub struct HtmlCanvas {
     ptr: Rc<RefCell<NodeRef>>,
-    tf: Rc<RefCell<Transform>>
+    tf: Rc<RefCell<Transform>>,
+
+    /// The transform of the current scope is at the back, and the ones of the enclosing scopes come before it
+    scopes: Rc<RefCell<Vec<ScopeTransform>>>
 }
 
 macro_rules! fm {
@@ -67,11 +80,32 @@ macro_rules! fig {
     };
 }
 
+/// Gets the 2d context of a canvas element
+fn context_of(canvas: &HtmlCanvasElement) -> Result<CanvasRenderingContext2d, DrawError> {
+    let b = canvas.get_context("2d");
+    if let Err(e) = b {
+        return Err(DrawError::new(format!("Failed to get canvas context. Got: {:?}", e)));
+    }
+
+    let c = b.unwrap();
+    if c.is_none() {
+        return Err(DrawError::new("Failed to get canvas context, got no values."));
+    }
+
+    let d = c.unwrap().dyn_into::<CanvasRenderingContext2d>();
+    if let Err(e) = d {
+        return Err(DrawError::new(format!("Failed to get canvas context, got {:?}.", e)));
+    }
+
+    return Ok(d.unwrap());
+}
+
 impl HtmlCanvas {
     pub fn new(t: Rc<RefCell<Transform>>) -> Self {
         Self {
             ptr: Rc::new(RefCell::new(NodeRef::default())),
-            tf: t
+            tf: t,
+            scopes: Rc::new(RefCell::new(vec![ScopeTransform::identity()]))
         }
     }
 
@@ -83,103 +117,307 @@ impl HtmlCanvas {
     fn context(&self) -> Result<CanvasRenderingContext2d, DrawError> {
         let a = fig!(self).cast::<HtmlCanvasElement>();
         if a.is_none() {
-            return Err(DrawError { msg: String::from("Failed to get canvas element") });
+            return Err(DrawError::new("Failed to get canvas element"));
         }
 
-        let b = a.unwrap().get_context("2d");
-        if let Err(e) = b {
-            return Err(DrawError { msg: format!("Failed to get canvas context. Got: {:?}", e) });
-        }
+        return context_of(&a.unwrap());
+    }
 
-        let c = b.unwrap();
-        if c.is_none() {
-            return Err(DrawError { msg: format!("Failed to get canvas context, got no values.") });
-        }
+    /// The number of pixels that one unit length in local coordinates takes up, ignoring the transform of the scope.
+    /// The canvas applies the transform of the scope on top of this
+    fn view_scale(&self) -> f64 {
+        self.tf.borrow().scale
+    }
+
+    /// How much the transform of the current scope scales lengths by
+    fn scope_scale(&self) -> f64 {
+        self.scopes.borrow().last().map(|x| x.scale()).unwrap_or(1.)
+    }
+
+    /// Draws one tile of the pattern on a new canvas that is not attached to the document
+    fn pattern_tile(&self, pattern: FillPattern, color: &Color) -> Result<HtmlCanvasElement, DrawError> {
+        let px = PT * self.view_scale();
+        let s = (PATTERN_TILE_SIZE * px).max(2.).ceil();
+
+        let document = window()
+            .and_then(|w| w.document())
+            .ok_or(DrawError::new("Failed to get document"))?;
+
+        let tile = document.create_element("canvas")
+            .map_err(|e| DrawError::new(format!("Failed to create canvas. Got: {:?}", e)))?
+            .dyn_into::<HtmlCanvasElement>()
+            .map_err(|e| DrawError::new(format!("Failed to create canvas. Got: {:?}", e)))?;
+
+        tile.set_width(s as u32);
+        tile.set_height(s as u32);
+
+        let ctx = context_of(&tile)?;
+        let color = JsValue::from_str(&color.to_svg());
+        ctx.set_stroke_style(&color);
+        ctx.set_fill_style(&color);
+        ctx.set_line_width(0.4 * px);
+        ctx.begin_path();
+
+        // Diagonal lines are drawn three times, shifted by a tile, so the corners of the tile are covered too
+        let north_east = || for k in [-s, 0., s] {
+            ctx.move_to(k, s);
+            ctx.line_to(k + s, 0.);
+        };
+
+        let north_west = || for k in [-s, 0., s] {
+            ctx.move_to(k, 0.);
+            ctx.line_to(k + s, s);
+        };
+
+        let horizontal = || {
+            ctx.move_to(0., s / 2.);
+            ctx.line_to(s, s / 2.);
+        };
+
+        let vertical = || {
+            ctx.move_to(s / 2., 0.);
+            ctx.line_to(s / 2., s);
+        };
+
+        match pattern {
+            FillPattern::HorizontalLines => horizontal(),
+            FillPattern::VerticalLines => vertical(),
+            FillPattern::NorthEastLines => north_east(),
+            FillPattern::NorthWestLines => north_west(),
+            FillPattern::Grid => {
+                horizontal();
+                vertical();
+            },
+            FillPattern::CrossHatch => {
+                north_east();
+                north_west();
+            },
+            FillPattern::Dots => {
+                ctx.arc(s / 2., s / 2., 0.5 * px, 0., 2. * std::f64::consts::PI).cvt()?;
+                ctx.fill();
+                return Ok(tile);
+            }
+        };
+
+        ctx.stroke();
+        Ok(tile)
+    }
 
-        let d = c.unwrap().dyn_into::<CanvasRenderingContext2d>();
-        if let Err(e) = d {
-            return Err(DrawError { msg: format!("Failed to get canvas context, got {:?}.", e) });
+    /// Resets all the contents on the canvas
+    pub fn reset(&self) -> Result<(), DrawError> {
+        let ctx = self.context()?;
+
+        let a = fig!(self).cast::<HtmlCanvasElement>();
+        if let Some(canvas) = a {
+            // The whole canvas is cleared, whatever the transform of the current scope is
+            ctx.save();
+            ctx.set_transform(1., 0., 0., 1., 0., 0.).cvt()?;
+            ctx.clear_rect(0., 0., canvas.width() as f64, canvas.height() as f64);
+            ctx.restore();
+            return Ok(());
         }
 
-        return Ok(d.unwrap());
+        return Err(DrawError::new("Failed to get canvas element"));
     }
+}
 
-    /// For all the draw methods, returns () if the result is successfully drawn,
-    /// otherwise returns an Err
-    pub fn draw_circle(&self, local_coords: Coordinates, radius: f64) -> Result<(), DrawError> {
-        let (x, y) = self.tf.borrow().local_to_client(local_coords);
-        let c = self.context()?;
-        c.begin_path();
-        c.arc(x, y, radius, 0., PI * 2.).cvt()?;
-        c.stroke();
+impl RenderTarget for HtmlCanvas {
+    fn scale(&self) -> f64 {
+        self.view_scale() * self.scope_scale()
+    }
 
+    fn erase(&self) -> Result<(), DrawError> {
+        self.reset()
+    }
+
+    fn begin_path(&self) -> Result<(), DrawError> {
+        self.context()?.begin_path();
         Ok(())
     }
 
-    /// For all the draw methods, returns () if the result is successfully drawn,
-    /// otherwise returns an Err
-    pub fn draw_rectangles(&self, corner_1: Coordinates, corner_2: Coordinates) -> Result<(), DrawError> {
-        let (t, l) = self.tf.borrow().local_to_client(corner_1);
-        let (b, r) = self.tf.borrow().local_to_client(corner_2);
+    fn move_to(&self, p: Coordinates) -> Result<(), DrawError> {
+        let (x, y) = self.tf.borrow().local_to_client(p);
+        self.context()?.move_to(x, y);
+        Ok(())
+    }
 
-        let bot = max(t, b);
-        let top = min(t, b);
-        let lef = min(l, r);
-        let rig = max(l, r);
+    fn line_to(&self, p: Coordinates) -> Result<(), DrawError> {
+        let (x, y) = self.tf.borrow().local_to_client(p);
+        self.context()?.line_to(x, y);
+        Ok(())
+    }
 
-        self.context()?.fill_rect(top, lef, bot - top, rig - lef);
+    fn quadratic_curve_to(&self, control: Coordinates, to: Coordinates) -> Result<(), DrawError> {
+        let (cx, cy) = self.tf.borrow().local_to_client(control);
+        let (x, y) = self.tf.borrow().local_to_client(to);
+        self.context()?.quadratic_curve_to(cx, cy, x, y);
+        Ok(())
+    }
 
+    fn bezier_curve_to(&self, control_start: Coordinates, control_end: Coordinates, to: Coordinates) -> Result<(), DrawError> {
+        let (c1x, c1y) = self.tf.borrow().local_to_client(control_start);
+        let (c2x, c2y) = self.tf.borrow().local_to_client(control_end);
+        let (x, y) = self.tf.borrow().local_to_client(to);
+        self.context()?.bezier_curve_to(c1x, c1y, c2x, c2y, x, y);
         Ok(())
     }
 
-    /// For all the draw methods, returns () if the result is successfully drawn,
-    /// otherwise returns an Err
-    pub fn draw_line(&self, start: Coordinates, end: Coordinates) -> Result<(), DrawError> {
-        let (x1, y1) = self.tf.borrow().local_to_client(start);
-        let (x2, y2) = self.tf.borrow().local_to_client(end);
+    /// The client coordinates have the y-axis pointing downwards, so the angles are negated.
+    /// The direction on screen stays the same so we keep the anticlockwise flag
+    fn arc(&self, center: Coordinates, radius: f64, start_angle: f64, end_angle: f64, anticlockwise: bool) -> Result<(), DrawError> {
+        let (x, y) = self.tf.borrow().local_to_client(center);
+        let r = radius * self.view_scale();
+        self.context()?.arc_with_anticlockwise(x, y, r, -start_angle, -end_angle, anticlockwise).cvt()
+    }
 
-        let ctx = self.context()?;
+    fn ellipse(&self, center: Coordinates, x_radius: f64, y_radius: f64, rotation: f64, start_angle: f64, end_angle: f64, anticlockwise: bool) -> Result<(), DrawError> {
+        let (x, y) = self.tf.borrow().local_to_client(center);
+        let s = self.view_scale();
+        self.context()?.ellipse_with_anticlockwise(x, y, x_radius * s, y_radius * s, -rotation, -start_angle, -end_angle, anticlockwise).cvt()
+    }
 
-        ctx.move_to(x1, y1);
-        ctx.line_to(x2, y2);
-        ctx.stroke();
+    fn close_path(&self) -> Result<(), DrawError> {
+        self.context()?.close_path();
+        Ok(())
+    }
 
+    fn fill(&self) -> Result<(), DrawError> {
+        self.context()?.fill();
         Ok(())
     }
 
-    /// An optimized version for drawing many lines to approximate a curve
-    pub fn draw_curve(&self, coords: Vec<Coordinates>) -> Result<(), DrawError> {
-        if coords.len() == 0 {
-            return Err(DrawError{
-                msg: format!("The number of coordinates cannot be 0"),
-            });
-        }
+    fn fill_with_rule(&self, rule: FillRule) -> Result<(), DrawError> {
+        let rule = match rule {
+            FillRule::NonZero => CanvasWindingRule::Nonzero,
+            FillRule::EvenOdd => CanvasWindingRule::Evenodd
+        };
+        self.context()?.fill_with_canvas_winding_rule(rule);
+        Ok(())
+    }
 
+    fn stroke(&self) -> Result<(), DrawError> {
+        self.context()?.stroke();
+        Ok(())
+    }
+
+    fn fill_text(&self, text: &str, p: Coordinates, angle: f64) -> Result<(), DrawError> {
+        let (x, y) = self.tf.borrow().local_to_client(p);
         let ctx = self.context()?;
 
-        let (x1, y1) = self.tf.borrow().local_to_client(coords[0]);
-        ctx.move_to(x1, y1);
+        // The y-axis points down on the canvas, so counterclockwise is a negative rotation
+        ctx.save();
+        ctx.translate(x, y).cvt()?;
+        ctx.rotate(-angle).cvt()?;
+        let res = ctx.fill_text(text, 0., 0.).cvt();
+        ctx.restore();
+        res
+    }
 
+    /// Font sizes are in pt, so they scale with the zoom level like everything else but not with the scope
+    fn set_font_size(&self, size: f64) -> Result<(), DrawError> {
+        let px = size * PT * self.view_scale() / self.scope_scale();
+        self.context()?.set_font(&format!("{px}px serif"));
+        Ok(())
+    }
 
-        for coord in coords[1..].iter() {
-            let (x2, y2) = self.tf.borrow().local_to_client(*coord);
-            ctx.line_to(x2, y2);
+    /// Widths and dashes are in pt, so they scale with the zoom level like everything else but not with the scope
+    fn set_stroke_style(&self, style: &StrokeStyle) -> Result<(), DrawError> {
+        let ctx = self.context()?;
+        let px = PT * self.view_scale() / self.scope_scale();
+
+        let color = JsValue::from_str(&style.effective_color().to_svg());
+        ctx.set_stroke_style(&color);
+        ctx.set_fill_style(&color);
+        ctx.set_line_width(style.width * px);
+        ctx.set_line_cap(style.cap.canvas_name());
+        ctx.set_line_join(style.join.name());
+
+        let dashes = js_sys::Array::new();
+        for x in style.dash.lengths(style.width) {
+            dashes.push(&JsValue::from_f64(x * px));
         }
+        ctx.set_line_dash(&dashes).cvt()
+    }
 
-        ctx.stroke();
+    fn set_fill_style(&self, style: &FillStyle, extent: BoundingBox) -> Result<(), DrawError> {
+        let ctx = self.context()?;
+
+        match style {
+            FillStyle::Solid { color, opacity } => {
+                let Color { r, g, b, alpha, .. } = color;
+                ctx.set_fill_style(&JsValue::from_str(&format!("rgba({r},{g},{b},{})", alpha * opacity)));
+            },
+
+            FillStyle::Pattern { pattern, color } => {
+                let tile = self.pattern_tile(*pattern, color)?;
+                let p = ctx.create_pattern_with_html_canvas_element(&tile, "repeat")
+                    .map_err(|e| DrawError::new(format!("Failed to create pattern. Got: {:?}", e)))?
+                    .ok_or(DrawError::new("Failed to create pattern, got no values."))?;
+                ctx.set_fill_style(&p);
+            },
+
+            FillStyle::LinearShading { from, to, angle } => {
+                let (a, b) = FillStyle::shading_axis(*angle, &extent);
+                let (x0, y0) = self.tf.borrow().local_to_client(a);
+                let (x1, y1) = self.tf.borrow().local_to_client(b);
+                let g = ctx.create_linear_gradient(x0, y0, x1, y1);
+                g.add_color_stop(0., &from.to_svg()).cvt()?;
+                g.add_color_stop(1., &to.to_svg()).cvt()?;
+                ctx.set_fill_style(&g);
+            },
+
+            FillStyle::RadialShading { inner, outer } => {
+                // The outer color is reached at the corners of the bounding box
+                let center = (extent.min() + extent.max()) * 0.5;
+                let r = (extent.max() - extent.min()).magnitude() / 2. * self.view_scale();
+                let (x, y) = self.tf.borrow().local_to_client(center);
+                let g = ctx.create_radial_gradient(x, y, 0., x, y, r)
+                    .map_err(|e| DrawError::new(format!("Failed to create gradient. Got: {:?}", e)))?;
+                g.add_color_stop(0., &inner.to_svg()).cvt()?;
+                g.add_color_stop(1., &outer.to_svg()).cvt()?;
+                ctx.set_fill_style(&g);
+            },
+        };
 
         Ok(())
     }
 
-    /// Resets all the contents on the canvas
-    pub fn reset(&self) -> Result<(), DrawError> {
-        let ctx = self.context()?;
+    fn save(&self) -> Result<(), DrawError> {
+        self.context()?.save();
+        let mut scopes = self.scopes.borrow_mut();
+        let current = *scopes.last().unwrap();
+        scopes.push(current);
+        Ok(())
+    }
 
-        let a = fig!(self).cast::<HtmlCanvasElement>();
-        if let Some(canvas) = a {
-            ctx.clear_rect(0., 0., canvas.width() as f64, canvas.height() as f64);
+    fn restore(&self) -> Result<(), DrawError> {
+        self.context()?.restore();
+        let mut scopes = self.scopes.borrow_mut();
+        if scopes.len() > 1 {
+            scopes.pop();
         }
+        Ok(())
+    }
 
-        return Err(DrawError { msg: String::from("Failed to get canvas element") });
+    /// Everything is drawn in client coordinates, so the transform of the scope is conjugated by the transform
+    /// from local to client coordinates before it goes on the canvas
+    fn transform(&self, shift: Coordinates, rotate: f64, scale: f64) -> Result<(), DrawError> {
+        let m = {
+            let mut scopes = self.scopes.borrow_mut();
+            let m = scopes.last().unwrap().then(shift, rotate, scale);
+            *scopes.last_mut().unwrap() = m;
+            m
+        };
+
+        let (ox, oy) = self.tf.borrow().local_to_client(Coordinates::new(0, 0));
+        let s = self.view_scale();
+        let e = ox - m.a * ox - m.b * oy + s * m.tx;
+        let f = oy + m.b * ox - m.a * oy - s * m.ty;
+        self.context()?.set_transform(m.a, -m.b, m.b, m.a, e, f).cvt()
     }
-}
\ No newline at end of file
+
+    fn clip(&self) -> Result<(), DrawError> {
+        self.context()?.clip();
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+//! Logging for the parts of the crate that do not depend on the browser. By default messages go to the
+//! browser console on wasm with the web feature, and are dropped everywhere else. Use set_logger to send them somewhere else,
+//! for example the command line tool prints them to stderr in verbose mode
+
+use std::sync::RwLock;
+use lazy_static::lazy_static;
+
+pub type Logger = fn(&str);
+
+lazy_static! {
+    static ref LOGGER: RwLock<Option<Logger>> = RwLock::new(None);
+}
+
+/// Replaces the default destination of log messages
+pub fn set_logger(f: Logger) {
+    *LOGGER.write().unwrap() = Some(f);
+}
+
+pub fn log_message<S: AsRef<str>>(msg: S) {
+    if let Some(f) = *LOGGER.read().unwrap() {
+        f(msg.as_ref());
+        return;
+    }
+
+    #[cfg(all(feature = "web", target_arch = "wasm32"))]
+    gloo::console::log!(msg.as_ref());
+}
+
+/// Drop-in replacement for gloo::console::log for code that should also build natively
+macro_rules! log {
+    ($x:expr) => {
+        $crate::core::log_message($x)
+    };
+}
+
+pub(crate) use log;
@@ -0,0 +1,187 @@
+//! Named coordinates, like \coordinate (A) at (1, 2) in Tikz. Objects can refer to a point by its name instead of
+//! its value, so that moving the named coordinate moves everything that refers to it
+
+use std::fmt::{Debug, Display};
+use std::sync::{Arc, RwLock};
+
+use crate::core::*;
+
+/// A shared table of named coordinates. Cloning the registry gives another handle to the same table
+#[derive(Clone)]
+pub struct CoordinateRegistry {
+    inner: Arc<RwLock<Vec<(String, Coordinates)>>>
+}
+
+impl CoordinateRegistry {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(vec![]))
+        }
+    }
+
+    /// Defines a named coordinate, or moves it if it is already defined. Returns a reference to it
+    pub fn set<S: Into<String>>(&self, name: S, at: Coordinates) -> NamedCoordinate {
+        let name = name.into();
+        let mut v = self.inner.write().unwrap();
+        match v.iter_mut().find(|x| x.0 == name) {
+            Some(x) => x.1 = at,
+            None => v.push((name.clone(), at))
+        };
+
+        NamedCoordinate {
+            name,
+            registry: self.clone()
+        }
+    }
+
+    /// Returns the current value of the named coordinate
+    pub fn get(&self, name: &str) -> Option<Coordinates> {
+        self.inner.read().unwrap().iter().find(|x| x.0 == name).map(|x| x.1)
+    }
+
+    /// Returns a reference to the named coordinate if it is defined
+    pub fn reference(&self, name: &str) -> Option<NamedCoordinate> {
+        self.get(name)?;
+        Some(NamedCoordinate {
+            name: name.to_string(),
+            registry: self.clone()
+        })
+    }
+
+    /// Takes the named coordinate out of the registry and returns where it was. Nothing may refer to it any more
+    pub fn remove(&self, name: &str) -> Option<Coordinates> {
+        let mut v = self.inner.write().unwrap();
+        let i = v.iter().position(|x| x.0 == name)?;
+        Some(v.remove(i).1)
+    }
+
+    /// Returns every named coordinate in the order they are defined
+    pub fn definitions(&self) -> Vec<(String, Coordinates)> {
+        self.inner.read().unwrap().clone()
+    }
+}
+
+impl PartialEq for CoordinateRegistry {
+    fn eq(&self, other: &Self) -> bool {
+        self.definitions() == other.definitions()
+    }
+}
+
+/// A reference to a named coordinate. Names are only removed from a registry once nothing refers to them, so the reference is always valid
+#[derive(Clone)]
+pub struct NamedCoordinate {
+    name: String,
+    registry: CoordinateRegistry
+}
+
+impl NamedCoordinate {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns where the named coordinate is right now
+    pub fn get(&self) -> Coordinates {
+        self.registry.get(&self.name).expect("Named coordinates are not removed while they are referred to")
+    }
+}
+
+impl Debug for NamedCoordinate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}) at {}", self.name, self.get())
+    }
+}
+
+impl PartialEq for NamedCoordinate {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && Arc::ptr_eq(&self.registry.inner, &other.registry.inner)
+    }
+}
+
+/// A point on the figure, given either by its value or by a name
+#[derive(Clone, PartialEq)]
+pub enum Position {
+    At(Coordinates),
+    Named(NamedCoordinate),
+}
+
+impl Position {
+    /// Returns the value of the point right now
+    pub fn get(&self) -> Coordinates {
+        match self {
+            Position::At(x) => *x,
+            Position::Named(x) => x.get()
+        }
+    }
+
+    /// Writes the point in Tikz. Named coordinates keep their name
+    pub fn tikzify(&self, opts: &TikzOutputOptions) -> String {
+        match self {
+            Position::At(x) => opts.coords(*x),
+            Position::Named(x) => format!("({})", x.name)
+        }
+    }
+}
+
+impl From<Coordinates> for Position {
+    fn from(x: Coordinates) -> Self {
+        Position::At(x)
+    }
+}
+
+impl From<NamedCoordinate> for Position {
+    fn from(x: NamedCoordinate) -> Self {
+        Position::Named(x)
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Position::At(x) => write!(f, "{}", x),
+            Position::Named(x) => write!(f, "({})", x.name)
+        }
+    }
+}
+
+impl Debug for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (self as &dyn Display).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        let reg = CoordinateRegistry::new();
+        let a = reg.set("A", Coordinates::new(1, 2));
+        reg.set("B", Coordinates::new(3, 4));
+        assert_eq!(a.get(), Coordinates::new(1, 2));
+
+        // Moving a coordinate is seen through every reference to it
+        reg.set("A", Coordinates::new(5, 6));
+        assert_eq!(a.get(), Coordinates::new(5, 6));
+        assert_eq!(reg.reference("A"), Some(a));
+        assert_eq!(reg.reference("C"), None);
+        assert_eq!(reg.definitions(), vec![("A".to_string(), Coordinates::new(5, 6)), ("B".to_string(), Coordinates::new(3, 4))]);
+
+        assert_eq!(reg.remove("B"), Some(Coordinates::new(3, 4)));
+        assert_eq!(reg.remove("B"), None);
+        assert_eq!(reg.definitions(), vec![("A".to_string(), Coordinates::new(5, 6))]);
+    }
+
+    #[test]
+    fn test_position() {
+        let reg = CoordinateRegistry::new();
+        let p: Position = reg.set("A", Coordinates::new(1, 2)).into();
+        let q: Position = Coordinates::new(0.5, 0).into();
+
+        let opts = TikzOutputOptions::default();
+        assert_eq!(p.tikzify(&opts), "(A)");
+        assert_eq!(q.tikzify(&opts), "(0.5, 0)");
+        assert_eq!(p.get(), Coordinates::new(1, 2));
+        assert_eq!(format!("{p}{q}"), "(A)(0.5, 0)");
+    }
+}
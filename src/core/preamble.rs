@@ -0,0 +1,204 @@
+//! Typed requirements on the preamble of the document. Every object reports what it needs when it is turned into Tikz code,
+//! and the figure merges them together, removing duplicates and putting everything in the order LaTeX expects
+
+/// Tikz libraries that are loaded with \usetikzlibrary
+#[derive(Clone, PartialEq, Debug)]
+pub enum TikzLibrary {
+    ArrowsMeta,
+    Patterns,
+    Decorations,
+    Calc,
+    Shapes,
+    Other(String)
+}
+
+impl TikzLibrary {
+    pub fn name(&self) -> &str {
+        match self {
+            TikzLibrary::ArrowsMeta => "arrows.meta",
+            TikzLibrary::Patterns => "patterns",
+            TikzLibrary::Decorations => "decorations",
+            TikzLibrary::Calc => "calc",
+            TikzLibrary::Shapes => "shapes",
+            TikzLibrary::Other(x) => x
+        }
+    }
+}
+
+/// A color defined with \definecolor{name}{model}{spec}
+#[derive(Clone, PartialEq, Debug)]
+pub struct ColorDefinition {
+    pub name: String,
+    pub model: String,
+    pub spec: String
+}
+
+/// A style defined with \tikzset{name/.style={options}}
+#[derive(Clone, PartialEq, Debug)]
+pub struct TikzStyle {
+    pub name: String,
+    pub options: String
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Preamble {
+    packages: Vec<String>,
+    libraries: Vec<TikzLibrary>,
+    colors: Vec<ColorDefinition>,
+    styles: Vec<TikzStyle>
+}
+
+impl Preamble {
+    /// Creates an empty preamble. Tikz itself is always assumed to be loaded
+    pub fn new() -> Self {
+        Self {
+            packages: vec![],
+            libraries: vec![],
+            colors: vec![],
+            styles: vec![]
+        }
+    }
+
+    /// Requires a package loaded with \usepackage
+    pub fn package<S: Into<String>>(mut self, name: S) -> Self {
+        let name = name.into();
+        if !self.packages.contains(&name) {
+            self.packages.push(name);
+        }
+        return self;
+    }
+
+    /// Requires a tikz library
+    pub fn library(mut self, library: TikzLibrary) -> Self {
+        if !self.libraries.contains(&library) {
+            self.libraries.push(library);
+        }
+        return self;
+    }
+
+    /// Requires a color definition. If a color with the same name is already defined, the first definition wins
+    pub fn color<S1: Into<String>, S2: Into<String>, S3: Into<String>>(mut self, name: S1, model: S2, spec: S3) -> Self {
+        let def = ColorDefinition {
+            name: name.into(),
+            model: model.into(),
+            spec: spec.into()
+        };
+
+        if !self.colors.iter().any(|x| x.name == def.name) {
+            self.colors.push(def);
+        }
+        return self;
+    }
+
+    /// Requires a tikz style. If a style with the same name is already defined, the first definition wins
+    pub fn style<S1: Into<String>, S2: Into<String>>(mut self, name: S1, options: S2) -> Self {
+        let style = TikzStyle {
+            name: name.into(),
+            options: options.into()
+        };
+
+        if !self.styles.iter().any(|x| x.name == style.name) {
+            self.styles.push(style);
+        }
+        return self;
+    }
+
+    /// Adds every requirement of other into self, skipping the ones we already have
+    pub fn merge(&mut self, other: &Preamble) {
+        let mut merged = std::mem::replace(self, Preamble::new());
+
+        for x in other.packages.iter() {
+            merged = merged.package(x.as_str());
+        }
+
+        for x in other.libraries.iter() {
+            merged = merged.library(x.clone());
+        }
+
+        for x in other.colors.iter() {
+            merged = merged.color(x.name.as_str(), x.model.as_str(), x.spec.as_str());
+        }
+
+        for x in other.styles.iter() {
+            merged = merged.style(x.name.as_str(), x.options.as_str());
+        }
+
+        *self = merged;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packages.len() == 0 && self.libraries.len() == 0 && self.colors.len() == 0 && self.styles.len() == 0
+    }
+
+    /// Returns the lines of the preamble. Packages come first, then the libraries, since both have to be loaded before
+    /// any color or style that uses them is defined
+    pub fn lines(&self) -> Vec<String> {
+        let mut v = vec![];
+
+        for x in self.packages.iter() {
+            v.push(format!("\\usepackage{{{x}}}"));
+        }
+
+        if self.libraries.len() > 0 {
+            let libs = self.libraries.iter()
+                .map(|x| x.name())
+                .collect::<Vec<&str>>()
+                .join(", ");
+            v.push(format!("\\usetikzlibrary{{{libs}}}"));
+        }
+
+        for x in self.colors.iter() {
+            v.push(format!("\\definecolor{{{}}}{{{}}}{{{}}}", x.name, x.model, x.spec));
+        }
+
+        for x in self.styles.iter() {
+            v.push(format!("\\tikzset{{{}/.style={{{}}}}}", x.name, x.options));
+        }
+
+        return v;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order() {
+        let p = Preamble::new()
+            .style("thick line", "line width=2pt")
+            .color("mygreen", "RGB", "0,128,0")
+            .library(TikzLibrary::Patterns)
+            .package("amsmath")
+            .library(TikzLibrary::ArrowsMeta);
+
+        assert_eq!(p.lines(), vec![
+            "\\usepackage{amsmath}",
+            "\\usetikzlibrary{patterns, arrows.meta}",
+            "\\definecolor{mygreen}{RGB}{0,128,0}",
+            "\\tikzset{thick line/.style={line width=2pt}}",
+        ]);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut p = Preamble::new()
+            .library(TikzLibrary::Calc)
+            .color("a", "rgb", "1,0,0");
+        assert!(!p.is_empty());
+
+        p.merge(&Preamble::new()
+            .library(TikzLibrary::Calc)
+            .library(TikzLibrary::Other("shadings".to_string()))
+            .color("a", "rgb", "0,1,0")
+            .package("xcolor")
+            .package("xcolor"));
+
+        assert_eq!(p.lines(), vec![
+            "\\usepackage{xcolor}",
+            "\\usetikzlibrary{calc, shadings}",
+            "\\definecolor{a}{rgb}{1,0,0}",
+        ]);
+        assert!(Preamble::new().is_empty());
+    }
+}
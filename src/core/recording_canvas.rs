@@ -0,0 +1,183 @@
+//! A render target that draws nothing and records every call made on it instead.
+//! This lets us assert what an object would draw on the canvas without a browser
+
+use std::cell::RefCell;
+
+use crate::core::{Coordinates, RenderTarget, DrawError, StrokeStyle, FillStyle, FillRule, BoundingBox};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum DrawCall {
+    BeginPath,
+    MoveTo(Coordinates),
+    LineTo(Coordinates),
+    QuadraticCurveTo{control: Coordinates, to: Coordinates},
+    BezierCurveTo{control_start: Coordinates, control_end: Coordinates, to: Coordinates},
+    Arc{center: Coordinates, radius: f64, start_angle: f64, end_angle: f64, anticlockwise: bool},
+    Ellipse{center: Coordinates, x_radius: f64, y_radius: f64, rotation: f64, start_angle: f64, end_angle: f64, anticlockwise: bool},
+    ClosePath,
+    Fill,
+    FillEvenOdd,
+    Stroke,
+    FillText{text: String, position: Coordinates, angle: f64},
+    SetFontSize(f64),
+    SetStrokeStyle(StrokeStyle),
+    SetFillStyle{style: FillStyle, extent: BoundingBox},
+    Save,
+    Restore,
+    Transform{shift: Coordinates, rotate: f64, scale: f64},
+    Clip,
+    Erase,
+}
+
+pub struct RecordingCanvas {
+    scale: f64,
+    calls: RefCell<Vec<DrawCall>>
+}
+
+impl RecordingCanvas {
+    /// Creates a recording canvas where one unit length is one pixel
+    pub fn new() -> Self {
+        Self::with_scale(1.)
+    }
+
+    pub fn with_scale(scale: f64) -> Self {
+        Self {
+            scale,
+            calls: RefCell::new(vec![])
+        }
+    }
+
+    /// Returns every call made on this canvas so far, in order
+    pub fn calls(&self) -> Vec<DrawCall> {
+        self.calls.borrow().clone()
+    }
+
+    /// Forgets all the calls recorded so far
+    pub fn clear(&self) {
+        self.calls.borrow_mut().clear();
+    }
+
+    fn record(&self, call: DrawCall) -> Result<(), DrawError> {
+        self.calls.borrow_mut().push(call);
+        Ok(())
+    }
+}
+
+impl RenderTarget for RecordingCanvas {
+    fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    fn erase(&self) -> Result<(), DrawError> {
+        self.record(DrawCall::Erase)
+    }
+
+    fn begin_path(&self) -> Result<(), DrawError> {
+        self.record(DrawCall::BeginPath)
+    }
+
+    fn move_to(&self, p: Coordinates) -> Result<(), DrawError> {
+        self.record(DrawCall::MoveTo(p))
+    }
+
+    fn line_to(&self, p: Coordinates) -> Result<(), DrawError> {
+        self.record(DrawCall::LineTo(p))
+    }
+
+    fn quadratic_curve_to(&self, control: Coordinates, to: Coordinates) -> Result<(), DrawError> {
+        self.record(DrawCall::QuadraticCurveTo { control, to })
+    }
+
+    fn bezier_curve_to(&self, control_start: Coordinates, control_end: Coordinates, to: Coordinates) -> Result<(), DrawError> {
+        self.record(DrawCall::BezierCurveTo { control_start, control_end, to })
+    }
+
+    fn arc(&self, center: Coordinates, radius: f64, start_angle: f64, end_angle: f64, anticlockwise: bool) -> Result<(), DrawError> {
+        self.record(DrawCall::Arc { center, radius, start_angle, end_angle, anticlockwise })
+    }
+
+    fn ellipse(&self, center: Coordinates, x_radius: f64, y_radius: f64, rotation: f64, start_angle: f64, end_angle: f64, anticlockwise: bool) -> Result<(), DrawError> {
+        self.record(DrawCall::Ellipse { center, x_radius, y_radius, rotation, start_angle, end_angle, anticlockwise })
+    }
+
+    fn close_path(&self) -> Result<(), DrawError> {
+        self.record(DrawCall::ClosePath)
+    }
+
+    fn fill(&self) -> Result<(), DrawError> {
+        self.record(DrawCall::Fill)
+    }
+
+    fn fill_with_rule(&self, rule: FillRule) -> Result<(), DrawError> {
+        match rule {
+            FillRule::NonZero => self.record(DrawCall::Fill),
+            FillRule::EvenOdd => self.record(DrawCall::FillEvenOdd)
+        }
+    }
+
+    fn stroke(&self) -> Result<(), DrawError> {
+        self.record(DrawCall::Stroke)
+    }
+
+    fn fill_text(&self, text: &str, p: Coordinates, angle: f64) -> Result<(), DrawError> {
+        self.record(DrawCall::FillText { text: text.to_string(), position: p, angle })
+    }
+
+    fn set_font_size(&self, size: f64) -> Result<(), DrawError> {
+        self.record(DrawCall::SetFontSize(size))
+    }
+
+    fn set_stroke_style(&self, style: &StrokeStyle) -> Result<(), DrawError> {
+        self.record(DrawCall::SetStrokeStyle(style.clone()))
+    }
+
+    fn set_fill_style(&self, style: &FillStyle, extent: BoundingBox) -> Result<(), DrawError> {
+        self.record(DrawCall::SetFillStyle { style: style.clone(), extent })
+    }
+
+    fn save(&self) -> Result<(), DrawError> {
+        self.record(DrawCall::Save)
+    }
+
+    fn restore(&self) -> Result<(), DrawError> {
+        self.record(DrawCall::Restore)
+    }
+
+    fn transform(&self, shift: Coordinates, rotate: f64, scale: f64) -> Result<(), DrawError> {
+        self.record(DrawCall::Transform { shift, rotate, scale })
+    }
+
+    fn clip(&self) -> Result<(), DrawError> {
+        self.record(DrawCall::Clip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_line() {
+        let c = RecordingCanvas::new();
+        c.draw_line(Coordinates::new(0, 0), Coordinates::new(1, 2)).unwrap();
+        assert_eq!(c.calls(), vec![
+            DrawCall::BeginPath,
+            DrawCall::MoveTo(Coordinates::new(0, 0)),
+            DrawCall::LineTo(Coordinates::new(1, 2)),
+            DrawCall::Stroke
+        ]);
+
+        c.clear();
+        assert_eq!(c.calls(), vec![]);
+    }
+
+    #[test]
+    fn test_record_curve() {
+        let c = RecordingCanvas::new();
+        assert!(c.draw_curve(vec![]).is_err());
+
+        c.draw_curve(vec![Coordinates::new(0, 0), Coordinates::new(1, 0), Coordinates::new(1, 1)]).unwrap();
+        assert_eq!(c.calls().len(), 5);
+        assert_eq!(c.calls()[3], DrawCall::LineTo(Coordinates::new(1, 1)));
+    }
+}
@@ -0,0 +1,138 @@
+//! A backend agnostic drawing surface. Figure objects draw themselves on a render target in local (figure) coordinates,
+//! and every implementation is responsible for mapping them onto whatever it actually draws on.
+//! The operations mirror the 2D canvas API so the Html canvas implementation stays a thin wrapper
+
+use std::f64::consts::PI;
+use std::fmt::Debug;
+
+use crate::core::{Coordinates, StrokeStyle, FillStyle, FillRule, BoundingBox};
+
+pub struct DrawError {
+    msg: String
+}
+
+impl DrawError {
+    pub fn new<S: Into<String>>(msg: S) -> Self {
+        Self {
+            msg: msg.into()
+        }
+    }
+}
+
+impl Debug for DrawError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Draw Error -- {}", self.msg)
+    }
+}
+
+/// For all the methods, returns () if the operation is successful, otherwise returns an Err.
+/// Coordinates and lengths are in local coordinates, and angles are in radians measured counterclockwise from the x-axis
+pub trait RenderTarget {
+    /// The number of pixels that one unit length in local coordinates takes up. This is useful for
+    /// objects which should look the same size regardless of the zoom level, like points
+    fn scale(&self) -> f64;
+
+    /// Erases everything drawn so far
+    fn erase(&self) -> Result<(), DrawError>;
+
+    /// Starts a new path, discarding the current one
+    fn begin_path(&self) -> Result<(), DrawError>;
+
+    /// Moves the pen to p, starting a new subpath
+    fn move_to(&self, p: Coordinates) -> Result<(), DrawError>;
+
+    /// Draws a straight line to p
+    fn line_to(&self, p: Coordinates) -> Result<(), DrawError>;
+
+    /// Draws a quadratic Bezier curve to 'to'
+    fn quadratic_curve_to(&self, control: Coordinates, to: Coordinates) -> Result<(), DrawError>;
+
+    /// Draws a cubic Bezier curve to 'to'
+    fn bezier_curve_to(&self, control_start: Coordinates, control_end: Coordinates, to: Coordinates) -> Result<(), DrawError>;
+
+    /// Draws a circular arc around center
+    fn arc(&self, center: Coordinates, radius: f64, start_angle: f64, end_angle: f64, anticlockwise: bool) -> Result<(), DrawError>;
+
+    /// Draws an elliptical arc around center. The x-axis of the ellipse is rotated counterclockwise from the x-axis by 'rotation'
+    fn ellipse(&self, center: Coordinates, x_radius: f64, y_radius: f64, rotation: f64, start_angle: f64, end_angle: f64, anticlockwise: bool) -> Result<(), DrawError>;
+
+    /// Draws a straight line back to the start of the current subpath
+    fn close_path(&self) -> Result<(), DrawError>;
+
+    /// Fills the current path
+    fn fill(&self) -> Result<(), DrawError>;
+
+    /// Fills the current path, with the rule deciding which parts are inside. Plain fill uses the nonzero rule
+    fn fill_with_rule(&self, rule: FillRule) -> Result<(), DrawError>;
+
+    /// Strokes the current path
+    fn stroke(&self) -> Result<(), DrawError>;
+
+    /// Draws text with its bottom left corner at p, rotated around p by the angle
+    fn fill_text(&self, text: &str, p: Coordinates, angle: f64) -> Result<(), DrawError>;
+
+    /// Sets the size of the text drawn from now on, in pt
+    fn set_font_size(&self, size: f64) -> Result<(), DrawError>;
+
+    /// Sets the style of every stroke from now on. Until a fill style is set, fills use the stroke color
+    fn set_stroke_style(&self, style: &StrokeStyle) -> Result<(), DrawError>;
+
+    /// Sets the style of every fill from now on. Shadings and patterns are laid out over the extent,
+    /// which should be the bounding box of the shape that will be filled
+    fn set_fill_style(&self, style: &FillStyle, extent: BoundingBox) -> Result<(), DrawError>;
+
+    /// Pushes the transform, the clip region and the styles onto a stack
+    fn save(&self) -> Result<(), DrawError>;
+
+    /// Pops the transform, the clip region and the styles pushed by the last save
+    fn restore(&self) -> Result<(), DrawError>;
+
+    /// Transforms everything drawn from now on: points are scaled about the origin, rotated by the angle and then shifted.
+    /// This composes with the current transform. Like in Tikz, line widths and font sizes stay the same
+    fn transform(&self, shift: Coordinates, rotate: f64, scale: f64) -> Result<(), DrawError>;
+
+    /// Restricts everything drawn from now on to the inside of the current path
+    fn clip(&self) -> Result<(), DrawError>;
+
+    /// Draws a single straight line segment
+    fn draw_line(&self, start: Coordinates, end: Coordinates) -> Result<(), DrawError> {
+        self.begin_path()?;
+        self.move_to(start)?;
+        self.line_to(end)?;
+        self.stroke()
+    }
+
+    /// Draws the outline of a circle
+    fn draw_circle(&self, center: Coordinates, radius: f64) -> Result<(), DrawError> {
+        self.begin_path()?;
+        self.arc(center, radius, 0., PI * 2., false)?;
+        self.stroke()
+    }
+
+    /// Draws the outline of a rectangle given two opposite corners
+    fn draw_rectangle(&self, corner_1: Coordinates, corner_2: Coordinates) -> Result<(), DrawError> {
+        self.begin_path()?;
+        self.move_to(corner_1)?;
+        self.line_to(Coordinates::new(corner_2[0], corner_1[1]))?;
+        self.line_to(corner_2)?;
+        self.line_to(Coordinates::new(corner_1[0], corner_2[1]))?;
+        self.close_path()?;
+        self.stroke()
+    }
+
+    /// Draws many lines to approximate a curve
+    fn draw_curve(&self, coords: Vec<Coordinates>) -> Result<(), DrawError> {
+        if coords.len() == 0 {
+            return Err(DrawError::new("The number of coordinates cannot be 0"));
+        }
+
+        self.begin_path()?;
+        self.move_to(coords[0])?;
+
+        for coord in coords[1..].iter() {
+            self.line_to(*coord)?;
+        }
+
+        self.stroke()
+    }
+}
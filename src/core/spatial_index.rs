@@ -0,0 +1,204 @@
+//! A bounding volume hierarchy over the bounding boxes of objects, so that finding the objects near a point
+//! does not have to look at every object
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::core::*;
+
+/// Leaves hold at most this many items
+const LEAF_SIZE: usize = 8;
+
+#[derive(Clone, PartialEq, Debug)]
+enum Node {
+    /// Items start..end in the sorted list of items
+    Leaf{bb: BoundingBox, start: usize, end: usize},
+    Branch{bb: BoundingBox, left: usize, right: usize},
+}
+
+impl Node {
+    fn bb(&self) -> &BoundingBox {
+        match self {
+            Node::Leaf { bb, .. } | Node::Branch { bb, .. } => bb
+        }
+    }
+}
+
+/// Something to look at next in the nearest neighbour search, ordered so that the closest comes out of the heap first
+struct Candidate {
+    distance: f64,
+    node: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.distance == other.distance
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+/// An immutable index of items, each given by its bounding box and a number chosen by the caller. Build a new one when the items change
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpatialIndex {
+    items: Vec<(BoundingBox, usize)>,
+    nodes: Vec<Node>,
+}
+
+impl SpatialIndex {
+    pub fn new(mut items: Vec<(BoundingBox, usize)>) -> Self {
+        let mut nodes = vec![];
+        let n = items.len();
+        if n > 0 {
+            Self::build(&mut items, 0, n, &mut nodes);
+        }
+        Self { items, nodes }
+    }
+
+    /// Builds the subtree of items start..end and returns the index of its root. The items are split in half along
+    /// the longer side of their bounding box
+    fn build(items: &mut Vec<(BoundingBox, usize)>, start: usize, end: usize, nodes: &mut Vec<Node>) -> usize {
+        // Safe unwrap - there is always at least one item
+        let bb = items[start..end].iter().map(|x| x.0).reduce(|a, b| a.union(&b)).unwrap();
+
+        if end - start <= LEAF_SIZE {
+            nodes.push(Node::Leaf { bb, start, end });
+            return nodes.len() - 1;
+        }
+
+        let axis = if bb.width() >= bb.height() { 0 } else { 1 };
+        items[start..end].sort_by(|a, b| a.0.center()[axis].total_cmp(&b.0.center()[axis]));
+
+        let mid = (start + end) / 2;
+        let left = Self::build(items, start, mid, nodes);
+        let right = Self::build(items, mid, end, nodes);
+        nodes.push(Node::Branch { bb, left, right });
+        return nodes.len() - 1;
+    }
+
+    fn root(&self) -> Option<usize> {
+        if self.nodes.len() == 0 { None } else { Some(self.nodes.len() - 1) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns every item whose bounding box is within 'radius' of p, in no particular order
+    pub fn query(&self, p: Coordinates, radius: f64) -> Vec<usize> {
+        let mut found = vec![];
+        let mut stack: Vec<usize> = self.root().into_iter().collect();
+
+        while let Some(i) = stack.pop() {
+            if self.nodes[i].bb().distance_to(p) > radius {
+                continue;
+            }
+
+            match self.nodes[i] {
+                Node::Leaf { start, end, .. } => {
+                    found.extend(self.items[start..end].iter().filter(|x| x.0.distance_to(p) <= radius).map(|x| x.1));
+                },
+                Node::Branch { left, right, .. } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+
+        return found;
+    }
+
+    /// Returns the item closest to p and its distance, where 'distance' gives the exact distance to an item. It has to
+    /// be at least the distance to the bounding box of the item, so that everything further away than the best so far can be skipped
+    pub fn nearest<F: Fn(usize) -> f64>(&self, p: Coordinates, distance: F) -> Option<(usize, f64)> {
+        let mut best: Option<(usize, f64)> = None;
+        let mut heap = BinaryHeap::new();
+        if let Some(root) = self.root() {
+            heap.push(Candidate { distance: self.nodes[root].bb().distance_to(p), node: root });
+        }
+
+        while let Some(Candidate { distance: lower_bound, node }) = heap.pop() {
+            if let Some((_, d)) = best {
+                if lower_bound > d {
+                    break;
+                }
+            }
+
+            match self.nodes[node] {
+                Node::Leaf { start, end, .. } => {
+                    for (bb, item) in self.items[start..end].iter() {
+                        if best.is_some_and(|(_, d)| bb.distance_to(p) > d) {
+                            continue;
+                        }
+
+                        let d = distance(*item);
+                        if best.is_none_or(|(_, b)| d < b) {
+                            best = Some((*item, d));
+                        }
+                    }
+                },
+                Node::Branch { left, right, .. } => {
+                    for child in [left, right] {
+                        heap.push(Candidate { distance: self.nodes[child].bb().distance_to(p), node: child });
+                    }
+                }
+            }
+        }
+
+        return best;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A row of unit squares with gaps of one between them
+    fn squares(n: usize) -> SpatialIndex {
+        SpatialIndex::new((0..n).map(|i| {
+            let x = 2. * i as f64;
+            (BoundingBox::new(Coordinates::new(x, 0), Coordinates::new(x + 1., 1)), i)
+        }).collect())
+    }
+
+    #[test]
+    fn test_query() {
+        let index = squares(1000);
+        assert_eq!(index.len(), 1000);
+
+        let mut found = index.query(Coordinates::new(101.5, 0.5), 0.6);
+        found.sort();
+        assert_eq!(found, vec![50, 51]);
+        assert_eq!(index.query(Coordinates::new(101.5, 0.5), 0.4), Vec::<usize>::new());
+        assert_eq!(SpatialIndex::new(vec![]).query(Coordinates::new(0, 0), 1.), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_nearest() {
+        let index = squares(1000);
+        let p = Coordinates::new(300.7, 3);
+        let boxes = |i: usize| BoundingBox::new(Coordinates::new(2. * i as f64, 0), Coordinates::new(2. * i as f64 + 1., 1)).distance_to(p);
+        assert_eq!(index.nearest(p, boxes), Some((150, 2.)));
+
+        // The exact distance can be larger than the distance to the box
+        let centers = |i: usize| (Coordinates::new(2. * i as f64 + 0.5, 0.5) - p).magnitude();
+        assert_eq!(index.nearest(p, centers).map(|x| x.0), Some(150));
+        assert_eq!(SpatialIndex::new(vec![]).nearest(p, centers), None);
+    }
+}
@@ -0,0 +1,380 @@
+//! How a line is stroked: its width, color, dash pattern, caps, joins and arrow tips. Lengths are in pt like in Tikz,
+//! and the default style is the same as Tikz's default style so that it does not need to be written out
+
+use crate::core::*;
+
+/// Dash patterns. The presets are the same as the ones in Tikz and the custom pattern is a list of on/off lengths in pt
+#[derive(Clone, PartialEq, Debug)]
+pub enum DashPattern {
+    Solid,
+    Dotted,
+    DenselyDotted,
+    LooselyDotted,
+    Dashed,
+    DenselyDashed,
+    LooselyDashed,
+    DashDot,
+    Custom(Vec<f64>)
+}
+
+impl DashPattern {
+    /// Returns the on/off lengths in pt. Dots are as long as the line is wide
+    pub fn lengths(&self, line_width: f64) -> Vec<f64> {
+        match self {
+            DashPattern::Solid => vec![],
+            DashPattern::Dotted => vec![line_width, 2.],
+            DashPattern::DenselyDotted => vec![line_width, 1.],
+            DashPattern::LooselyDotted => vec![line_width, 4.],
+            DashPattern::Dashed => vec![3., 3.],
+            DashPattern::DenselyDashed => vec![3., 2.],
+            DashPattern::LooselyDashed => vec![3., 6.],
+            DashPattern::DashDot => vec![3., 2., line_width, 2.],
+            DashPattern::Custom(v) => v.clone()
+        }
+    }
+
+    fn tikzify(&self, opts: &TikzOutputOptions) -> Option<String> {
+        let s = match self {
+            DashPattern::Solid => return None,
+            DashPattern::Dotted => "dotted",
+            DashPattern::DenselyDotted => "densely dotted",
+            DashPattern::LooselyDotted => "loosely dotted",
+            DashPattern::Dashed => "dashed",
+            DashPattern::DenselyDashed => "densely dashed",
+            DashPattern::LooselyDashed => "loosely dashed",
+            DashPattern::DashDot => "dash dot",
+            DashPattern::Custom(v) => {
+                let pattern = v.chunks(2).map(|x| {
+                    match x {
+                        [on, off] => format!("on {}pt off {}pt", opts.number(*on), opts.number(*off)),
+                        _ => format!("on {}pt", opts.number(x[0]))
+                    }
+                }).collect::<Vec<String>>().join(" ");
+                return Some(format!("dash pattern={pattern}"));
+            }
+        };
+
+        Some(s.to_string())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Rect
+}
+
+impl LineCap {
+    /// The name of the cap in Tikz
+    pub fn tikz_name(&self) -> &'static str {
+        match self {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Rect => "rect",
+        }
+    }
+
+    /// The name of the cap on the Html canvas and in svg
+    pub fn canvas_name(&self) -> &'static str {
+        match self {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Rect => "square",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel
+}
+
+impl LineJoin {
+    /// The name of the join, which happens to be the same in Tikz, svg and the Html canvas
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel",
+        }
+    }
+}
+
+/// Arrow tips from the arrows.meta library
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ArrowTip {
+    None,
+    Stealth,
+    Latex,
+    To,
+    Bar,
+    Circle
+}
+
+impl ArrowTip {
+    fn tikz_name(&self) -> &'static str {
+        match self {
+            ArrowTip::None => "",
+            ArrowTip::Stealth => "{Stealth}",
+            ArrowTip::Latex => "{Latex}",
+            ArrowTip::To => "{To}",
+            ArrowTip::Bar => "{Bar}",
+            ArrowTip::Circle => "{Circle}",
+        }
+    }
+
+    /// Draws the tip with its point at 'at', pointing towards 'direction'. Lengths are in local coordinates.
+    /// We use the default size of arrows.meta which grows with the line width
+    pub fn draw_on_canvas(&self, c: &dyn RenderTarget, at: Coordinates, direction: Coordinates, line_width: f64) -> Result<(), DrawError> {
+        if *self == ArrowTip::None || direction.magnitude() == 0. {
+            return Ok(());
+        }
+
+        let length = 3. * PT + 4.5 * line_width;
+        let d = direction.normalize() * length;
+        let n = Coordinates::new(-d[1], d[0]) * 0.375;
+        let back = at - d;
+
+        c.begin_path()?;
+        match self {
+            ArrowTip::Stealth => {
+                c.move_to(at)?;
+                c.line_to(back + n)?;
+                c.line_to(at - d * 0.7)?;
+                c.line_to(back - n)?;
+                c.close_path()?;
+                c.fill()?;
+            },
+
+            ArrowTip::Latex => {
+                c.move_to(at)?;
+                c.line_to(back + n)?;
+                c.line_to(back - n)?;
+                c.close_path()?;
+                c.fill()?;
+            },
+
+            ArrowTip::To => {
+                c.move_to(back + n)?;
+                c.line_to(at)?;
+                c.line_to(back - n)?;
+            },
+
+            ArrowTip::Bar => {
+                c.move_to(at + n)?;
+                c.line_to(at - n)?;
+            },
+
+            ArrowTip::Circle => {
+                let r = length / 2.;
+                c.arc(at - d * 0.5, r, 0., 2. * std::f64::consts::PI, false)?;
+                c.fill()?;
+            },
+
+            ArrowTip::None => ()
+        };
+        c.stroke()
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct StrokeStyle {
+    /// Line width in pt
+    pub width: f64,
+
+    /// None means the default color, which is black
+    pub color: Option<Color>,
+    pub dash: DashPattern,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    pub start_arrow: ArrowTip,
+    pub end_arrow: ArrowTip,
+}
+
+impl StrokeStyle {
+    /// The default style of Tikz: a solid black 0.4pt line
+    pub fn new() -> Self {
+        Self {
+            width: 0.4,
+            color: None,
+            dash: DashPattern::Solid,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            start_arrow: ArrowTip::None,
+            end_arrow: ArrowTip::None,
+        }
+    }
+
+    pub fn width(mut self, width: f64) -> Self {
+        self.width = width;
+        return self;
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        return self;
+    }
+
+    pub fn dash(mut self, dash: DashPattern) -> Self {
+        self.dash = dash;
+        return self;
+    }
+
+    pub fn cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        return self;
+    }
+
+    pub fn join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        return self;
+    }
+
+    pub fn arrows(mut self, start: ArrowTip, end: ArrowTip) -> Self {
+        self.start_arrow = start;
+        self.end_arrow = end;
+        return self;
+    }
+
+    /// The color the line is drawn in, which is black unless set
+    pub fn effective_color(&self) -> Color {
+        self.color.clone().unwrap_or(Color::rgb(0, 0, 0))
+    }
+
+    /// Line width in local coordinates, which are cm
+    pub fn local_width(&self) -> f64 {
+        self.width * PT
+    }
+
+    /// Returns the Tikz options of this style, separated by commas, leaving out everything that is the same as the default.
+    /// Widths and dashes are always in pt, whatever the unit of the figure
+    pub fn tikz_options(&self, opts: &TikzOutputOptions) -> (Vec<String>, Preamble) {
+        let default = StrokeStyle::new();
+        let mut v = vec![];
+        let mut preamble = Preamble::new();
+
+        if let Some(color) = &self.color {
+            let (c, p) = color.to_tikz();
+            v.push(format!("color={c}"));
+            preamble.merge(&p);
+
+            // Tikz colors have no alpha
+            if color.alpha < 1. {
+                v.push(format!("draw opacity={}", opts.number(color.alpha)));
+            }
+        }
+
+        if self.width != default.width {
+            v.push(format!("line width={}pt", opts.number(self.width)));
+        }
+
+        if let Some(dash) = self.dash.tikzify(opts) {
+            v.push(dash);
+        }
+
+        if self.cap != default.cap {
+            v.push(format!("line cap={}", self.cap.tikz_name()));
+        }
+
+        if self.join != default.join {
+            v.push(format!("line join={}", self.join.name()));
+        }
+
+        if self.start_arrow != ArrowTip::None || self.end_arrow != ArrowTip::None {
+            v.push(format!("{}-{}", self.start_arrow.tikz_name(), self.end_arrow.tikz_name()));
+            preamble = preamble.library(TikzLibrary::ArrowsMeta);
+        }
+
+        return (v, preamble);
+    }
+
+    /// Returns the stroke attributes of an svg element
+    pub fn svgify(&self) -> String {
+        let color = self.effective_color().to_svg();
+        let mut s = format!("stroke=\"{color}\" stroke-width=\"{}\"", self.local_width().to_svg());
+
+        let dashes = self.dash.lengths(self.width);
+        if dashes.len() > 0 {
+            let dashes = dashes.iter().map(|x| (x * PT).to_svg()).collect::<Vec<String>>().join(" ");
+            s.push_str(&format!(" stroke-dasharray=\"{dashes}\""));
+        }
+
+        if self.cap != LineCap::Butt {
+            s.push_str(&format!(" stroke-linecap=\"{}\"", self.cap.canvas_name()));
+        }
+
+        if self.join != LineJoin::Miter {
+            s.push_str(&format!(" stroke-linejoin=\"{}\"", self.join.name()));
+        }
+
+        return s;
+    }
+}
+
+impl IsTikzPropertyField for StrokeStyle {
+    fn to_tikz(&self) -> (String, Preamble) {
+        let (v, p) = self.tikz_options(&TikzOutputOptions::default());
+        (v.join(", "), p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let (s, p) = StrokeStyle::new().to_tikz();
+        assert_eq!(s, "");
+        assert!(p.is_empty());
+    }
+
+    #[test]
+    fn test_tikz_options() {
+        let style = StrokeStyle::new()
+            .width(1.2)
+            .dash(DashPattern::DenselyDashed)
+            .cap(LineCap::Rect)
+            .join(LineJoin::Round)
+            .arrows(ArrowTip::None, ArrowTip::Stealth);
+
+        let (s, p) = style.to_tikz();
+        assert_eq!(s, "line width=1.2pt, densely dashed, line cap=rect, line join=round, -{Stealth}");
+        assert_eq!(p.lines(), vec!["\\usetikzlibrary{arrows.meta}"]);
+
+        let style = StrokeStyle::new().dash(DashPattern::Custom(vec![2., 1., 0.5]));
+        assert_eq!(style.to_tikz().0, "dash pattern=on 2pt off 1pt on 0.5pt");
+
+        let style = StrokeStyle::new().color(Color::parse("red!40!white").unwrap().with_alpha(0.5));
+        assert_eq!(style.to_tikz().0, "color=red!40!white, draw opacity=0.5");
+
+        // Numbers are written like everywhere else in the Tikz code
+        let style = StrokeStyle::new().width(0.1 + 0.2).dash(DashPattern::Custom(vec![1. / 3., 1.]));
+        assert_eq!(style.to_tikz().0, "line width=0.3pt, dash pattern=on 0.3333pt off 1pt");
+        let opts = TikzOutputOptions { precision: Some(1), unit_suffix: true, ..Default::default() };
+        assert_eq!(style.tikz_options(&opts).0, vec!["line width=0.3pt", "dash pattern=on 0.3pt off 1pt"]);
+    }
+
+    #[test]
+    fn test_dash_lengths() {
+        assert_eq!(DashPattern::Solid.lengths(0.4), Vec::<f64>::new());
+        assert_eq!(DashPattern::Dotted.lengths(0.8), vec![0.8, 2.]);
+        assert_eq!(DashPattern::DashDot.lengths(0.4), vec![3., 2., 0.4, 2.]);
+    }
+
+    #[test]
+    fn test_arrow_tip() {
+        let c = RecordingCanvas::new();
+        ArrowTip::Latex.draw_on_canvas(&c, Coordinates::new(1, 0), Coordinates::new(1, 0), 0.4 * PT).unwrap();
+        let calls = c.calls();
+        assert_eq!(calls[1], DrawCall::MoveTo(Coordinates::new(1, 0)));
+        assert_eq!(calls[calls.len() - 2..], [DrawCall::Fill, DrawCall::Stroke]);
+
+        let c = RecordingCanvas::new();
+        ArrowTip::None.draw_on_canvas(&c, Coordinates::new(1, 0), Coordinates::new(1, 0), 0.4 * PT).unwrap();
+        assert_eq!(c.calls(), vec![]);
+    }
+}
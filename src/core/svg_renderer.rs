@@ -0,0 +1,109 @@
+//! Creates SVG bindings to Rust, mirroring the Tikz renderer. We use the figure coordinates as SVG user units
+//! and set the document size in cm, so one unit on the figure is one cm on the page just like in Tikz
+
+use std::rc::Rc;
+use crate::core::*;
+
+/// Size of one Tikz pt in cm
+pub const PT: f64 = 2.54 / 72.27;
+
+/// Default line width of Tikz, which is 0.4pt
+pub const SVG_LINE_WIDTH: f64 = 0.4 * PT;
+
+pub trait SvgShape {
+    /// This returns the svg element and the region of the plane it covers
+    fn draw(&self) -> (String, BoundingBox);
+}
+
+/// SVG has the y-axis pointing downwards, so we flip the y coordinate
+pub fn svg_coords(c: Coordinates) -> (f64, f64) {
+    // 0 - y instead of -y so that we never print -0
+    (c[0], 0. - c[1])
+}
+
+/// Formats a coordinate as "x y" for use in path data
+pub fn svg_point(c: Coordinates) -> String {
+    let (x, y) = svg_coords(c);
+    format!("{} {}", x.to_svg(), y.to_svg())
+}
+
+pub struct SvgFigure {
+    data: Vec<Rc<dyn SvgShape>>
+}
+
+impl SvgFigure {
+    pub fn new() -> Self {
+        Self {
+            data: vec![]
+        }
+    }
+
+    /// Adds an element to the svg
+    pub fn draw<T: SvgShape + 'static>(mut self, s: T) -> Self {
+        self.data.push(Rc::new(s) as Rc<dyn SvgShape>);
+        return self;
+    }
+
+    /// Draws the svg figure. The viewBox is the union of the extent of every element,
+    /// padded a little bit so that strokes on the boundary are not clipped
+    pub fn output(&self) -> String {
+        let mut body = vec![];
+        let mut extent: Option<BoundingBox> = None;
+
+        for x in self.data.iter() {
+            let (b, bb) = x.draw();
+            body.push(b);
+            extent = Some(match extent {
+                Some(e) => e.union(&bb),
+                None => bb
+            });
+        }
+
+        let viewbox = extent
+            .unwrap_or(BoundingBox::from_point(Coordinates::new(0, 0)))
+            .expand(2. * SVG_LINE_WIDTH);
+
+        // The top left corner of the viewbox is the flipped version of the top left corner of the figure
+        let (x, y) = svg_coords(Coordinates::new(viewbox.min()[0], viewbox.max()[1]));
+        let w = viewbox.width();
+        let h = viewbox.height();
+
+        let body_text = body.iter()
+            .map(|x| format!("    {x}\n"))
+            .collect::<String>();
+
+        format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}cm\" height=\"{h}cm\" viewBox=\"{x} {y} {w} {h}\">\n{body_text}</svg>\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::figures::*;
+
+    #[test]
+    fn test_svg_coords() {
+        assert_eq!(svg_point(Coordinates::new(1, 2)), "1 -2");
+        assert_eq!(svg_point(Coordinates::new(-1, 0)), "-1 0");
+    }
+
+    #[test]
+    fn test_empty_figure() {
+        let svg = SvgFigure::new().output();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn test_viewbox() {
+        let svg = SvgFigure::new()
+            .draw(FOLine::new(Coordinates::new(0, 0), Coordinates::new(2, 1)).wrap())
+            .draw(FOLine::new(Coordinates::new(-1, 0), Coordinates::new(0, -3)).wrap())
+            .output();
+
+        let bb = BoundingBox::new(Coordinates::new(-1, -3), Coordinates::new(2, 1)).expand(2. * SVG_LINE_WIDTH);
+        let expected = format!("viewBox=\"{} {} {} {}\"", bb.min()[0], 0. - bb.max()[1], bb.width(), bb.height());
+        assert!(svg.contains(&expected), "{svg}");
+        assert!(svg.contains("<line x1=\"0\" y1=\"0\" x2=\"2\" y2=\"-1\""), "{svg}");
+    }
+}
@@ -0,0 +1,149 @@
+//! Controls how numbers and coordinates are written in the Tikz code. Raw f64 values like 0.30000000000000004
+//! are hard to read and change all the time, so by default we round them and tidy them up
+
+use crate::core::Coordinates;
+
+/// The length of one unit on the figure
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TikzUnit {
+    Cm,
+    Mm,
+    Pt
+}
+
+impl TikzUnit {
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            TikzUnit::Cm => "cm",
+            TikzUnit::Mm => "mm",
+            TikzUnit::Pt => "pt",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TikzOutputOptions {
+    /// The number of decimal places to round to. None prints the full f64
+    pub precision: Option<usize>,
+
+    /// Prints 1.5 instead of 1.5000
+    pub strip_trailing_zeros: bool,
+
+    /// Prints 0 instead of -0, which shows up a lot after rounding
+    pub normalize_negative_zero: bool,
+
+    /// The length of one unit on the figure
+    pub unit: TikzUnit,
+
+    /// If true, every length carries the unit, like (1cm, 2cm). Otherwise lengths are unitless and
+    /// the unit is set with the x= and y= options on the tikzpicture
+    pub unit_suffix: bool,
+}
+
+impl Default for TikzOutputOptions {
+    fn default() -> Self {
+        Self {
+            precision: Some(4),
+            strip_trailing_zeros: true,
+            normalize_negative_zero: true,
+            unit: TikzUnit::Cm,
+            unit_suffix: false,
+        }
+    }
+}
+
+impl TikzOutputOptions {
+    /// Formats a plain number, like an angle
+    pub fn number(&self, x: f64) -> String {
+        let mut s = match self.precision {
+            Some(p) => format!("{:.*}", p, x),
+            None => x.to_string()
+        };
+
+        if self.strip_trailing_zeros && s.contains('.') {
+            s = s.trim_end_matches('0').trim_end_matches('.').to_string();
+        }
+
+        if self.normalize_negative_zero && s.starts_with('-') && s[1..].chars().all(|c| c == '0' || c == '.') {
+            s.remove(0);
+        }
+
+        return s;
+    }
+
+    /// Formats a length on the figure, like a radius
+    pub fn length(&self, x: f64) -> String {
+        if self.unit_suffix {
+            return format!("{}{}", self.number(x), self.unit.suffix());
+        }
+        return self.number(x);
+    }
+
+    /// Formats a point as (x, y)
+    pub fn coords(&self, c: Coordinates) -> String {
+        format!("({}, {})", self.length(c[0]), self.length(c[1]))
+    }
+
+    /// The options to put on the tikzpicture environment. Tikz uses 1cm as the unit by default so we only need them otherwise
+    pub fn picture_options(&self) -> Option<String> {
+        if self.unit_suffix || self.unit == TikzUnit::Cm {
+            return None;
+        }
+
+        let u = self.unit.suffix();
+        Some(format!("[x=1{u}, y=1{u}]"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        let opts = TikzOutputOptions::default();
+        assert_eq!(opts.number(0.1 + 0.2), "0.3");
+        assert_eq!(opts.number(2.), "2");
+        assert_eq!(opts.number(-0.00001), "0");
+        assert_eq!(opts.number(-1.23456), "-1.2346");
+
+        let opts = TikzOutputOptions {
+            precision: Some(2),
+            strip_trailing_zeros: false,
+            normalize_negative_zero: false,
+            ..Default::default()
+        };
+        assert_eq!(opts.number(1.5), "1.50");
+        assert_eq!(opts.number(-0.001), "-0.00");
+
+        let opts = TikzOutputOptions {
+            precision: None,
+            ..Default::default()
+        };
+        assert_eq!(opts.number(0.1 + 0.2), "0.30000000000000004");
+    }
+
+    #[test]
+    fn test_units() {
+        let c = Coordinates::new(1, -0.5);
+        let opts = TikzOutputOptions::default();
+        assert_eq!(opts.coords(c), "(1, -0.5)");
+        assert_eq!(opts.picture_options(), None);
+
+        let opts = TikzOutputOptions {
+            unit: TikzUnit::Mm,
+            ..Default::default()
+        };
+        assert_eq!(opts.coords(c), "(1, -0.5)");
+        assert_eq!(opts.picture_options(), Some("[x=1mm, y=1mm]".to_string()));
+
+        let opts = TikzOutputOptions {
+            unit: TikzUnit::Pt,
+            unit_suffix: true,
+            ..Default::default()
+        };
+        assert_eq!(opts.coords(c), "(1pt, -0.5pt)");
+        assert_eq!(opts.length(2.), "2pt");
+        assert_eq!(opts.picture_options(), None);
+    }
+}
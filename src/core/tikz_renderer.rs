@@ -3,10 +3,11 @@
 use std::rc::Rc;
 use std::fmt::Display;
 use crate::figures::*;
+use crate::core::{Preamble, TikzOutputOptions, Coordinates, BoundingBox};
 
 pub trait TikzShape {
-    /// This returns the command and the preamble
-    fn draw(&self) -> (String, Option<String>);
+    /// This returns the command and what it needs in the preamble. Numbers should be formatted with opts
+    fn draw(&self, opts: &TikzOutputOptions) -> (String, Preamble);
 }
 
 pub enum TikzDrawMode {
@@ -26,55 +27,221 @@ impl TikzDrawMode {
 }
 
 pub struct TikzFigure {
-    data: Vec<Rc<dyn TikzShape>>
+    coordinates: Vec<(String, Coordinates)>,
+    data: Vec<Rc<dyn TikzShape>>,
+    caption: Option<String>,
+    label: Option<String>,
+    bounding_box: Option<BoundingBox>,
+    options: TikzOutputOptions,
 }
 
 impl TikzFigure {
     pub fn new() -> Self {
         Self {
-            data: vec![]
+            coordinates: vec![],
+            data: vec![],
+            caption: None,
+            label: None,
+            bounding_box: None,
+            options: TikzOutputOptions::default(),
         }
     }
 
-    /// Adds an element to the svg
+    /// Sets how numbers and units are written in the output
+    pub fn options(mut self, options: TikzOutputOptions) -> Self {
+        self.options = options;
+        return self;
+    }
+
+    /// Sets the caption used in the figure environment
+    pub fn caption<S: Into<String>>(mut self, caption: S) -> Self {
+        self.caption = Some(caption.into());
+        return self;
+    }
+
+    /// Sets the label used in the figure environment so the figure can be referenced with \ref
+    pub fn label<S: Into<String>>(mut self, label: S) -> Self {
+        self.label = Some(label.into());
+        return self;
+    }
+
+    /// Fixes the size of the picture with \useasboundingbox, so that it takes up exactly this much space on the page
+    pub fn use_as_bounding_box(mut self, bb: BoundingBox) -> Self {
+        self.bounding_box = Some(bb);
+        return self;
+    }
+
+    /// Defines a named coordinate with \coordinate, which comes before every element in the figure
+    pub fn coordinate<S: Into<String>>(mut self, name: S, at: Coordinates) -> Self {
+        self.coordinates.push((name.into(), at));
+        return self;
+    }
+
+    /// Adds an element to the figure
     pub fn draw<T: TikzShape + 'static>(mut self, s: T) -> Self {
         self.data.push(Rc::new(s) as Rc<dyn TikzShape>);
         return self;
     }
 
-    /// Draws the svg figure. We allow height and width to be Strings be
-    pub fn output(&self) -> String {
-        let mut body = vec![];
-        let mut preamble = vec![];
+    /// Returns the tikzpicture environment and the lines of the preamble that every object needs together
+    fn picture(&self) -> (String, Vec<String>) {
+        let mut body = String::new();
+        let mut preamble = Preamble::new();
+
+        // This has to come first, since the bounding box stops growing after it
+        if let Some(bb) = &self.bounding_box {
+            body.push_str(&format!("    \\useasboundingbox {} rectangle {};\n", self.options.coords(bb.min()), self.options.coords(bb.max())));
+        }
+
+        for (name, at) in self.coordinates.iter() {
+            body.push_str(&format!("    \\coordinate ({name}) at {};\n", self.options.coords(*at)));
+        }
 
         for x in self.data.iter() {
-            let (b, p) = x.draw();
-            body.push(b);
-            if let Some(pre) = p {
-                preamble.push(pre);
+            // Scopes take up more than one line
+            let (b, p) = x.draw(&self.options);
+            for line in b.lines() {
+                body.push_str(&format!("    {line}\n"));
             }
+            preamble.merge(&p);
+        }
+
+        let preamble = preamble.lines();
+        let picture_options = self.options.picture_options().unwrap_or_default();
+        let picture = format!("\\begin{{tikzpicture}}{picture_options}\n{body}\\end{{tikzpicture}}\n");
+        return (picture, preamble);
+    }
+
+    /// Draws the tikz figure. If any object needs something in the preamble, it is put on top with a comment
+    pub fn output(&self) -> String {
+        let (picture, preamble) = self.picture();
+
+        if preamble.len() > 0 {
+            let preams = preamble.join("\n");
+            return format!("% This is the preamble section. Please include it in the beginning of your document\n{preams}\n\n% This is the body of your Tikz Figure\n{picture}");
+        }
+
+        return picture;
+    }
+
+    /// Draws the tikz figure as a complete LaTeX document that compiles on its own
+    pub fn standalone(&self) -> String {
+        let (picture, preamble) = self.picture();
+        let preams = preamble.iter()
+            .map(|x| format!("{x}\n"))
+            .collect::<String>();
+
+        return format!("\\documentclass{{standalone}}\n\\usepackage{{tikz}}\n{preams}\n\\begin{{document}}\n{picture}\\end{{document}}\n");
+    }
+
+    /// Draws the tikz figure inside a figure environment with the caption and the label, ready to be put in a document.
+    /// Same as output(), the preamble is put on top with a comment
+    pub fn figure_environment(&self) -> String {
+        let (picture, preamble) = self.picture();
+
+        let mut body = String::from("\\begin{figure}[ht]\n    \\centering\n");
+        for line in picture.lines() {
+            body.push_str(&format!("    {line}\n"));
         }
 
-        let body_text = format!(r#"
-        \\begin{{tikzpicture}}
-            {}
-        \\end{{tikzpicture}}
-        "#, body.join("\n"));
+        if let Some(caption) = &self.caption {
+            body.push_str(&format!("    \\caption{{{caption}}}\n"));
+        }
+
+        if let Some(label) = &self.label {
+            body.push_str(&format!("    \\label{{{label}}}\n"));
+        }
+
+        body.push_str("\\end{figure}\n");
 
         if preamble.len() > 0 {
-            preamble.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
             let preams = preamble.join("\n");
-            let result = format!(r#"
-                % This is the preamble section. Please include it in the beginning of your document
-                {preams}
+            return format!("% This is the preamble section. Please include it in the beginning of your document\n{preams}\n\n% This is the body of your Tikz Figure\n{body}");
+        }
+
+        return body;
+    }
+}
 
-                % This is the body of your Tikz Figure
-                {body_text}
-            "#);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::*;
 
-            return result;
+    #[test]
+    fn test_output() {
+        let fig = TikzFigure::new()
+            .draw(FOLine::new(Coordinates::new(0, 0), Coordinates::new(1, 2)).wrap());
+        assert_eq!(fig.output(), "\\begin{tikzpicture}\n    \\draw (0, 0) -- (1, 2);\n\\end{tikzpicture}\n");
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let fig = TikzFigure::new()
+            .coordinate("A", Coordinates::new(0, 0))
+            .use_as_bounding_box(BoundingBox::new(Coordinates::new(-1, 0), Coordinates::new(2, 0.5)));
+        assert_eq!(fig.output(), concat!(
+            "\\begin{tikzpicture}\n",
+            "    \\useasboundingbox (-1, 0) rectangle (2, 0.5);\n",
+            "    \\coordinate (A) at (0, 0);\n",
+            "\\end{tikzpicture}\n"
+        ));
+    }
+
+    #[test]
+    fn test_standalone() {
+        let fig = TikzFigure::new()
+            .draw(FOPoint::new(Coordinates::new(1, 1)).wrap());
+        let doc = fig.standalone();
+        assert!(doc.starts_with("\\documentclass{standalone}\n\\usepackage{tikz}\n\n\\begin{document}\n\\begin{tikzpicture}\n"), "{doc}");
+        assert!(doc.ends_with("\\end{tikzpicture}\n\\end{document}\n"), "{doc}");
+    }
+
+    #[test]
+    fn test_standalone_preamble() {
+        struct NeedsLibrary;
+        impl TikzShape for NeedsLibrary {
+            fn draw(&self, _opts: &TikzOutputOptions) -> (String, Preamble) {
+                ("\\draw[-Stealth] (0, 0) -- (1, 1);".to_string(), Preamble::new().library(TikzLibrary::ArrowsMeta).package("amsmath"))
+            }
         }
 
-        return body_text;
+        let doc = TikzFigure::new().draw(NeedsLibrary).draw(NeedsLibrary).standalone();
+        assert!(doc.starts_with("\\documentclass{standalone}\n\\usepackage{tikz}\n\\usepackage{amsmath}\n\\usetikzlibrary{arrows.meta}\n\n\\begin{document}\n"), "{doc}");
+    }
+
+    #[test]
+    fn test_figure_environment() {
+        let fig = TikzFigure::new()
+            .draw(FOLine::new(Coordinates::new(0, 0), Coordinates::new(1, 2)).wrap())
+            .caption("A line")
+            .label("fig:line");
+
+        assert_eq!(fig.figure_environment(), concat!(
+            "\\begin{figure}[ht]\n",
+            "    \\centering\n",
+            "    \\begin{tikzpicture}\n",
+            "        \\draw (0, 0) -- (1, 2);\n",
+            "    \\end{tikzpicture}\n",
+            "    \\caption{A line}\n",
+            "    \\label{fig:line}\n",
+            "\\end{figure}\n"
+        ));
+
+        let env = TikzFigure::new().figure_environment();
+        assert!(!env.contains("\\caption") && !env.contains("\\label"), "{env}");
+    }
+
+    #[test]
+    fn test_output_options() {
+        let fig = TikzFigure::new()
+            .draw(FOLine::new(Coordinates::new(0.1 + 0.2, -0.00001), Coordinates::new(1, 2)).wrap());
+        assert!(fig.output().contains("\\draw (0.3, 0) -- (1, 2);"), "{}", fig.output());
+
+        let fig = fig.options(TikzOutputOptions {
+            unit: TikzUnit::Mm,
+            ..Default::default()
+        });
+        assert!(fig.output().starts_with("\\begin{tikzpicture}[x=1mm, y=1mm]\n"), "{}", fig.output());
     }
 }
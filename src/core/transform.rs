@@ -3,11 +3,21 @@
 //! - screen coordinates (e.g. mouse click)
 //! - local coordinates (e.g. figure)
 //! - client coordiantes (e.g. canvas)
+//!
+//! The view can be zoomed and panned by changing the scale and the origin. The origin is not rounded to whole pixels,
+//! so converting back and forth stays exact at any zoom level
 
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::core::{Coordinates};
+use crate::core::{Coordinates, BoundingBox};
+
+/// The scale the view starts with, in pixels per unit length
+pub const DEFAULT_SCALE: f64 = 100.;
+
+/// How far the view can zoom out and in, in pixels per unit length
+const MIN_SCALE: f64 = 1.;
+const MAX_SCALE: f64 = 100000.;
 
 #[derive(PartialEq, Clone, Copy)]
 pub struct Transform {
@@ -18,7 +28,7 @@ pub struct Transform {
     pub scale: f64,
 
     /// Where the origin ought to be in world coordinates. Coordinates can be very big or negative - means the origin is out of sight
-    pub origin: (i32, i32),
+    pub origin: (f64, f64),
 
     pub margins: (i32, i32, i32, i32),
 }
@@ -30,12 +40,14 @@ impl Transform {
         let w = sidebar_width as i32;
         let th = terminal_height as i32;
 
-        Self {
+        let mut tf = Self {
             screen_size: (1920, 1080),
-            scale: 100.,
-            origin: (0, 0),
+            scale: DEFAULT_SCALE,
+            origin: (0., 0.),
             margins: (h, 0, th, w),
-        }
+        };
+        tf.reset_origin();
+        return tf;
     }
 
     /// Returns size of main canvas, in width and height
@@ -47,20 +59,24 @@ impl Transform {
         (w, h)
     }
 
+    /// Returns the middle of the main canvas in world coordinates
+    fn center(&self) -> (f64, f64) {
+        let (top, _, _, left) = self.margins;
+        let (x, y) = self.get_self_size();
+        ((left + x/2) as f64, (top + y/2) as f64)
+    }
+
     /// Sets the origin right at the middle of the transform
     pub fn reset_origin(&mut self) {
-        let (top, right, bottom, left) = self.margins;
-        let (x, y) = self.get_self_size();
-        let ox = left + x/2;
-        let oy = top + y/2;
-        self.origin = (ox, oy);
+        self.origin = self.center();
     }
 
-    /// Recalculates the screen size
+    /// Recalculates the screen size. Whatever was in the middle of the canvas stays in the middle
     pub fn set_screen_size(&mut self, x: i32, y: i32) {
+        let (cx, cy) = self.center();
         self.screen_size = (x, y);
-        self.reset_origin();
-
+        let (nx, ny) = self.center();
+        self.origin = (self.origin.0 + nx - cx, self.origin.1 + ny - cy);
     }
 
     pub fn set_margin_top(&mut self, m: i32) {
@@ -83,18 +99,54 @@ impl Transform {
         self.scale = m;
     }
 
+    /// Zooms by 'factor' while the point under world position (x, y) stays where it is, like zooming with the mouse wheel
+    pub fn zoom_at(&mut self, factor: f64, x: i32, y: i32) {
+        let p = self.world_to_local(x, y);
+        self.scale = (self.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+        self.origin = (x as f64 - p[0] * self.scale, y as f64 + p[1] * self.scale);
+    }
+
+    /// Moves everything on the canvas by (dx, dy) pixels
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        self.origin = (self.origin.0 + dx as f64, self.origin.1 + dy as f64);
+    }
+
+    /// Zooms and pans so that the bounding box fills the canvas, leaving 'padding' pixels on every side.
+    /// A box without width and height is only moved to the middle
+    pub fn zoom_to_fit(&mut self, bb: BoundingBox, padding: f64) {
+        let (w, h) = self.get_self_size();
+        let (w, h) = ((w as f64 - 2. * padding).max(1.), (h as f64 - 2. * padding).max(1.));
+
+        let sx = if bb.width() > 0. { w / bb.width() } else { f64::INFINITY };
+        let sy = if bb.height() > 0. { h / bb.height() } else { f64::INFINITY };
+        let s = sx.min(sy);
+        if s.is_finite() {
+            self.scale = s.clamp(MIN_SCALE, MAX_SCALE);
+        }
+
+        let (cx, cy) = self.center();
+        let c = bb.center();
+        self.origin = (cx - c[0] * self.scale, cy + c[1] * self.scale);
+    }
+
+    /// Goes back to the scale the view starts with, with the origin in the middle
+    pub fn reset_view(&mut self) {
+        self.scale = DEFAULT_SCALE;
+        self.reset_origin();
+    }
+
     /// Transforms screen_x and screen_y into local_coordinates
     pub fn world_to_local(&self, x: i32, y: i32) -> Coordinates {
         // Satisfies x = origin + a * scale_x;
-        let a = (x - self.origin.0) as f64/self.scale;
-        let b = (self.origin.1 - y) as f64/self.scale;
+        let a = (x as f64 - self.origin.0)/self.scale;
+        let b = (self.origin.1 - y as f64)/self.scale;
         Coordinates::new(a, b)
     }
 
     #[inline(always)]
     fn ltw(&self, v: Coordinates) -> (f64, f64) {
-        let x = self.origin.0 as f64 + v[0] * self.scale;
-        let y = self.origin.1 as f64 - v[1] * self.scale;
+        let x = self.origin.0 + v[0] * self.scale;
+        let y = self.origin.1 - v[1] * self.scale;
         (x, y)
     }
 
@@ -136,7 +188,7 @@ mod tests {
         // x: 190 + 826/2
         // y: 60 + 536/2
 
-        assert_eq!(tf.origin, (603, 328));
+        assert_eq!(tf.origin, (603., 328.));
         assert_eq!(tf.local_to_world(Coordinates::new(0., 0.)), (603, 328));
     }
 
@@ -182,4 +234,59 @@ mod tests {
         assert_eq!(tf.local_to_world(Coordinates::new(-1., 0.)), (503, 328));
         assert_eq!(tf.world_to_local(503, 328), Coordinates::new(-1., 0.));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_zoom() {
+        let mut tf = Transform::new(60, 190, 150);
+        tf.set_screen_size(1016, 746);
+
+        // The point under the cursor stays put however far in or out we go
+        let p = tf.world_to_local(700, 300);
+        for factor in [1.1, 1.1, 0.5, 3., 0.001] {
+            tf.zoom_at(factor, 700, 300);
+            assert_eq!(tf.world_to_local(700, 300), p);
+            assert_eq!(tf.local_to_world(p), (700, 300));
+        }
+        assert_eq!(tf.scale, MIN_SCALE);
+
+        // Panning moves everything by the same number of pixels
+        tf.zoom_at(37., 1, 2);
+        let q = Coordinates::new(1.25, -3.5);
+        let (x, y) = tf.ltw(q);
+        tf.pan(10, -20);
+        assert_eq!(tf.ltw(q), (x + 10., y - 20.));
+
+        tf.reset_view();
+        assert_eq!(tf.scale, DEFAULT_SCALE);
+        assert_eq!(tf.local_to_world(Coordinates::new(0., 0.)), (603, 328));
+    }
+
+    #[test]
+    fn test_zoom_to_fit() {
+        let mut tf = Transform::new(60, 190, 150);
+        tf.set_screen_size(1016, 746);
+
+        // The canvas is 826 x 536, so the height decides the scale
+        tf.zoom_to_fit(BoundingBox::new(Coordinates::new(1., 1.), Coordinates::new(3., 6.)), 18.);
+        assert_eq!(tf.scale, 100.);
+        assert_eq!(tf.local_to_world(Coordinates::new(2., 3.5)), (603, 328));
+        assert_eq!(tf.local_to_world(Coordinates::new(2., 6.)), (603, 78));
+
+        // A single point only gets moved to the middle
+        tf.zoom_to_fit(BoundingBox::from_point(Coordinates::new(5., 5.)), 18.);
+        assert_eq!(tf.scale, 100.);
+        assert_eq!(tf.local_to_world(Coordinates::new(5., 5.)), (603, 328));
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut tf = Transform::new(60, 190, 150);
+        tf.set_screen_size(1016, 746);
+        tf.pan(50, 50);
+
+        // Whatever was in the middle stays in the middle
+        let p = tf.world_to_local(603, 328);
+        tf.set_screen_size(1216, 946);
+        assert_eq!(tf.world_to_local(703, 428), p);
+    }
+}
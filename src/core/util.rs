@@ -10,8 +10,10 @@ pub use cheap_string::*;
 pub use coord_transform::*;
 pub use scoped_vec::ScopedVec;
 
+use crate::core::Preamble;
+
 pub trait IsTikzPropertyField {
-    fn to_tikz(&self) -> (String, Option<String>);
+    fn to_tikz(&self) -> (String, Preamble);
 }
 
 pub trait IsSVGPropertyField {
@@ -19,27 +21,6 @@ pub trait IsSVGPropertyField {
 }
 
 
-#[derive(Clone, Copy, PartialEq)]
-pub struct Color(pub u8, pub u8, pub u8);
-
-impl IsSVGPropertyField for Color {
-    fn to_svg(&self) -> String {
-        let Color(r, g, b) = self;
-        return format!("rgb({r},{g},{b})");
-    }
-}
-
-impl IsTikzPropertyField for Color {
-    /// This function returns two strings - the first one being the one in the commands,
-    /// the second one being any side effects on the preamble - for example using patterns
-    /// and defining colors
-    fn to_tikz(&self) -> (String, Option<String>) {
-        let Color(r, g, b) = self;
-        let st = format!("{{rgb,255:red,{r};green,{g};blue,{b}}}");
-        return (st, None)
-    }
-}
-
 macro_rules! propertify {
     ($($t:ty), *) => {
         $ (
@@ -50,8 +31,8 @@ macro_rules! propertify {
             }
 
             impl IsTikzPropertyField for $t {
-                fn to_tikz(&self) -> (String, Option<String>) {
-                    (self.to_string(), None)
+                fn to_tikz(&self) -> (String, Preamble) {
+                    (self.to_string(), Preamble::new())
                 }
             }
         )*
@@ -0,0 +1,14 @@
+//! The core logic of the app that does not depend on the browser: the command parser and the figure complex
+//! which keeps the figure and the terminal text in sync. The web app and the command line tool both drive this
+
+pub mod figure_object_complex;
+pub mod history;
+pub mod parser;
+pub mod selection;
+pub mod terminal_text_renderer;
+
+pub use figure_object_complex::{FigureComplex, FigureObjectComplex, FigureRenderType};
+pub use history::{EditCommand, History};
+pub use parser::{parse, parse_command, ParsedCommand, coordinate_command, parse_move, move_command, parse_remove, remove_command, initialize_parser, ParserError, ParserErrorType};
+pub use selection::{Selection, draw_marquee};
+pub use terminal_text_renderer::TerminalTextRenderer;
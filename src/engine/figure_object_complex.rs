@@ -0,0 +1,970 @@
+//! The core logic of the wrapper app, responsible for synchronization of everything.
+//! The actual "render on all different types of canvas" logic is the figures part of the app
+//!
+//! What happens when we click on the main canvas (say, to initialize a point):
+//!  1. The canvas sensor detects the mouse input and returns the coordinates back to the canvas manager
+//!  2. The canvas manager parses the mouse input along with the side bar mode etc. to make it into a figure object complex (FOC)
+//!  3. The canvas manager draws the figure object complex
+//!  4. The canvas manager triggers a rerender on the canvas renderer and the terminal
+//!
+//! What happens when we type something on the terminal (say, point(1, 2)):
+//!  1. The terminal returns the text back to the canvas manager
+//!  2. The canvas manager passes the text to parse() which performs the parsing and returns a Result<FOC, Error>
+//!  3. If the result contains a FOC, draw it
+//!  4. The canvas manager triggers a rerender on the canvas renderer and the terminal
+//!
+//! This also handles the side bar state and how we draw certain objects across multiple frames.
+//! With the move tool, clicking selects objects and dragging moves the selection or selects everything in a rectangle
+
+#[cfg(feature = "web")]
+use yew::prelude::*;
+
+use crate::figures::*;
+use crate::engine::*;
+#[cfg(feature = "web")]
+use crate::app::SideBarType;
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use crate::core::*;
+
+/// Objects this many pixels away from a click still count as clicked
+const PICK_RADIUS: f64 = 5.;
+
+enum DrawState {
+    Point,
+    Curve,
+    Move,
+    None
+}
+
+/// What the mouse is doing with the move tool, from when it is pressed until it is released
+enum Gesture {
+    /// Shifting the selection. 'before' holds the selected objects as they were when the dragging started
+    Translate { from: Coordinates, before: Vec<(ObjectId, DrawableObject)> },
+
+    /// Dragging out a rectangle to select everything inside it. With shift, the selection is added to instead of replaced
+    Marquee { from: Coordinates, to: Coordinates, shift: bool },
+}
+
+#[derive(Clone)]
+/// A figure object complex is a string plus interior mutable drawable object. The id is what the object is drawn with on the figure
+pub struct FigureObjectComplex {
+    pub id: ObjectId,
+    pub st: CheapString,
+    pub fo: Rc<RefCell<DrawableObject>>
+}
+
+impl Debug for FigureObjectComplex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FOC({}, command:{}, obj:{:?})", self.id, self.st, self.fo.borrow())
+    }
+}
+
+impl FigureObjectComplex {
+    pub fn new(x: DrawableObject, s: String) -> Self {
+        let st = s.wrap();
+        let fo = Rc::new(RefCell::new(x));
+        return Self {
+            id: ObjectId::new(), st, fo
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FigureRenderType {
+    DoNothing,
+    Render,
+    Rerender,
+    RerenderLast,
+    Error(CheapString)
+}
+
+/// A figure-terminal complex is a proxy for the figure but houses figureobject complexes. Every change to the
+/// figure goes through an edit command so that it can be undone and redone
+pub struct FigureComplex {
+    basis: Vec<FigureObjectComplex>,
+    fig: Figure,
+    ttext: TerminalTextRenderer,
+    state: DrawState,
+    trail: ScopedVec<Coordinates>,
+    history: History,
+    selection: Selection,
+    gesture: Option<Gesture>,
+
+    /// True if pressing the mouse has already changed the selection, so the click that comes after releasing it should not
+    gesture_selected: bool,
+
+    /// The number of pixels one unit length takes up on the screen, so that clicks can be a few pixels off
+    view_scale: f64
+}
+
+impl FigureComplex {
+    pub fn new() -> Self {
+        FigureComplex {
+            basis: vec![],
+            fig: Figure::new(),
+            ttext: TerminalTextRenderer::new(),
+            state: DrawState::None,
+            trail: ScopedVec::new(),
+            history: History::new(),
+            selection: Selection::new(),
+            gesture: None,
+            gesture_selected: false,
+            view_scale: 100.
+        }
+    }
+
+    /// Does the command and records it in the history
+    fn edit(&mut self, cmd: EditCommand) {
+        self.apply(&cmd);
+        self.history.push(cmd);
+    }
+
+    /// Does the command without touching the history
+    fn apply(&mut self, cmd: &EditCommand) {
+        match cmd {
+            EditCommand::Add { foc } => {
+                self.basis.push(foc.clone());
+                self.fig.draw_with_id(foc.id, foc.fo.borrow().clone());
+            },
+
+            EditCommand::TerminalLine { line } => {
+                self.ttext.push(line.clone());
+            },
+
+            EditCommand::Remove { foc, .. } => {
+                self.basis.retain(|x| x.id != foc.id);
+                self.fig.remove(foc.id);
+            },
+
+            EditCommand::Modify { id, after, .. } | EditCommand::Move { id, after, .. } | EditCommand::Restyle { id, after, .. } => {
+                self.set_object(*id, after.clone());
+            },
+
+            EditCommand::MoveCoordinate { name, after, .. } => {
+                self.fig.define_coordinate(name.clone(), *after);
+            }
+        }
+    }
+
+    /// Takes back the command without touching the history
+    fn revert(&mut self, cmd: &EditCommand) {
+        match cmd {
+            EditCommand::Add { foc } => {
+                self.basis.retain(|x| x.id != foc.id);
+                self.fig.remove(foc.id);
+            },
+
+            EditCommand::TerminalLine { line } => {
+                self.ttext.remove_last(line);
+            },
+
+            EditCommand::Remove { foc, position } => {
+                self.fig.insert(*position, foc.id, foc.fo.borrow().clone());
+
+                // The figure complexes are kept in the same order as in the figure
+                let i = self.basis.iter()
+                    .take_while(|x| self.fig.position(x.id).is_none_or(|p| p < *position))
+                    .count();
+                self.basis.insert(i, foc.clone());
+            },
+
+            EditCommand::Modify { id, before, .. } | EditCommand::Move { id, before, .. } | EditCommand::Restyle { id, before, .. } => {
+                self.set_object(*id, before.clone());
+            },
+
+            EditCommand::MoveCoordinate { name, before: Some(before), .. } => {
+                self.fig.move_coordinate(name, *before);
+            },
+
+            // Everything drawn with it has been taken back before, since it came later
+            EditCommand::MoveCoordinate { name, before: None, .. } => {
+                self.fig.remove_coordinate(name);
+            }
+        }
+    }
+
+    /// Puts 'obj' in place of the object with the id in both the figure and its figure object complex
+    fn set_object(&mut self, id: ObjectId, obj: DrawableObject) -> bool {
+        if self.fig.replace(id, obj.clone()).is_none() {
+            return false;
+        }
+
+        if let Some(foc) = self.basis.iter().find(|x| x.id == id) {
+            *foc.fo.borrow_mut() = obj;
+        }
+        return true;
+    }
+
+    /// Draws a figure object complex on self, and writes its command in the terminal
+    fn draw(&mut self, d: FigureObjectComplex) {
+        let line = d.st.clone();
+        self.edit(EditCommand::Add { foc: d });
+        self.edit(EditCommand::TerminalLine { line });
+    }
+
+    /// Starts a transaction: every edit until it ends is undone and redone in one step. Does nothing if one has already begun
+    pub fn begin_transaction(&mut self) {
+        self.history.begin();
+    }
+
+    /// Ends the transaction that has begun, if any
+    pub fn end_transaction(&mut self) {
+        self.history.end();
+    }
+
+    /// Returns the figure object complex drawn with the id
+    pub fn get(&self, id: ObjectId) -> Option<&FigureObjectComplex> {
+        self.basis.iter().find(|x| x.id == id)
+    }
+
+    /// Removes the object from the figure. The command that made it stays in the terminal history,
+    /// and the remove command is written after it so that replaying the terminal removes it too
+    pub fn remove(&mut self, id: ObjectId) -> Option<DrawableObject> {
+        let position = self.fig.position(id)?;
+        return self.remove_with_line(id, remove_command(position).wrap());
+    }
+
+    /// Removes the object and writes the line in the terminal, which are undone together
+    fn remove_with_line(&mut self, id: ObjectId, line: CheapString) -> Option<DrawableObject> {
+        let position = self.fig.position(id)?;
+        let obj = self.fig.get(id)?.clone();
+        let foc = match self.get(id) {
+            Some(foc) => foc.clone(),
+            None => FigureObjectComplex { id, st: obj.repr().wrap(), fo: Rc::new(RefCell::new(obj.clone())) }
+        };
+
+        self.begin_transaction();
+        self.edit(EditCommand::Remove { foc, position });
+        self.edit(EditCommand::TerminalLine { line });
+        self.end_transaction();
+
+        self.selection.retain_in(&self.fig);
+        return Some(obj);
+    }
+
+    /// Puts 'obj' in place of the object with the id, keeping its place in the figure. Returns false if there is no such object
+    pub fn replace(&mut self, id: ObjectId, obj: DrawableObject) -> bool {
+        let before = match self.fig.get(id) {
+            Some(x) => x.clone(),
+            None => return false
+        };
+
+        self.edit(EditCommand::Modify { id, before, after: obj });
+        return true;
+    }
+
+    /// Shifts the object with the id by 'by'. Returns false if there is no such object
+    pub fn move_object(&mut self, id: ObjectId, by: Coordinates) -> bool {
+        let before = match self.fig.get(id) {
+            Some(x) => x.clone(),
+            None => return false
+        };
+
+        let after = before.transformed(&Affine2::translate(by));
+        self.edit(EditCommand::Move { id, by, before, after });
+        return true;
+    }
+
+    /// Puts 'obj', which is the object with the id drawn in another style, in its place. Returns false if there is no such object
+    pub fn restyle(&mut self, id: ObjectId, obj: DrawableObject) -> bool {
+        let before = match self.fig.get(id) {
+            Some(x) => x.clone(),
+            None => return false
+        };
+
+        self.edit(EditCommand::Restyle { id, before, after: obj });
+        return true;
+    }
+
+    /// Moves the named coordinate and writes the command that does the same in the terminal. Everything drawn with a reference
+    /// to it moves along, which can be anywhere in the figure, so the canvas has to be rerendered. Does nothing if there is no coordinate with that name
+    pub fn move_coordinate(&mut self, name: &str, to: Coordinates) -> FigureRenderType {
+        let before = match self.fig.coordinate(name) {
+            Some(x) => x.get(),
+            None => return FigureRenderType::DoNothing
+        };
+
+        self.begin_transaction();
+        self.edit(EditCommand::MoveCoordinate { name: name.to_string(), before: Some(before), after: to });
+        self.edit(EditCommand::TerminalLine { line: coordinate_command(name, to).wrap() });
+        self.end_transaction();
+        return FigureRenderType::Rerender;
+    }
+
+    /// Takes back the last transaction, last edit first. Everything has to be rerendered afterwards since anything could have changed
+    pub fn undo(&mut self) -> FigureRenderType {
+        match self.history.undo() {
+            Some(t) => {
+                for cmd in t.iter().rev() {
+                    self.revert(cmd);
+                }
+                self.selection.retain_in(&self.fig);
+                FigureRenderType::Rerender
+            },
+            None => FigureRenderType::DoNothing
+        }
+    }
+
+    /// Does the last undone transaction again
+    pub fn redo(&mut self) -> FigureRenderType {
+        match self.history.redo() {
+            Some(t) => {
+                for cmd in t.iter() {
+                    self.apply(cmd);
+                }
+                self.selection.retain_in(&self.fig);
+                FigureRenderType::Rerender
+            },
+            None => FigureRenderType::DoNothing
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Returns the objects selected with the move tool
+    pub fn selection(&self) -> &Selection {
+        &self.selection
+    }
+
+    /// Sets how many pixels one unit length takes up on the screen
+    pub fn set_view_scale(&mut self, scale: f64) {
+        self.view_scale = scale;
+    }
+
+    /// Returns the topmost object under v, if any
+    fn pick(&self, v: Coordinates) -> Option<ObjectId> {
+        self.fig.hit_test(v, PICK_RADIUS / self.view_scale).first().copied()
+    }
+
+    /// Returns the figure with everything drawn so far
+    pub fn figure(&self) -> &Figure {
+        &self.fig
+    }
+
+    /// Returns the figure mutably. This is for setting figure-wide properties like the caption.
+    /// Draw on the figure through the figure complex, otherwise the terminal text goes out of sync
+    pub fn figure_mut(&mut self) -> &mut Figure {
+        &mut self.fig
+    }
+
+    /// Draws a figure with the text prompt. Offloads the text to the parser. Returns what has to be drawn again on the canvas
+    pub fn draw_with_text<S1: StringLike>(&mut self, s: S1) -> Result<FigureRenderType, ParserError> {
+        log!(format!("Trying to draw {}", s));
+        let wrapped_text = s.wrap();
+
+        // Moves and removes refer to objects already in the figure, so they are not drawn like everything else
+        if let Some((positions, by)) = parse_move(wrapped_text.clone()) {
+            return self.move_with_text(wrapped_text, positions, by);
+        }
+
+        if let Some(position) = parse_remove(wrapped_text.clone()) {
+            return self.remove_with_text(wrapped_text, position);
+        }
+
+        let cmd = parse_command(wrapped_text.clone(), self.fig.coordinates())?;
+
+        // Everything the command draws or defines is undone together with its line in the terminal
+        self.begin_transaction();
+
+        // Moving a named coordinate moves everything drawn with it, which can be anywhere in the figure
+        let render_type = if cmd.coordinates.len() > 0 { FigureRenderType::Rerender } else { FigureRenderType::Render };
+        for (name, after) in cmd.coordinates.into_iter() {
+            let before = self.fig.coordinate(&name).map(|x| x.get());
+            self.edit(EditCommand::MoveCoordinate { name, before, after });
+        }
+
+        if cmd.focs.len() > 0 {
+            for foc in cmd.focs.into_iter() {
+                log!(format!("Drawing {:?}", foc));
+                self.edit(EditCommand::Add { foc });
+            }
+        }
+        else {
+            // Nothing to draw but we weren't dead - means its a valid command that expands to something that we do not have to draw
+            log!(format!("Pushing {}", s));
+        }
+
+        self.edit(EditCommand::TerminalLine { line: wrapped_text.clone() });
+        self.end_transaction();
+
+        Ok(render_type)
+    }
+
+    /// Shifts the objects at the positions, counting from the bottom of the figure, and writes the move command in the terminal.
+    /// The objects have to be erased from where they were, so everything gets rerendered
+    fn move_with_text(&mut self, line: CheapString, positions: Vec<usize>, by: Coordinates) -> Result<FigureRenderType, ParserError> {
+        let ids = self.fig.iter().map(|x| x.0).collect::<Vec<ObjectId>>();
+        if let Some(p) = positions.iter().find(|p| **p >= ids.len()) {
+            return Err(ParserError {
+                error_type: ParserErrorType::FunctionEvaluateError,
+                msg: format!("There is no object {} to move - only {} objects are drawn", p, ids.len()),
+                src: "FigureComplex::move_with_text()"
+            });
+        }
+
+        self.begin_transaction();
+        for p in positions {
+            self.move_object(ids[p], by);
+        }
+        self.edit(EditCommand::TerminalLine { line });
+        self.end_transaction();
+
+        Ok(FigureRenderType::Rerender)
+    }
+
+    /// Removes the object at the position, counting from the bottom of the figure, and writes the remove command in the terminal
+    fn remove_with_text(&mut self, line: CheapString, position: usize) -> Result<FigureRenderType, ParserError> {
+        let id = match self.fig.iter().nth(position) {
+            Some((id, _)) => id,
+            None => return Err(ParserError {
+                error_type: ParserErrorType::FunctionEvaluateError,
+                msg: format!("There is no object {} to remove - only {} objects are drawn", position, self.fig.len()),
+                src: "FigureComplex::remove_with_text()"
+            })
+        };
+
+        self.remove_with_line(id, line);
+        Ok(FigureRenderType::Rerender)
+    }
+
+    /// This unpacks the figure complex into a bunch of terminal commands.
+    /// Main method used to render terminal text
+    #[cfg(feature = "web")]
+    pub fn get_terminal_text(&self) -> Html {
+        self.ttext.unpack_html()
+    }
+
+    /// Renders the canvas
+    pub fn render(&self, canvas: &dyn RenderTarget) -> Result<(), DrawError> {
+        let y = self.fig.render(|x| {
+            x.draw_on_canvas(canvas)
+        });
+
+        for x in y {
+            x?;
+        }
+
+        Ok(())
+    }
+
+    /// Rerenders the canvas from scratch, with the selection and the marquee on top of everything
+    pub fn rerender(&self, canvas: &dyn RenderTarget) -> Result<(), DrawError> {
+        canvas.erase()?;
+
+        let y = self.fig.load_all(|x| {
+            x.draw_on_canvas(canvas)
+        });
+
+        for x in y {
+            x?;
+        }
+
+        self.selection.draw_on_canvas(&self.fig, canvas)?;
+
+        if let Some(Gesture::Marquee { from, to, .. }) = &self.gesture {
+            draw_marquee(*from, *to, canvas)?;
+        }
+
+        Ok(())
+    }
+
+    /// Rerenders the last object of the canvas
+    pub fn rerender_last(&self, canvas: &dyn RenderTarget) -> Result<(), DrawError> {
+        let y = self.fig.load_last(|x| {
+            x.draw_on_canvas(canvas)
+        });
+
+        for x in y {
+            x?;
+        }
+
+        Ok(())
+    }
+
+    /// Changes the tool. Leaving the move tool deselects everything, in which case the canvas has to be rerendered
+    #[cfg(feature = "web")]
+    pub fn set_state(&mut self, state: SideBarType) -> FigureRenderType {
+        self.state = match state {
+            SideBarType::Path => DrawState::Curve,
+            SideBarType::Point => DrawState::Point,
+            SideBarType::Move => DrawState::Move,
+            _ => DrawState::None
+        };
+
+        if matches!(self.state, DrawState::Move) || self.selection.is_empty() {
+            return FigureRenderType::DoNothing;
+        }
+
+        self.selection.clear();
+        return FigureRenderType::Rerender;
+    }
+
+    /// Resets the trail by creating a new Rc
+    fn reset_trail(&mut self) {
+        self.trail.close();
+        self.trail = ScopedVec::new();
+    }
+
+    /// This represents a click on the canvas. Handles this click on the local coordinates and transforms it into an object.
+    /// With the move tool, this selects the object clicked on, or adds it to the selection or takes it out if shift is held
+    pub fn click(&mut self, v: Coordinates, shift: bool) -> FigureRenderType {
+        match self.state {
+            DrawState::Point => {
+                log!("I should draw a point");
+                let p = Point::new(v);
+                let s = p.repr();
+                self.begin_transaction();
+                self.draw(FigureObjectComplex::new(p.wrap(), s));
+                self.end_transaction();
+                return FigureRenderType::Render;
+            },
+
+            DrawState::Move => {
+                // Pressing the mouse has already done the selecting
+                if self.gesture_selected {
+                    self.gesture_selected = false;
+                    return FigureRenderType::DoNothing;
+                }
+
+                match (self.pick(v), shift) {
+                    (Some(id), true) => self.selection.toggle(id),
+                    (Some(id), false) => self.selection.select_only(id),
+                    (None, true) => return FigureRenderType::DoNothing,
+                    (None, false) => self.selection.clear()
+                }
+                return FigureRenderType::Rerender;
+            },
+
+            _ => {
+                return FigureRenderType::DoNothing;
+            }
+        };
+    }
+
+    /// Drops a gesture that never stopped, like when the mouse was released where the canvas could not see it. Dragged objects
+    /// go back to where they were, and a curve being drawn is kept as it is
+    fn cancel_gesture(&mut self) {
+        if let Some(Gesture::Translate { before, .. }) = self.gesture.take() {
+            for (id, obj) in before {
+                self.set_object(id, obj);
+            }
+        }
+
+        self.reset_trail();
+        self.end_transaction();
+    }
+
+    /// This is called when we sense that the user starts dragging. Everything done until the dragging stops is one transaction
+    pub fn start_dragging(&mut self, v: Coordinates, shift: bool) -> FigureRenderType {
+        self.cancel_gesture();
+        self.begin_transaction();
+        self.gesture_selected = false;
+
+        match self.state {
+            DrawState::Curve => {
+                self.trail.push(v);
+                let c = Curve::new(self.trail.shallow_copy());
+                let s = c.repr();
+                self.draw(FigureObjectComplex::new(c.wrap(), s));
+                return FigureRenderType::Render;
+            },
+
+            DrawState::Move => {
+                let id = match self.pick(v) {
+                    Some(id) => id,
+                    None => {
+                        self.gesture = Some(Gesture::Marquee { from: v, to: v, shift });
+                        return FigureRenderType::DoNothing;
+                    }
+                };
+
+                // Dragging an object that is not selected selects it first
+                if !self.selection.contains(id) {
+                    if shift { self.selection.add(id) } else { self.selection.select_only(id) }
+                    self.gesture_selected = true;
+                }
+
+                let before = self.selection.ids().iter()
+                    .filter_map(|x| self.fig.get(*x).map(|obj| (*x, obj.clone())))
+                    .collect();
+                self.gesture = Some(Gesture::Translate { from: v, before });
+                return FigureRenderType::Rerender;
+            },
+
+            _ => ()
+        }
+        return FigureRenderType::DoNothing
+    }
+
+    /// This is called when we sense that the user is dragging
+    pub fn dragging(&mut self, v: Coordinates) -> FigureRenderType {
+
+        match self.state {
+            DrawState::Curve => {
+                self.trail.push(v);
+                // Ok this is actually handwavy unsafe rust. What (ought to) happen is that the curve
+                // stores a copy of self.trail, which we have just appended the new coordinates
+                // Now we just need to rerender the canvas and the curve should draw itself with
+                // the new coordinates
+
+                return FigureRenderType::RerenderLast;
+            },
+
+            DrawState::Move => {
+                match &mut self.gesture {
+                    Some(Gesture::Translate { from, before }) => {
+                        // Objects are moved from where they started rather than from the last frame, so nothing adds up
+                        let tf = Affine2::translate(v - *from);
+                        for (id, obj) in before.clone() {
+                            self.set_object(id, obj.transformed(&tf));
+                        }
+                        return FigureRenderType::Rerender;
+                    },
+
+                    Some(Gesture::Marquee { to, .. }) => {
+                        *to = v;
+                        return FigureRenderType::Rerender;
+                    },
+
+                    None => ()
+                }
+            },
+
+            _ => ()
+        }
+        return FigureRenderType::DoNothing;
+    }
+
+    /// Records the moves of a translate gesture that has finished, along with the terminal command that does the same
+    fn finish_translate(&mut self, from: Coordinates, before: Vec<(ObjectId, DrawableObject)>, v: Coordinates) {
+        let by = v - from;
+        if by == Coordinates::new(0, 0) {
+            return;
+        }
+
+        // The terminal refers to objects by where they are in the figure, since ids are not part of any command
+        let tf = Affine2::translate(by);
+        let mut positions = vec![];
+        for (id, before) in before.into_iter() {
+            let after = before.transformed(&tf);
+            self.set_object(id, after.clone());
+            self.history.push(EditCommand::Move { id, by, before, after });
+            positions.extend(self.fig.position(id));
+        }
+        positions.sort();
+
+        self.edit(EditCommand::TerminalLine { line: move_command(&positions, by).wrap() });
+        self.gesture_selected = true;
+    }
+
+    /// Selects everything inside the rectangle of a marquee gesture that has finished. A rectangle too small to see is a click instead
+    fn finish_marquee(&mut self, from: Coordinates, v: Coordinates, shift: bool) {
+        let marquee = BoundingBox::new(from, v);
+        if marquee.width().max(marquee.height()) * self.view_scale < PICK_RADIUS {
+            return;
+        }
+
+        if !shift {
+            self.selection.clear();
+        }
+
+        let inside = self.fig.iter()
+            .filter(|(_, obj)| obj.bounding_box().is_some_and(|bb| marquee.contains(&bb)))
+            .map(|(id, _)| id)
+            .collect::<Vec<ObjectId>>();
+
+        for id in inside {
+            self.selection.add(id);
+        }
+        self.gesture_selected = true;
+    }
+
+    /// This is called when we sense that the user stops dragging
+    pub fn stop_dragging(&mut self, v: Coordinates) -> FigureRenderType {
+        // Reset_trail resets the trail by completely making a new Rc
+        // so the Rc in the curve stays in the curve and is not affected.
+        self.reset_trail();
+
+        let render_type = match self.gesture.take() {
+            Some(Gesture::Translate { from, before }) => {
+                self.finish_translate(from, before, v);
+                FigureRenderType::Rerender
+            },
+
+            Some(Gesture::Marquee { from, shift, .. }) => {
+                self.finish_marquee(from, v, shift);
+                FigureRenderType::Rerender
+            },
+
+            None => FigureRenderType::DoNothing
+        };
+
+        self.end_transaction();
+
+        match self.state {
+            DrawState::Curve => {
+                FigureRenderType::Rerender
+            },
+
+            DrawState::Move => render_type,
+
+            // The render method for a point will be fired during the click event instead of the stop-dragging eent
+            DrawState::Point => {
+                FigureRenderType::DoNothing
+            }
+
+            _ => FigureRenderType::DoNothing
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reprs(fc: &FigureComplex) -> Vec<String> {
+        fc.figure().iter().map(|x| x.1.repr()).collect()
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        initialize_parser();
+        let mut fc = FigureComplex::new();
+        fc.draw_with_text("point(1, 2)").unwrap();
+        fc.draw_with_text("point(3, 4)").unwrap();
+        fc.draw_with_text("point(5, 6)").unwrap();
+        let ids = fc.figure().iter().map(|x| x.0).collect::<Vec<ObjectId>>();
+
+        // Removing and undoing puts the object back in the middle
+        assert!(fc.remove(ids[1]).is_some());
+        assert_eq!(reprs(&fc), vec!["point(1, 2)", "point(5, 6)"]);
+        fc.undo();
+        assert_eq!(reprs(&fc), vec!["point(1, 2)", "point(3, 4)", "point(5, 6)"]);
+        assert!(fc.get(ids[1]).is_some());
+
+        // Moving, then undoing and redoing
+        assert!(fc.move_object(ids[0], Coordinates::new(1, 1)));
+        assert_eq!(reprs(&fc)[0], "point(2, 3)");
+        fc.undo();
+        assert_eq!(reprs(&fc)[0], "point(1, 2)");
+        fc.redo();
+        assert_eq!(reprs(&fc)[0], "point(2, 3)");
+        assert_eq!(fc.get(ids[0]).unwrap().fo.borrow().repr(), "point(2, 3)");
+
+        // Undoing a command also takes its line out of the terminal
+        fc.undo();
+        fc.undo();
+        assert_eq!(reprs(&fc), vec!["point(1, 2)", "point(3, 4)"]);
+        assert_eq!(fc.ttext.unpack().len(), 2);
+        assert!(matches!(fc.redo(), FigureRenderType::Rerender));
+        assert_eq!(fc.ttext.unpack().len(), 3);
+
+        // A new edit clears everything that could be redone
+        fc.undo();
+        assert!(fc.can_redo());
+        assert!(fc.replace(ids[0], Point::new(Coordinates::new(0, 0)).wrap()));
+        assert!(!fc.can_redo());
+        assert!(matches!(fc.redo(), FigureRenderType::DoNothing));
+        assert_eq!(reprs(&fc), vec!["point(0, 0)", "point(3, 4)"]);
+    }
+
+    #[test]
+    fn test_transactions() {
+        initialize_parser();
+        let mut fc = FigureComplex::new();
+        fc.draw_with_text("point(1, 2)").unwrap();
+        fc.draw_with_text("1 + 2").unwrap();
+        fc.draw_with_text("point(3, 4), point(5, 6)").unwrap();
+        assert_eq!(fc.figure().len(), 3);
+        assert_eq!(fc.basis.len(), 3);
+
+        // The objects of a command and its line go away together
+        fc.undo();
+        assert_eq!(reprs(&fc), vec!["point(1, 2)"]);
+        assert_eq!(fc.basis.len(), 1);
+        assert_eq!(fc.ttext.unpack().len(), 2);
+
+        // So does the line of a command that draws nothing
+        fc.undo();
+        assert_eq!(fc.ttext.unpack().len(), 1);
+
+        fc.redo();
+        fc.redo();
+        assert_eq!(reprs(&fc), vec!["point(1, 2)", "point(3, 4)", "point(5, 6)"]);
+        assert_eq!(fc.ttext.unpack().iter().map(|x| x.to_string()).collect::<Vec<String>>(), vec!["point(1, 2)", "1 + 2", "point(3, 4), point(5, 6)"]);
+
+        // Everything done during a gesture is one transaction
+        let ids = fc.figure().iter().map(|x| x.0).collect::<Vec<ObjectId>>();
+        fc.start_dragging(Coordinates::new(0, 0), false);
+        fc.move_object(ids[0], Coordinates::new(1, 0));
+        fc.move_object(ids[0], Coordinates::new(1, 0));
+        fc.stop_dragging(Coordinates::new(2, 0));
+        assert_eq!(reprs(&fc)[0], "point(3, 2)");
+        fc.undo();
+        assert_eq!(reprs(&fc)[0], "point(1, 2)");
+    }
+
+    #[test]
+    fn test_remove_with_text() {
+        initialize_parser();
+        let mut fc = FigureComplex::new();
+        fc.draw_with_text("point(1, 2)").unwrap();
+        fc.draw_with_text("point(3, 4)").unwrap();
+        let ids = fc.figure().iter().map(|x| x.0).collect::<Vec<ObjectId>>();
+
+        // Removing writes a line that removes the same object when the terminal is replayed
+        assert!(fc.remove(ids[0]).is_some());
+        let lines = fc.ttext.unpack().iter().map(|x| x.to_string()).collect::<Vec<String>>();
+        assert_eq!(lines, vec!["point(1, 2)", "point(3, 4)", "remove(0)"]);
+
+        let mut replayed = FigureComplex::new();
+        for line in lines.iter() {
+            replayed.draw_with_text(line.as_str()).unwrap();
+        }
+        assert_eq!(reprs(&replayed), vec!["point(3, 4)"]);
+
+        // The object and the line come back together
+        fc.undo();
+        assert_eq!(reprs(&fc), vec!["point(1, 2)", "point(3, 4)"]);
+        assert_eq!(fc.ttext.unpack().len(), 2);
+
+        assert!(matches!(fc.draw_with_text("remove(1)"), Ok(FigureRenderType::Rerender)));
+        assert_eq!(reprs(&fc), vec!["point(1, 2)"]);
+        assert_eq!(fc.draw_with_text("remove(1)").unwrap_err().error_type, ParserErrorType::FunctionEvaluateError);
+        assert_eq!(fc.ttext.unpack().len(), 3);
+    }
+
+    #[test]
+    fn test_move_coordinate() {
+        initialize_parser();
+        let mut fc = FigureComplex::new();
+        fc.draw_with_text("coordinate(\"A\", 0, 0), coordinate(\"B\", 1, 2)").unwrap();
+        fc.draw_with_text("line(\"A\", \"B\")").unwrap();
+
+        assert!(matches!(fc.move_coordinate("C", Coordinates::new(3, 3)), FigureRenderType::DoNothing));
+        assert!(matches!(fc.move_coordinate("B", Coordinates::new(3, 3)), FigureRenderType::Rerender));
+        assert!(fc.figure().to_tikz().output().contains("\\coordinate (B) at (3, 3);"));
+        assert_eq!(fc.figure().bounding_box(), Some(BoundingBox::new(Coordinates::new(0, 0), Coordinates::new(3, 3))));
+
+        // The move is written in the terminal, so replaying it puts the coordinate in the same place
+        let lines = fc.ttext.unpack().iter().map(|x| x.to_string()).collect::<Vec<String>>();
+        assert_eq!(lines.last().unwrap(), "coordinate(\"B\", 3, 3)");
+        let mut replayed = FigureComplex::new();
+        for line in lines.iter() {
+            replayed.draw_with_text(line.as_str()).unwrap();
+        }
+        assert_eq!(replayed.figure().to_tikz().output(), fc.figure().to_tikz().output());
+
+        fc.undo();
+        assert_eq!(fc.figure().coordinate("B").unwrap().get(), Coordinates::new(1, 2));
+        assert_eq!(fc.ttext.unpack().len(), 2);
+        fc.redo();
+        assert_eq!(fc.figure().coordinate("B").unwrap().get(), Coordinates::new(3, 3));
+
+        // Undoing the definitions forgets the names, after the line drawn with them is gone
+        fc.undo();
+        fc.undo();
+        fc.undo();
+        assert!(fc.figure().coordinate("A").is_none());
+        assert!(fc.draw_with_text("line(\"A\", \"B\")").is_err());
+        fc.redo();
+        assert_eq!(fc.figure().coordinate("B").unwrap().get(), Coordinates::new(1, 2));
+    }
+
+    #[test]
+    fn test_move_tool() {
+        initialize_parser();
+        let mut fc = FigureComplex::new();
+        fc.draw_with_text("point(0, 0)").unwrap();
+        fc.draw_with_text("point(1, 0)").unwrap();
+        fc.draw_with_text("point(5, 5)").unwrap();
+        let ids = fc.figure().iter().map(|x| x.0).collect::<Vec<ObjectId>>();
+        fc.state = DrawState::Move;
+
+        // Click, shift click and shift click again
+        fc.click(Coordinates::new(0, 0.01), false);
+        assert_eq!(fc.selection().ids(), &ids[..1]);
+        fc.click(Coordinates::new(1, 0), true);
+        assert_eq!(fc.selection().ids(), &ids[..2]);
+        fc.click(Coordinates::new(1, 0), true);
+        assert_eq!(fc.selection().ids(), &ids[..1]);
+        fc.click(Coordinates::new(3, 3), false);
+        assert!(fc.selection().is_empty());
+
+        // Everything inside the marquee gets selected, and the click that comes after does nothing
+        fc.start_dragging(Coordinates::new(-1, -1), false);
+        fc.dragging(Coordinates::new(2, 1));
+        fc.stop_dragging(Coordinates::new(2, 1));
+        fc.click(Coordinates::new(2, 1), false);
+        assert_eq!(fc.selection().ids(), &ids[..2]);
+
+        // Dragging one of the selected objects moves all of them
+        fc.start_dragging(Coordinates::new(1, 0), false);
+        fc.dragging(Coordinates::new(1.5, 0));
+        assert_eq!(reprs(&fc), vec!["point(0.5, 0)", "point(1.5, 0)", "point(5, 5)"]);
+        fc.stop_dragging(Coordinates::new(2, 0));
+        fc.click(Coordinates::new(2, 0), false);
+        assert_eq!(reprs(&fc), vec!["point(1, 0)", "point(2, 0)", "point(5, 5)"]);
+        assert_eq!(fc.selection().len(), 2);
+        assert_eq!(fc.ttext.unpack().last().unwrap().to_string(), "move(0, 1)(1, 0)");
+
+        // Running the terminal commands again draws the same figure
+        let mut replayed = FigureComplex::new();
+        for line in fc.ttext.unpack() {
+            replayed.draw_with_text(line).unwrap();
+        }
+        assert_eq!(reprs(&replayed), reprs(&fc));
+        assert!(replayed.draw_with_text("move(3)(1, 0)").is_err());
+        assert!(matches!(replayed.draw_with_text("move(2)(0, 1)"), Ok(FigureRenderType::Rerender)));
+        assert!(matches!(replayed.draw_with_text("point(0, 0)"), Ok(FigureRenderType::Render)));
+
+        // The highlight goes on top of the figure
+        let c = RecordingCanvas::with_scale(100.);
+        fc.rerender(&c).unwrap();
+        let calls = c.calls();
+        assert_eq!(calls[0], DrawCall::Erase);
+        assert_eq!(calls.iter().filter(|x| **x == DrawCall::Fill).count(), 16);
+        assert_eq!(calls.last(), Some(&DrawCall::Restore));
+
+        // The whole move is one edit
+        fc.undo();
+        assert_eq!(reprs(&fc), vec!["point(0, 0)", "point(1, 0)", "point(5, 5)"]);
+        assert_eq!(fc.ttext.unpack().len(), 3);
+    }
+
+    #[test]
+    fn test_stale_gesture() {
+        initialize_parser();
+        let mut fc = FigureComplex::new();
+        fc.draw_with_text("point(0, 0)").unwrap();
+        fc.draw_with_text("point(5, 5)").unwrap();
+        fc.state = DrawState::Move;
+
+        // A drag whose release was never seen is dropped when the next one starts
+        fc.start_dragging(Coordinates::new(0, 0), false);
+        fc.dragging(Coordinates::new(1, 1));
+        assert_eq!(reprs(&fc)[0], "point(1, 1)");
+        fc.start_dragging(Coordinates::new(5, 5), false);
+        assert_eq!(reprs(&fc)[0], "point(0, 0)");
+        fc.dragging(Coordinates::new(6, 5));
+        fc.stop_dragging(Coordinates::new(6, 5));
+        assert_eq!(reprs(&fc), vec!["point(0, 0)", "point(6, 5)"]);
+        assert_eq!(fc.ttext.unpack().last().unwrap().to_string(), "move(1)(1, 0)");
+
+        // Only the drag that finished is undone, and the history is back to the points
+        fc.undo();
+        assert_eq!(reprs(&fc), vec!["point(0, 0)", "point(5, 5)"]);
+        assert_eq!(fc.ttext.unpack().len(), 2);
+        fc.undo();
+        assert_eq!(reprs(&fc), vec!["point(0, 0)"]);
+    }
+}
@@ -0,0 +1,180 @@
+//! The undo and redo history of the figure complex. Every change to the figure is made through an edit command,
+//! which remembers enough to be done again and to be taken back. Commands are undone in transactions, so that
+//! everything one terminal command or one mouse gesture did goes away in one step
+
+use crate::figures::*;
+use crate::engine::*;
+use crate::core::*;
+
+/// A reversible change to the figure complex
+#[derive(Clone, Debug)]
+pub enum EditCommand {
+    /// Draws the object on top of everything else
+    Add { foc: FigureObjectComplex },
+
+    /// Writes the line at the bottom of the terminal
+    TerminalLine { line: CheapString },
+
+    /// Takes the object out of the figure. 'position' is where it was from the bottom, so that undoing puts it back in the same place
+    Remove { foc: FigureObjectComplex, position: usize },
+
+    /// Puts another object in place of the object with the id
+    Modify { id: ObjectId, before: DrawableObject, after: DrawableObject },
+
+    /// Shifts the object with the id by 'by'. The object before the shift is kept so that undoing is exact
+    Move { id: ObjectId, by: Coordinates, before: DrawableObject, after: DrawableObject },
+
+    /// Puts the same object with another style in place of the object with the id
+    Restyle { id: ObjectId, before: DrawableObject, after: DrawableObject },
+
+    /// Moves the named coordinate, and with it everything drawn with a reference to it. 'before' is None if the
+    /// coordinate is defined by this command
+    MoveCoordinate { name: String, before: Option<Coordinates>, after: Coordinates },
+}
+
+impl EditCommand {
+    /// Returns the id of the object this command changes, or None if it only changes the terminal or a named coordinate
+    pub fn id(&self) -> Option<ObjectId> {
+        match self {
+            EditCommand::Add { foc } | EditCommand::Remove { foc, .. } => Some(foc.id),
+            EditCommand::Modify { id, .. } | EditCommand::Move { id, .. } | EditCommand::Restyle { id, .. } => Some(*id),
+            EditCommand::TerminalLine { .. } | EditCommand::MoveCoordinate { .. } => None
+        }
+    }
+}
+
+/// Edit commands that are undone and redone together, in the order they were done
+pub type Transaction = Vec<EditCommand>;
+
+/// Two stacks of transactions. Undoing moves the last transaction from the undo stack to the redo stack and redoing moves it back
+pub struct History {
+    undo_stack: Vec<Transaction>,
+    redo_stack: Vec<Transaction>,
+
+    /// The transaction that commands go into until it ends, if one has begun
+    open: Option<Transaction>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History {
+            undo_stack: vec![],
+            redo_stack: vec![],
+            open: None,
+        }
+    }
+
+    /// Starts a transaction. Every command pushed until it ends is undone in one step. Does nothing if one has already begun
+    pub fn begin(&mut self) {
+        if self.open.is_none() {
+            self.open = Some(vec![]);
+        }
+    }
+
+    /// Ends the transaction that has begun, if any. Transactions without any command are left out of the history
+    pub fn end(&mut self) {
+        if let Some(t) = self.open.take() {
+            if t.len() > 0 {
+                self.undo_stack.push(t);
+            }
+        }
+    }
+
+    /// Records a command that has just been done, as a transaction of its own unless one has begun. Anything that was
+    /// undone before can no longer be redone
+    pub fn push(&mut self, cmd: EditCommand) {
+        match &mut self.open {
+            Some(t) => t.push(cmd),
+            None => self.undo_stack.push(vec![cmd])
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Returns the transaction to undo, and keeps it to be redone later. A transaction that has begun ends first
+    pub fn undo(&mut self) -> Option<Transaction> {
+        self.end();
+        let t = self.undo_stack.pop()?;
+        self.redo_stack.push(t.clone());
+        return Some(t);
+    }
+
+    /// Returns the transaction to redo, and keeps it to be undone again later
+    pub fn redo(&mut self) -> Option<Transaction> {
+        self.end();
+        let t = self.redo_stack.pop()?;
+        self.undo_stack.push(t.clone());
+        return Some(t);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.undo_stack.len() > 0 || self.open.as_ref().is_some_and(|t| t.len() > 0)
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.redo_stack.len() > 0
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.open = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add() -> EditCommand {
+        EditCommand::Add { foc: FigureObjectComplex::new(Point::new(Coordinates::new(0, 0)).wrap(), String::from("point(0, 0)")) }
+    }
+
+    #[test]
+    fn test_stacks() {
+        let mut history = History::new();
+        assert!(history.undo().is_none());
+
+        let (a, b) = (add(), add());
+        history.push(a.clone());
+        history.push(b.clone());
+        let ids = |t: Option<Transaction>| t.unwrap().iter().map(|x| x.id()).collect::<Vec<Option<ObjectId>>>();
+        assert_eq!(ids(history.undo()), vec![b.id()]);
+        assert!(history.can_redo());
+        assert_eq!(ids(history.redo()), vec![b.id()]);
+        assert!(history.redo().is_none());
+
+        // A new edit throws away whatever was undone
+        history.undo();
+        history.push(add());
+        assert!(!history.can_redo());
+        assert!(history.can_undo());
+    }
+
+    #[test]
+    fn test_transactions() {
+        let mut history = History::new();
+        let (a, b) = (add(), add());
+        let line = EditCommand::TerminalLine { line: String::from("point(0, 0)").wrap() };
+
+        history.begin();
+        history.push(a.clone());
+        history.begin();
+        history.push(b.clone());
+        history.push(line);
+        history.end();
+
+        // Empty transactions are not undone
+        history.begin();
+        history.end();
+
+        let t = history.undo().unwrap();
+        assert_eq!(t.iter().map(|x| x.id()).collect::<Vec<Option<ObjectId>>>(), vec![a.id(), b.id(), None]);
+        assert!(!history.can_undo());
+
+        // Undoing in the middle of a transaction ends it first
+        history.begin();
+        history.push(a.clone());
+        assert_eq!(history.undo().map(|t| t.len()), Some(1));
+        assert!(history.undo().is_none());
+    }
+}
@@ -0,0 +1,301 @@
+//! This module provides the parse() method to parse commands
+//! All the core implementations is actually in the parser folder. This module mostly only formats and bubbles the error back up only
+
+use crate::figures::*;
+use crate::engine::*;
+use crate::core::*;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use regex::Regex;
+use lazy_static::lazy_static;
+use paste::paste;
+
+mod ast;
+mod impure_pattern;
+mod ast_matcher;
+mod variables;
+mod parser_error;
+mod pure_pattern;
+mod utils;
+
+use self::impure_pattern::*;
+use self::pure_pattern::*;
+use self::ast::AST;
+pub use self::parser_error::*;
+
+
+pub fn initialize_parser() {
+    init_pure();
+    init_impure();
+}
+
+/// Returns if the parsers are initialized
+fn try_initialize() {
+    if !initialized_pure() {
+        init_pure();
+    }
+
+    if !initialized_impure() {
+        init_impure()
+    }
+}
+
+/// What a command does to the figure: the objects it draws and the named coordinates it defines or moves
+#[derive(Debug)]
+pub struct ParsedCommand {
+    pub focs: Vec<FigureObjectComplex>,
+    pub coordinates: Vec<(String, Coordinates)>,
+}
+
+/// Parses a string into possibly a figure object complex, trying to match every pattern possible
+/// If nothing matches, returns a parser error which is like an abstraction of every possible error that could occur
+pub fn parse<S: StringLike>(s: S) -> Result<Option<Vec<FigureObjectComplex>>, ParserError> {
+    let cmd = parse_command(s, &CoordinateRegistry::new())?;
+
+    // Handle the special case where nothing needs to be drawn
+    if cmd.focs.len() == 0 {
+        return Ok(None);
+    }
+
+    return Ok(Some(cmd.focs));
+}
+
+/// Parses a string into what it does to the figure. Named coordinates the command refers to are looked up in
+/// 'coordinates', and the ones it defines are only returned, so a command cannot use a name it defines itself
+pub fn parse_command<S: StringLike>(s: S, coordinates: &CoordinateRegistry) -> Result<ParsedCommand, ParserError> {
+    try_initialize();
+
+    // 1. Turn the command into a syntax tree
+    let ast = AST::new(&(s.wrap())).map_err( |x| {
+        let msg = format!("Parse error: {} - {} (char {})", x.error_type, x.message.unwrap_or_default(), x.position);
+
+        ParserError {
+            error_type: ParserErrorType::ASTCompilationError,
+            msg,
+            src: x.source
+        }
+    })?;
+
+    // 2. Evaluate all the functions inside expanded
+    let expanded = evaluate_all(ast).map_err(|x| {
+        ParserError {
+            error_type: ParserErrorType::FunctionEvaluateError,
+            msg: x.msg,
+            src: "parser::parse_command()"
+        }
+    })?;
+
+    // 3. Draw everything inside the AST after function evaluation
+    let effects = match parse_draw(expanded, coordinates) {
+        Ok(effects) => effects,
+        Err(PatternMatchError::NoMatch) => vec![],
+        Err(PatternMatchError::ASTMatchError(er)) => return Err(ParserError {
+            error_type: ParserErrorType::ASTMatchError,
+            msg: format!("Invalid syntax: {}", er),
+            src: "parser::parse_command()"
+        }),
+        Err(PatternMatchError::EvaluateError(er)) => return Err(ParserError {
+            error_type: ParserErrorType::FunctionEvaluateError,
+            msg: er,
+            src: "parser::parse_command()"
+        })
+    };
+
+    // 4. Turn all the drawable objects into Figure object complexes
+    let mut cmd = ParsedCommand { focs: vec![], coordinates: vec![] };
+    for effect in effects.into_iter() {
+        match effect {
+            ImpureEffect::Draw(dr) => {
+                let st = dr.repr();
+                cmd.focs.push(FigureObjectComplex::new(dr, st));
+            },
+            ImpureEffect::DefineCoordinate(name, at) => cmd.coordinates.push((name, at))
+        }
+    }
+
+    return Ok(cmd);
+}
+
+lazy_static! {
+    /// move(i, j, ...)(dx, dy), where i, j, ... are positions in the figure counting from the bottom
+    static ref MOVE_COMMAND: Regex = Regex::new(r"^\s*move\s*\(\s*(\d+(?:\s*,\s*\d+)*)\s*\)\s*\(\s*([^,()]+?)\s*,\s*([^,()]+?)\s*\)\s*$").unwrap();
+
+    /// remove(i), where i is a position in the figure counting from the bottom
+    static ref REMOVE_COMMAND: Regex = Regex::new(r"^\s*remove\s*\(\s*(\d+)\s*\)\s*$").unwrap();
+}
+
+/// Writes the command that shifts the objects at the positions by 'by'. Positions count from the bottom of the figure,
+/// which is the order the objects were drawn in, starting from 0
+pub fn move_command(positions: &[usize], by: Coordinates) -> String {
+    let positions = positions.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+    return format!("move({}){}", positions.join(", "), by);
+}
+
+/// Reads a command written by move_command() back into the positions, in increasing order, and how much to shift by.
+/// Returns None if the string is not a move command
+pub fn parse_move<S: StringLike>(s: S) -> Option<(Vec<usize>, Coordinates)> {
+    let s = s.wrap();
+    let caps = MOVE_COMMAND.captures(&s)?;
+
+    let mut positions = caps[1].split(',').map(|x| x.trim().parse::<usize>().ok()).collect::<Option<Vec<usize>>>()?;
+    positions.sort();
+    positions.dedup();
+
+    let dx = caps[2].parse::<f64>().ok()?;
+    let dy = caps[3].parse::<f64>().ok()?;
+    return Some((positions, Coordinates::new(dx, dy)));
+}
+
+/// Writes the command that defines the named coordinate at 'at', or moves it there if it is already defined
+pub fn coordinate_command(name: &str, at: Coordinates) -> String {
+    return format!("coordinate(\"{}\", {}, {})", name, at[0], at[1]);
+}
+
+/// Writes the command that removes the object at the position, which counts from the bottom of the figure like in move_command()
+pub fn remove_command(position: usize) -> String {
+    return format!("remove({position})");
+}
+
+/// Reads a command written by remove_command() back into the position. Returns None if the string is not a remove command
+pub fn parse_remove<S: StringLike>(s: S) -> Option<usize> {
+    let s = s.wrap();
+    let caps = REMOVE_COMMAND.captures(&s)?;
+    return caps[1].parse::<usize>().ok();
+}
+
+// Some of these here might not work, because they are features we aim to develop
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn compare_tree(result: &str, expected: &str) {
+        initialize_parser();
+        let ast1 = evaluate_all(AST::new(result).unwrap()).unwrap();
+        let ast2 = AST::new(expected).unwrap();
+
+        assert_eq!(ast1, ast2);
+    }
+
+    #[test]
+    fn test_parse_1() {
+        initialize_parser();
+        let cmd = "point(3, 5)".wrap();
+        let res = parse(cmd).unwrap().unwrap();
+        assert!(res.len() == 1);
+        assert!(res[0].fo.borrow().repr() == "point(3, 5)");
+    }
+
+    #[test]
+    fn test_parse_move() {
+        let cmd = move_command(&[0, 2], Coordinates::new(1.5, -2));
+        assert_eq!(cmd, "move(0, 2)(1.5, -2)");
+        assert_eq!(parse_move(cmd), Some((vec![0, 2], Coordinates::new(1.5, -2))));
+        assert_eq!(parse_move(" move( 3,1 , 1 ) (0.25 , 0) "), Some((vec![1, 3], Coordinates::new(0.25, 0))));
+        assert_eq!(parse_move("move(a)(1, 0)"), None);
+        assert_eq!(parse_move("point(1, 0)"), None);
+    }
+
+    #[test]
+    fn test_parse_coordinates() {
+        initialize_parser();
+        let reg = CoordinateRegistry::new();
+        reg.set("A", Coordinates::new(0, 0));
+        reg.set("B", Coordinates::new(1, 2));
+
+        // Definitions are returned without being defined
+        let cmd = parse_command(coordinate_command("C", Coordinates::new(1 + 2, 0.5)), &reg).unwrap();
+        assert_eq!(cmd.coordinates, vec![("C".to_string(), Coordinates::new(3, 0.5))]);
+        assert!(cmd.focs.is_empty());
+        assert_eq!(reg.get("C"), None);
+
+        let cmd = parse_command("line(\"A\", \"B\"), path(\"(A) -- B--A\")", &reg).unwrap();
+        let reprs = cmd.focs.iter().map(|x| x.fo.borrow().repr()).collect::<Vec<String>>();
+        assert_eq!(reprs, vec!["line(\"A\", \"B\")", "path(\"A -- B -- A\")"]);
+
+        for bad in ["line(\"A\", \"C\")", "path(\"A\")", "coordinate(\"1A\", 0, 0)", "coordinate(\"A B\", 0, 0)"] {
+            assert_eq!(parse_command(bad, &reg).unwrap_err().error_type, ParserErrorType::FunctionEvaluateError);
+        }
+    }
+
+    #[test]
+    fn test_parse_remove() {
+        let cmd = remove_command(3);
+        assert_eq!(cmd, "remove(3)");
+        assert_eq!(parse_remove(cmd), Some(3));
+        assert_eq!(parse_remove(" remove ( 0 ) "), Some(0));
+        assert_eq!(parse_remove("remove(0, 1)"), None);
+        assert_eq!(parse_remove("move(0)(1, 0)"), None);
+    }
+
+    #[test]
+    fn test_parse_label() {
+        initialize_parser();
+        let cmd = "label(\"$x + y$, (1)\", 1 + 2, 5)".wrap();
+        let res = parse(cmd).unwrap().unwrap();
+        assert!(res.len() == 1);
+        assert_eq!(res[0].fo.borrow().repr(), "label(\"$x + y$, (1)\", 3, 5)");
+
+        let cmd = "label(\"x, 1, 2)".wrap();
+        assert_eq!(parse(cmd).unwrap_err().error_type, ParserErrorType::ASTCompilationError);
+    }
+
+    #[test]
+    fn test_parse_2() {
+        initialize_parser();
+        let cmd = "".wrap();
+        if let Err(x) = parse(cmd) {
+            assert_eq!(x.error_type, ParserErrorType::ASTCompilationError);
+        }
+        else {
+            panic!()
+        }
+    }
+
+    #[test]
+    fn test_parse_3() {
+        compare_tree("point(1, add(2)(3))", "point(1, 5)");
+    }
+
+    #[test]
+    fn test_parse_4() {
+        compare_tree("point(1 + 2, 3 + 4 * (5 - 6))", "point(3, -1)");
+    }
+
+    #[test]
+    fn test_parse_5() {
+        initialize_parser();
+        let cmd = "{x} = 5, point(3, x)".wrap();
+        let res = parse(cmd).unwrap().unwrap();
+        assert!(res.len() == 1);
+        assert!(res[0].fo.borrow().repr() == "point(3, 5)");
+    }
+
+    #[test]
+    fn test_parse_6() {
+        initialize_parser();
+        let cmd = "{x} = 1, {y} = if(x)(0.5)(-0.5), point(x, y)".wrap();
+        let res = parse(cmd).unwrap().unwrap();
+        assert!(res.len() == 1);
+        assert!(res[0].fo.borrow().repr() == "point(1, 0.5)");
+    }
+
+    #[test]
+    fn test_parse_7() {
+        initialize_parser();
+        let cmd = "{x} = {y} = 1, point(x, y)".wrap();
+        let res = parse(cmd).unwrap().unwrap();
+        assert!(res.len() == 1);
+        assert!(res[0].fo.borrow().repr() == "point(1, 1)");
+    }
+
+    #[test]
+    fn test_parse_8() {
+        initialize_parser();
+        let cmd = "{i} = 10, while(i)(point(0, i), {i} = i-1)".wrap();
+        let res = parse(cmd).unwrap().unwrap();
+        assert!(res.len() == 10);
+        assert!(res[0].fo.borrow().repr() == "point(0, 10)");
+    }
+}
\ No newline at end of file
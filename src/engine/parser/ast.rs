@@ -26,6 +26,7 @@ impl PartialEq for AST {
 #[derive(PartialEq, Clone)]
 pub enum ASTNode {
     Number(f64),
+    Text(String),
     Expression(Vec<ASTNode>),
     Function(String, Vec<ASTNode>),
     Variable(VariableType),
@@ -35,6 +36,7 @@ impl Debug for ASTNode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ASTNode::Number(x) => write!(f, "Number({})", x),
+            ASTNode::Text(x) => write!(f, "Text({:?})", x),
             ASTNode::Expression(x) => write!(f, "Expression({})", x.iter().map(|y| format!("{:?}", y)).collect::<Vec<String>>().join(", ")),
             ASTNode::Function(name, x) => write!(f, "{}", print_fn(name, x)),
             ASTNode::Variable(x) => write!(f, "Variable({:?})", x),
@@ -60,7 +62,10 @@ pub enum ASTErrorType {
     InvalidSyntax,
 
     /// Invalid syntax inside a variable
-    InvalidVariableSyntax
+    InvalidVariableSyntax,
+
+    /// We found a quote that is never closed
+    UnclosedQuotes
 }
 
 impl Display for ASTErrorType {
@@ -71,7 +76,8 @@ impl Display for ASTErrorType {
             Self::ParseNumberFail => String::from("Failed to parse number"),
             Self::InvalidSyntax => String::from("Invalid syntax -"),
             Self::InvalidVariableSyntax => String::from("Invalid variable syntax -"),
-            Self::BracketsMismatch => String::from("Mismatching brackets")
+            Self::BracketsMismatch => String::from("Mismatching brackets"),
+            Self::UnclosedQuotes => String::from("Found unclosed quotes")
         };
         write!(f, "{s}")
     }
@@ -117,19 +123,47 @@ lazy_static! {
     static ref IS_POSITIVE_INT: Regex = Regex::new(r"^\d+$").unwrap();
     static ref IS_IDENT: Regex = Regex::new(r"^[A-Za-z_][A-Za-z_0-9]*$").unwrap();
     static ref IS_BRACKETED: Regex = Regex::new(r"^\(.*\)$").unwrap();
+    static ref IS_TEXT: Regex = Regex::new(r#"^"[^"]*"$"#).unwrap();
 }
 
 impl ASTNode {
     /// This function exists for testing only
     fn from_str(st: &str) -> Result<ASTNode, ASTError> {
-        // Remove all white spaces first
-        let s = st.split_whitespace().collect::<String>();
+        // Remove all white spaces first, except the ones inside quotes
+        let mut s = String::new();
+        let mut in_quotes = false;
+        let mut quote_pos = 0;
+        for (i, c) in st.chars().enumerate() {
+            if c == '"' {
+                in_quotes = !in_quotes;
+                quote_pos = i;
+            }
+
+            if in_quotes || !c.is_whitespace() {
+                s.push(c);
+            }
+        }
+
+        if in_quotes {
+            return Err(ASTError {
+                error_type: ASTErrorType::UnclosedQuotes,
+                position: quote_pos,
+                message: None,
+                source: "AST::from_str()"
+            });
+        }
+
         return ASTNode::from_str_recursive(&s, 0);
     }
 
     fn from_str_recursive(st: &str, offset: usize) -> Result<ASTNode, ASTError> {
         let s = st;
 
+        // - Is it text in quotes?
+        if IS_TEXT.is_match(s) {
+            return Ok(ASTNode::Text(s[1..s.len()-1].to_string()));
+        }
+
         // - Is it a variable?
         if s.check_brackets("{}") {
             return handle_variable(s, offset);
@@ -201,6 +235,11 @@ fn handle_variable(st: &str, offset: usize) -> Result<ASTNode, ASTError> {
         return Ok(ASTNode::Expression(vec![ASTNode::Variable(VariableType::Number); n]));
     }
 
+    // If contents is a pair of quotes, then it should match some text
+    if contents == "\"\"" {
+        return Ok(ASTNode::Variable(VariableType::Text));
+    }
+
     // If contents contains a single *, then let it match an arbitrary number of variables
     if contents == "*" {
         return Ok(ASTNode::Variable(VariableType::NumberTuple));
@@ -293,8 +332,17 @@ fn splice_at_top_level_delim<'a>(s: &'a str, offset: usize, delim: char) -> Resu
     let mut first_substr_pos = 0;
 
     let mut brackets = vec![];
+    let mut in_quotes = false;
+
+    for (i, c) in s.char_indices() {
+        // Nothing inside quotes counts as brackets or delimeters
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        if in_quotes {
+            continue;
+        }
 
-    for (i, c) in s.chars().enumerate() {
         // Use the stack implementation to check
         if c == '(' {
             brackets.push((BracketTypes::Round, i));
@@ -374,12 +422,17 @@ fn add_explicit_brackets(s: &str) -> String {
     // Make this true because this handles the case where the string starts with a -1
     let mut last_char_is_operator = false;
     let mut should_add_brackets = false;
+    let mut in_quotes = false;
 
     for (i, c) in s.chars().enumerate() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+
         // This brackets whenever we see two consecutive operators
         // It will simplify the implementation, and if your syntax is wrong to begin, you are screwed anyway
         match c {
-            '+' | '-' | '*' | '/' | '=' => {
+            '+' | '-' | '*' | '/' | '=' if !in_quotes => {
                 if bracket_count == 0 {
                     if last_char_is_operator || i == 0 {
                         last_char_is_operator = false;
@@ -404,11 +457,11 @@ fn add_explicit_brackets(s: &str) -> String {
                 }
             },
 
-            '(' | '{' => {
+            '(' | '{' if !in_quotes => {
                 bracket_count += 1;
             },
 
-            ')' | '}' => {
+            ')' | '}' if !in_quotes => {
                 bracket_count -= 1;
             },
 
@@ -861,6 +914,16 @@ mod test {
         compare_ast("1*-2+3", "add(mul(1)(-2))(3)");
     }
 
+    #[test]
+    fn test_compile_ast25() {
+        let ast = ASTNode::from_str("f(\"a (b), c = d\", 1)").unwrap();
+        assert_eq!(ast, ASTNode::Function("f".to_string(), vec![ASTNode::Expression(vec![
+            ASTNode::Text("a (b), c = d".to_string()),
+            ASTNode::Number(1.)
+        ])]));
+        assert!(AST::new("f(\"a)").is_err());
+    }
+
     #[test]
     fn test_parse_1() {
         let s1 = "point(3, 5)";
@@ -900,4 +963,13 @@ mod test {
         assert_eq!(result[5], 5.);
         assert_eq!(result[6], -6.);
     }
+
+    #[test]
+    fn test_parse_4() {
+        let ast1 = ASTNode::from_str("label(\"hi there\", 1)").unwrap();
+        let ast2 = ASTNode::from_str("label({\"\"}, {})").unwrap();
+        let result = copy_args_with_mat(&ast1, &ast2).unwrap().unwrap();
+        assert_eq!(result[0].text().unwrap(), "hi there");
+        assert_eq!(result[1], 1.);
+    }
 }
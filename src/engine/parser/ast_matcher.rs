@@ -48,6 +48,12 @@ fn copy_args_recursive(s: &ASTNode, mat: &ASTNode, result: &mut Vec<VariablePayl
             Ok(true)
         },
 
+        // Text in quotes can be matched into text
+        (ASTNode::Text(x), ASTNode::Variable(VariableType::Text)) => {
+            result.push(VariablePayload::Text(x.clone()));
+            Ok(true)
+        },
+
         // A number can be matched into a variable - this is the assignment operation
         // So we bind the variable to a function that takes 0 arguments and gives said number
         (ASTNode::Number(x), ASTNode::Variable(VariableType::Variable(name))) => {
@@ -92,6 +98,10 @@ fn copy_args_recursive(s: &ASTNode, mat: &ASTNode, result: &mut Vec<VariablePayl
             Ok(eq(x, y))
         },
 
+        (ASTNode::Text(x), ASTNode::Text(y)) => {
+            Ok(x == y)
+        },
+
         (ASTNode::Expression(x), ASTNode::Expression(y)) => {
             if x.len() != y.len() {
                 return Ok(false);
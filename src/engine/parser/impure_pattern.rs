@@ -0,0 +1,208 @@
+//! This module contains all functions that draw stuff.
+//! A function that draws something is like a print statement - outputs something, impure, and expands to nothing
+//! The implementation is a mirror of pure patterns
+
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::{cell::RefCell, sync::Mutex};
+use super::ast::*;
+use super::variables::*;
+use super::pure_pattern::FunctionEvaluateError;
+use crate::figures::*;
+use crate::core::*;
+use lazy_static::lazy_static;
+
+type ImpureFunctionBehavior = Box<dyn Fn(Vec<VariablePayload>, &CoordinateRegistry) -> Result<ImpureEffect, FunctionEvaluateError> + Send + Sync>;
+
+/// What an impure function does to the figure
+pub enum ImpureEffect {
+    Draw(DrawableObject),
+
+    /// Defines a named coordinate, or moves it if it is already defined
+    DefineCoordinate(String, Coordinates),
+}
+
+/// A pattern is something to match our code against
+pub struct ImpurePattern {
+    ptr: ImpureFunctionBehavior,
+    pattern: AST
+}
+
+impl ImpurePattern {
+    fn call(&self, v: Vec<VariablePayload>, coordinates: &CoordinateRegistry) -> Result<ImpureEffect, FunctionEvaluateError> {
+        return (self.ptr)(v, coordinates);
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum PatternMatchError {
+    NoMatch,
+    ASTMatchError(String),
+
+    /// The pattern matched but the function could not be evaluated, like when a named coordinate is not defined
+    EvaluateError(String)
+}
+
+/// A singleton function parser that helps us match and evaluate functions
+pub struct ImpurePatternLookup {
+    fns: Mutex<Vec<ImpurePattern>>,
+    names: Mutex<HashSet<String>>,
+    initialized: Mutex<bool>
+}
+
+impl ImpurePatternLookup {
+    pub fn new() -> Self {
+        Self {
+            fns: Mutex::new(vec![]),
+            names: Mutex::new(HashSet::new()),
+            initialized: Mutex::new(false)
+        }
+    }
+
+    pub fn push<F, S>(&self, pattern: &'static str, behavior: F) where
+    F: Fn(Vec<VariablePayload>) -> S + Send + Sync + 'static,
+    S: Drawable + WrapAsDrawable {
+        self.push_behavior(pattern, Box::new(move |x, _| Ok(ImpureEffect::Draw((behavior)(x).wrap()))));
+    }
+
+    /// Pushes a pattern whose function looks up or defines named coordinates, and can fail doing so
+    pub fn push_effect<F>(&self, pattern: &'static str, behavior: F) where
+    F: Fn(Vec<VariablePayload>, &CoordinateRegistry) -> Result<ImpureEffect, FunctionEvaluateError> + Send + Sync + 'static {
+        self.push_behavior(pattern, Box::new(behavior));
+    }
+
+    fn push_behavior(&self, pattern: &'static str, behavior: ImpureFunctionBehavior) {
+        let ast = AST::new(pattern).expect(&format!("Failed to compile predefined impure pattern: {}", pattern));
+
+        if let ASTNode::Function(ref name, _) = ast.root {
+            self.names.lock().unwrap().insert(name.to_owned());
+
+            let pat = ImpurePattern {
+                pattern: ast,
+                ptr: behavior
+            };
+
+            self.fns.lock().unwrap().push(pat);
+
+            return;
+        }
+
+        panic!("Precompiled impure pattern not a function")
+    }
+
+    /// Searches through every possible patterns out there and evaluates it if we find a match
+    /// The ASTNode x is guaranteed to be a function. Named coordinates are looked up in 'coordinates'
+    pub fn evaluate(&self, x: ASTNode, coordinates: &CoordinateRegistry) -> Result<ImpureEffect, PatternMatchError> {
+        for f in self.fns.lock().unwrap().iter() {
+            if let Some(vars) = f.pattern.matches(&x).map_err(|x| PatternMatchError::ASTMatchError(format!("{:?}", x)))? {
+                return f.call(vars, coordinates).map_err(|x| PatternMatchError::EvaluateError(x.msg));
+            }
+        }
+
+        Err(PatternMatchError::NoMatch)
+    }
+
+    /// Returns true if the fn_name corresponds to a (impure) function. This is useful because we want to defer any impure patterns inside the pure pattern function lookup
+    pub fn quick_lookup(&self, fn_name: &str) -> bool {
+        return self.names.lock().unwrap().contains(fn_name)
+    }
+
+    pub fn initialize(&self) {
+        *self.initialized.lock().unwrap() = true;
+    }
+}
+
+lazy_static! {
+    static ref PATTERNS: ImpurePatternLookup = ImpurePatternLookup::new();
+}
+
+
+/// Evaluates all the impure patterns in the vector recursively. Since only Impure patterns should be left at this point
+/// If we receive NoMatch, it should be fatal too
+fn eval_recursive(node: ASTNode, coordinates: &CoordinateRegistry, v: &mut Vec<ImpureEffect>) -> Result<(), PatternMatchError> {
+    match node {
+        ASTNode::Function(_, _) => {
+            // An impure function must only take numbers and what nots as input. So we won't evaluate recursively
+            v.push(PATTERNS.evaluate(node, coordinates)?);
+            Ok(())
+        },
+
+        ASTNode::Expression(args) => {
+            for arg in args.into_iter() {
+                eval_recursive(arg, coordinates, v)?;
+            }
+
+            Ok(())
+        },
+
+        _ => Ok(())
+    }
+}
+
+/// Collects everything that should be drawn or defined. The effects are collected post-order, and named coordinates
+/// are looked up in 'coordinates' without defining anything there. Since this should be the last step, we consume the AST
+pub fn parse_draw(s: AST, coordinates: &CoordinateRegistry) -> Result<Vec<ImpureEffect>, PatternMatchError> {
+    let mut v = vec![];
+    eval_recursive(s.root, coordinates, &mut v)?;
+    return Ok(v);
+}
+
+/// Returns the named coordinate, or an error if it is not defined
+fn named_coordinate(name: &str, coordinates: &CoordinateRegistry) -> Result<NamedCoordinate, FunctionEvaluateError> {
+    coordinates.reference(name).ok_or_else(|| FunctionEvaluateError {
+        msg: format!("The coordinate {} is not defined", name)
+    })
+}
+
+pub fn is_name_of_impure_fn(name: &str) -> bool {
+    return PATTERNS.quick_lookup(name);
+}
+
+/// This function is called on initialization of the parser. Put patterns here.
+pub fn init_impure() {
+    PATTERNS.push("point({}, {})", |v: Vec<VariablePayload>| {
+        Point::new(Coordinates::new(v[0].float().unwrap(), v[1].float().unwrap()))
+    });
+
+    PATTERNS.push("label({\"\"}, {}, {})", |v: Vec<VariablePayload>| {
+        Label::new(Coordinates::new(v[1].float().unwrap(), v[2].float().unwrap()), v[0].text().unwrap())
+    });
+
+    // Tikz allows more than this in names, but these are the ones that are safe to write anywhere
+    PATTERNS.push_effect("coordinate({\"\"}, {}, {})", |v: Vec<VariablePayload>, _: &CoordinateRegistry| {
+        let name = v[0].text()?;
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic()) || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(FunctionEvaluateError {
+                msg: format!("{:?} is not a valid coordinate name - use letters, digits and underscores, starting with a letter", name)
+            });
+        }
+
+        Ok(ImpureEffect::DefineCoordinate(name.to_string(), Coordinates::new(v[1].float()?, v[2].float()?)))
+    });
+
+    PATTERNS.push_effect("line({\"\"}, {\"\"})", |v: Vec<VariablePayload>, coordinates: &CoordinateRegistry| {
+        let points = vec![named_coordinate(v[0].text()?, coordinates)?, named_coordinate(v[1].text()?, coordinates)?];
+        Ok(ImpureEffect::Draw(NamedPath::new(points).wrap()))
+    });
+
+    // path("A -- B -- C"), where the names can also be in brackets like in Tikz
+    PATTERNS.push_effect("path({\"\"})", |v: Vec<VariablePayload>, coordinates: &CoordinateRegistry| {
+        let points = v[0].text()?.split("--")
+            .map(|x| named_coordinate(x.trim().trim_start_matches('(').trim_end_matches(')').trim(), coordinates))
+            .collect::<Result<Vec<NamedCoordinate>, FunctionEvaluateError>>()?;
+
+        if points.len() < 2 {
+            return Err(FunctionEvaluateError {
+                msg: "A path needs at least two coordinates, like path(\"A -- B\")".to_string()
+            });
+        }
+
+        Ok(ImpureEffect::Draw(NamedPath::new(points).wrap()))
+    });
+
+    PATTERNS.initialize();
+}
+
+pub fn initialized_impure() -> bool {
+    return *PATTERNS.initialized.lock().unwrap()
+}
\ No newline at end of file
@@ -7,7 +7,7 @@ use std::{cell::RefCell, sync::Mutex};
 use super::ast::*;
 use super::impure_pattern::is_name_of_impure_fn;
 use super::variables::*;
-use crate::app::core::parser::ast_matcher::copy_args_with_mat;
+use crate::engine::parser::ast_matcher::copy_args_with_mat;
 use crate::core::calc::is_zero;
 use crate::figures::*;
 use crate::core::*;
@@ -54,7 +54,15 @@ impl ExtraStringMethodsForAST for &str {
         }
 
         let mut brackets_count = 0;
-        for (i, c) in self.chars().enumerate() {
+        let mut in_quotes = false;
+        for (i, c) in self.char_indices() {
+            if c == '"' {
+                in_quotes = !in_quotes;
+            }
+            if in_quotes {
+                continue;
+            }
+
             if c == left {
                 brackets_count += 1;
             }
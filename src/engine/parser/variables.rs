@@ -17,6 +17,7 @@ pub enum VariableType {
     Number,
     NumberTuple,
     Variable(ThreadSafeCheapString),
+    Text,
     AST
 }
 
@@ -26,6 +27,7 @@ impl Debug for VariableType {
             VariableType::Number => write!(f, "Number"),
             VariableType::NumberTuple => write!(f, "NumberTuple"),
             VariableType::Variable(name) => write!(f, "Variable{}", name),
+            VariableType::Text => write!(f, "Text"),
             VariableType::AST => write!(f, "AST")
         }
     }
@@ -56,6 +58,7 @@ impl FunctionPayload {
 pub enum VariablePayload {
     Number(f64),
     NumberTuple(Vec<f64>),
+    Text(String),
     Function(FunctionPayload),
     AST(ASTNode)
 }
@@ -65,6 +68,7 @@ impl Debug for VariablePayload {
         match self {
             VariablePayload::Number(y) => write!(f, "Var({})", y),
             VariablePayload::NumberTuple(y) => write!(f, "Var({:?})", y),
+            VariablePayload::Text(y) => write!(f, "Var({:?})", y),
             VariablePayload::Function(x) => write!(f, "Function({:?})", x),
             VariablePayload::AST(node) => write!(f, "{:?}", node),
         }
@@ -101,6 +105,16 @@ impl VariablePayload {
         })
     }
 
+    pub fn text<'a>(&'a self) -> Result<&'a str, FunctionEvaluateError> {
+        if let VariablePayload::Text(x) = self {
+            return Ok(x);
+        }
+
+        Err(FunctionEvaluateError {
+            msg: format!("Unknown error: type mismatch for text")
+        })
+    }
+
     pub fn tuple<'a>(&'a self) -> Result<&'a Vec<f64>, FunctionEvaluateError> {
         if let VariablePayload::NumberTuple(x) = self {
             return Ok(x);
@@ -0,0 +1,173 @@
+//! The objects picked with the move tool, and how they are shown on the canvas: a highlight around each of them
+//! with handles on its corners and sides, and the rectangle being dragged out to select everything inside it
+
+use crate::figures::*;
+use crate::core::*;
+
+/// Handles are squares this many pixels wide, whatever the zoom level
+const HANDLE_SIZE: f64 = 6.;
+
+/// The highlight is this many pixels away from the object all around
+const HIGHLIGHT_PADDING: f64 = 4.;
+
+/// Lines of the highlight and the marquee are this many pixels wide
+const HIGHLIGHT_WIDTH: f64 = 1.;
+
+/// The selected objects, in the order they were selected
+#[derive(Clone, PartialEq, Debug)]
+pub struct Selection {
+    ids: Vec<ObjectId>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Selection { ids: vec![] }
+    }
+
+    pub fn ids(&self) -> &[ObjectId] {
+        &self.ids
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.len() == 0
+    }
+
+    pub fn contains(&self, id: ObjectId) -> bool {
+        self.ids.contains(&id)
+    }
+
+    /// Selects the object and nothing else
+    pub fn select_only(&mut self, id: ObjectId) {
+        self.ids = vec![id];
+    }
+
+    /// Adds the object to the selection if it is not selected already
+    pub fn add(&mut self, id: ObjectId) {
+        if !self.contains(id) {
+            self.ids.push(id);
+        }
+    }
+
+    /// Selects the object if it is not selected, otherwise deselects it
+    pub fn toggle(&mut self, id: ObjectId) {
+        if self.contains(id) {
+            self.ids.retain(|x| *x != id);
+        }
+        else {
+            self.ids.push(id);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.ids.clear();
+    }
+
+    /// Deselects everything that is no longer in the figure
+    pub fn retain_in(&mut self, fig: &Figure) {
+        self.ids.retain(|x| fig.contains(*x));
+    }
+
+    /// Draws the highlight and the handles of every selected object in the figure
+    pub fn draw_on_canvas(&self, fig: &Figure, c: &dyn RenderTarget) -> Result<(), DrawError> {
+        let px = 1. / c.scale();
+
+        c.save()?;
+        c.set_stroke_style(&highlight_stroke(c).dash(DashPattern::Dashed))?;
+        c.set_fill_style(&FillStyle::solid(highlight_color()), BoundingBox::from_point(Coordinates::new(0, 0)))?;
+
+        for bb in self.ids.iter().filter_map(|x| fig.get(*x)).filter_map(|x| x.bounding_box()) {
+            let bb = bb.expand(HIGHLIGHT_PADDING * px);
+            c.draw_rectangle(bb.min(), bb.max())?;
+
+            for p in handles(&bb) {
+                let half = Coordinates::new(HANDLE_SIZE * 0.5 * px, HANDLE_SIZE * 0.5 * px);
+                fill_rectangle(c, p - half, p + half)?;
+            }
+        }
+
+        c.restore()
+    }
+}
+
+/// Draws the rectangle of a rubber band selection from one corner to the other
+pub fn draw_marquee(from: Coordinates, to: Coordinates, c: &dyn RenderTarget) -> Result<(), DrawError> {
+    c.save()?;
+    c.set_stroke_style(&highlight_stroke(c).dash(DashPattern::DenselyDotted))?;
+    c.set_fill_style(&FillStyle::Solid { color: highlight_color(), opacity: 0.1 }, BoundingBox::new(from, to))?;
+    fill_rectangle(c, from, to)?;
+    c.draw_rectangle(from, to)?;
+    c.restore()
+}
+
+fn highlight_color() -> Color {
+    Color::rgb(0, 120, 215)
+}
+
+/// Line widths are in pt, so this works out the width that is always the same number of pixels
+fn highlight_stroke(c: &dyn RenderTarget) -> StrokeStyle {
+    StrokeStyle::new()
+        .width(HIGHLIGHT_WIDTH / c.scale() / PT)
+        .color(highlight_color())
+}
+
+/// The corners and the middle of every side of the box
+fn handles(bb: &BoundingBox) -> Vec<Coordinates> {
+    let (x1, y1) = (bb.min()[0], bb.min()[1]);
+    let (x2, y2) = (bb.max()[0], bb.max()[1]);
+    let (xm, ym) = ((x1 + x2) * 0.5, (y1 + y2) * 0.5);
+
+    vec![
+        Coordinates::new(x1, y1), Coordinates::new(xm, y1), Coordinates::new(x2, y1), Coordinates::new(x2, ym),
+        Coordinates::new(x2, y2), Coordinates::new(xm, y2), Coordinates::new(x1, y2), Coordinates::new(x1, ym),
+    ]
+}
+
+fn fill_rectangle(c: &dyn RenderTarget, corner_1: Coordinates, corner_2: Coordinates) -> Result<(), DrawError> {
+    c.begin_path()?;
+    c.move_to(corner_1)?;
+    c.line_to(Coordinates::new(corner_2[0], corner_1[1]))?;
+    c.line_to(corner_2)?;
+    c.line_to(Coordinates::new(corner_1[0], corner_2[1]))?;
+    c.close_path()?;
+    c.fill()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection() {
+        let mut fig = Figure::new();
+        let a = fig.draw(Point::new(Coordinates::new(0, 0)));
+        let b = fig.draw(Point::new(Coordinates::new(1, 1)));
+
+        let mut selection = Selection::new();
+        selection.select_only(a);
+        selection.add(b);
+        selection.add(b);
+        assert_eq!(selection.ids(), &[a, b]);
+
+        selection.toggle(a);
+        assert_eq!(selection.ids(), &[b]);
+        selection.toggle(a);
+        assert_eq!(selection.ids(), &[b, a]);
+
+        fig.remove(b);
+        selection.retain_in(&fig);
+        assert_eq!(selection.ids(), &[a]);
+
+        // One highlight and eight handles
+        let c = RecordingCanvas::with_scale(100.);
+        selection.draw_on_canvas(&fig, &c).unwrap();
+        let calls = c.calls();
+        assert_eq!(calls.iter().filter(|x| **x == DrawCall::Stroke).count(), 1);
+        assert_eq!(calls.iter().filter(|x| **x == DrawCall::Fill).count(), 8);
+        assert_eq!(calls.first(), Some(&DrawCall::Save));
+        assert_eq!(calls.last(), Some(&DrawCall::Restore));
+    }
+}
@@ -1,10 +1,11 @@
 //! Responsible for handling terminal text
 
 use crate::figures::*;
-use crate::app::*;
+use crate::engine::*;
 use crate::core::*;
 use std::rc::Rc;
 use std::cell::RefCell;
+#[cfg(feature = "web")]
 use yew::prelude::*;
 
 pub struct TerminalTextRenderer {
@@ -24,12 +25,24 @@ impl TerminalTextRenderer {
         self.v.pop()
     }
 
+    /// Removes the last line that reads the same as 'r'. Returns false if there is no such line
+    pub fn remove_last(&mut self, r: &CheapString) -> bool {
+        match self.v.iter().rposition(|x| x == r) {
+            Some(i) => {
+                self.v.remove(i);
+                true
+            },
+            None => false
+        }
+    }
+
     pub fn unpack(&self) -> Vec<CheapString> {
         self.v.iter().map(|x| {
             x.clone()
         }).collect::<Vec<CheapString>>()
     }
 
+    #[cfg(feature = "web")]
     pub fn unpack_html(&self) -> Html {
         self.v.iter().map(|x| {
             html!{
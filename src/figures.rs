@@ -9,12 +9,16 @@ pub use base::drawable::Drawable;
 pub use base::drawable::DrawableObject;
 pub use base::drawable::WrapAsDrawable;
 
-pub use base::figure::Figure;
+pub use base::figure::{Figure, ObjectId};
 
 pub use figureobjects::point::FOPoint;
 pub use figureobjects::line::FOLine;
-pub use figureobjects::path::FOPath;
+pub use figureobjects::path::{FOPath, PathType, PathDrawStyle, PathTarget, Offset};
+pub use figureobjects::node::{FONode, Anchor, NodeShape};
+pub use figureobjects::scope::FOScope;
 
 pub use drawables::point::Point;
 pub use drawables::curve::Curve;
+pub use drawables::label::Label;
+pub use drawables::named_path::NamedPath;
 
@@ -3,6 +3,7 @@
 //! If we look at the requirements for a Drawable object, we see we need the draw method, sized, clone, and no lifetime parameters
 
 use crate::figures::*;
+use crate::core::{Affine2, BoundingBox, Coordinates};
 use std::any::Any;
 use std::rc::Rc;
 use std::fmt::Debug;
@@ -15,6 +16,19 @@ pub trait Drawable: Send + Sync + 'static {
 
     /// This is useful for debug purposes. It should produce a unique string
     fn repr(&self) -> String;
+
+    /// Returns a new drawable that draws the image of this one under the transform
+    fn transformed(&self, tf: &Affine2) -> DrawableObject;
+
+    /// Returns the smallest bounding box of everything this draws, or None if it draws nothing
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        self.draw().iter().map(|x| x.bounding_box()).reduce(|a, b| a.union(&b))
+    }
+
+    /// Returns the distance from p to the closest thing this draws, or None if it draws nothing
+    fn distance_to(&self, p: Coordinates) -> Option<f64> {
+        self.draw().iter().map(|x| x.distance_to(p)).reduce(f64::min)
+    }
 }
 
 pub trait WrapAsDrawable {
@@ -46,6 +60,18 @@ impl Drawable for DrawableObject {
     fn repr(&self) -> String {
         return self.obj.repr();
     }
+
+    fn transformed(&self, tf: &Affine2) -> DrawableObject {
+        return self.obj.transformed(tf);
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        return self.obj.bounding_box();
+    }
+
+    fn distance_to(&self, p: Coordinates) -> Option<f64> {
+        return self.obj.distance_to(p);
+    }
 }
 
 impl Clone for DrawableObject {
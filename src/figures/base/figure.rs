@@ -2,14 +2,41 @@
 
 use std::any::{TypeId, Any};
 use std::cell::RefCell;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU64, Ordering};
 use crate::figures::*;
-use gloo::console::log;
+use crate::core::*;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Identifies a drawn object for as long as it lives, even if another object looks exactly the same. Ids are never reused
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct ObjectId(u64);
+
+impl ObjectId {
+    /// Returns an id that no other object has
+    pub fn new() -> Self {
+        ObjectId(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Display for ObjectId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
 
 #[derive(PartialEq)]
 // Rerender every time we draw/project/do anything basically
 pub struct Figure {
-    to_draw: Vec<DrawableObject>,
-    newly_drawn: RefCell<Vec<DrawableObject>>,
+    to_draw: Vec<(ObjectId, DrawableObject)>,
+    newly_drawn: RefCell<Vec<ObjectId>>,
+    caption: Option<String>,
+    label: Option<String>,
+    coordinates: CoordinateRegistry,
+
+    /// Built the first time the figure is searched, and thrown away whenever something in it changes
+    index: RefCell<Option<SpatialIndex>>,
 }
 
 impl Figure {
@@ -17,14 +44,135 @@ impl Figure {
         Figure {
             to_draw: vec![],
             newly_drawn: RefCell::new(vec![]),
+            caption: None,
+            label: None,
+            coordinates: CoordinateRegistry::new(),
+            index: RefCell::new(None),
+        }
+    }
+
+    /// Sets the caption of the figure when exported into a figure environment
+    pub fn set_caption<S: Into<String>>(&mut self, caption: S) {
+        self.caption = Some(caption.into());
+    }
+
+    /// Sets the label of the figure when exported into a figure environment
+    pub fn set_label<S: Into<String>>(&mut self, label: S) {
+        self.label = Some(label.into());
+    }
+
+    pub fn caption(&self) -> Option<&str> {
+        self.caption.as_deref()
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Defines a named coordinate, or moves it if it is already defined. Objects made with the returned
+    /// reference follow the coordinate when it moves, so the whole figure should be loaded again afterwards
+    pub fn define_coordinate<S: Into<String>>(&mut self, name: S, at: Coordinates) -> NamedCoordinate {
+        *self.index.get_mut() = None;
+        self.coordinates.set(name, at)
+    }
+
+    /// Moves a named coordinate. Returns false if there is no coordinate with that name
+    pub fn move_coordinate(&mut self, name: &str, to: Coordinates) -> bool {
+        if self.coordinates.get(name).is_none() {
+            return false;
+        }
+
+        self.coordinates.set(name, to);
+        *self.index.get_mut() = None;
+        return true;
+    }
+
+    /// Returns a reference to the named coordinate, if it is defined
+    pub fn coordinate(&self, name: &str) -> Option<NamedCoordinate> {
+        self.coordinates.reference(name)
+    }
+
+    /// Forgets the named coordinate and returns where it was. Nothing in the figure may refer to it any more
+    pub fn remove_coordinate(&mut self, name: &str) -> Option<Coordinates> {
+        self.coordinates.remove(name)
+    }
+
+    /// Returns the named coordinates of the figure, which commands look names up in
+    pub fn coordinates(&self) -> &CoordinateRegistry {
+        &self.coordinates
+    }
+
+    /// Adds 'obj' on top of everything else in the figure, and returns the id it is drawn with
+    pub fn draw<T: Drawable + WrapAsDrawable>(&mut self, obj: T) -> ObjectId {
+        let id = ObjectId::new();
+        self.draw_with_id(id, obj);
+        return id;
+    }
+
+    /// Adds 'obj' on top of everything else with an id it already has. If something in the figure has the id, it is replaced instead
+    pub fn draw_with_id<T: Drawable + WrapAsDrawable>(&mut self, id: ObjectId, obj: T) {
+        if self.contains(id) {
+            self.replace(id, obj);
+            return;
         }
+
+        *self.index.get_mut() = None;
+        self.to_draw.push((id, obj.wrap()));
+        self.newly_drawn.borrow_mut().push(id);
+    }
+
+    /// Puts 'obj' in the figure so that it has 'position' objects below it, or on top if there are fewer than that.
+    /// If something in the figure has the id, it is replaced instead. Whatever the figure was drawn on has to be rerendered afterwards
+    pub fn insert<T: Drawable + WrapAsDrawable>(&mut self, position: usize, id: ObjectId, obj: T) {
+        if self.contains(id) {
+            self.replace(id, obj);
+            return;
+        }
+
+        *self.index.get_mut() = None;
+        self.to_draw.insert(position.min(self.to_draw.len()), (id, obj.wrap()));
+    }
+
+    /// Returns the position of the object from the bottom, which is the first one drawn
+    pub fn position(&self, id: ObjectId) -> Option<usize> {
+        self.to_draw.iter().position(|x| x.0 == id)
+    }
+
+    pub fn contains(&self, id: ObjectId) -> bool {
+        self.position(id).is_some()
+    }
+
+    pub fn get(&self, id: ObjectId) -> Option<&DrawableObject> {
+        self.position(id).map(|i| &self.to_draw[i].1)
+    }
+
+    /// Takes the object out of the figure and returns it. Whatever it was drawn on has to be rerendered afterwards
+    pub fn remove(&mut self, id: ObjectId) -> Option<DrawableObject> {
+        let i = self.position(id)?;
+        *self.index.get_mut() = None;
+        self.newly_drawn.get_mut().retain(|x| *x != id);
+        Some(self.to_draw.remove(i).1)
+    }
+
+    /// Puts 'obj' in place of the object with the id, keeping the id and the order. Returns the old object, or None
+    /// without drawing anything if there is no such object. Whatever it was drawn on has to be rerendered afterwards
+    pub fn replace<T: Drawable + WrapAsDrawable>(&mut self, id: ObjectId, obj: T) -> Option<DrawableObject> {
+        let i = self.position(id)?;
+        *self.index.get_mut() = None;
+        Some(std::mem::replace(&mut self.to_draw[i].1, obj.wrap()))
     }
 
-    /// Adds 'obj' to the list of objects to be drawn. Returns an error if the dimension of the obj does not match that of the figure
-    pub fn draw<T: Drawable + WrapAsDrawable>(&mut self, obj: T) where {
-        let a = obj.wrap();
-        self.to_draw.push(a.clone());
-        self.newly_drawn.borrow_mut().push(a);
+    /// Iterates over every object with its id, from the bottom (the first one drawn) to the top
+    pub fn iter(&self) -> impl Iterator<Item = (ObjectId, &DrawableObject)> {
+        self.to_draw.iter().map(|(id, x)| (*id, x))
+    }
+
+    pub fn len(&self) -> usize {
+        self.to_draw.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.to_draw.is_empty()
     }
 
     /// Load method takes a function object and a projection object. The method will feed
@@ -33,7 +181,12 @@ impl Figure {
     pub fn render<T, S>(&self, f: T) -> Vec<S> where
         T: Fn(PlottableObject) -> S,
     {
-        let x = self.load(f, &self.newly_drawn.borrow());
+        let newly_drawn = self.newly_drawn.borrow()
+            .iter()
+            .filter_map(|id| self.get(*id).cloned())
+            .collect();
+
+        let x = self.load(f, &newly_drawn);
         *self.newly_drawn.borrow_mut() = vec![];
         return x;
     }
@@ -44,7 +197,7 @@ impl Figure {
     pub fn load_all<T, S>(&self, f: T) -> Vec<S> where
     T: Fn(PlottableObject) -> S
     {
-        return self.load(f, &self.to_draw);
+        return self.load(f, &self.to_draw.iter().map(|x| x.1.clone()).collect());
     }
 
     /// Rerender the last object only. This will not do anything if there is nothing in the figure
@@ -55,7 +208,7 @@ impl Figure {
             return vec![];
         }
 
-        let x = self.to_draw[self.to_draw.len()-1].clone();
+        let x = self.to_draw[self.to_draw.len()-1].1.clone();
 
         return self.load(f, &vec![x]);
     }
@@ -74,9 +227,181 @@ impl Figure {
         return v;
     }
 
+    /// Runs f with the spatial index of the objects in the figure, building it first if needed
+    fn with_index<T, F: FnOnce(&SpatialIndex) -> T>(&self, f: F) -> T {
+        let mut index = self.index.borrow_mut();
+        let index = index.get_or_insert_with(|| SpatialIndex::new(
+            self.to_draw.iter()
+                .enumerate()
+                .filter_map(|(i, x)| x.1.bounding_box().map(|bb| (bb, i)))
+                .collect()
+        ));
+        f(index)
+    }
+
+    /// Returns every object within 'tolerance' of p, with the topmost, which is the last one drawn, first
+    pub fn hit_test(&self, p: Coordinates, tolerance: f64) -> Vec<ObjectId> {
+        let mut hits = self.with_index(|index| index.query(p, tolerance))
+            .into_iter()
+            .filter(|i| self.to_draw[*i].1.distance_to(p).is_some_and(|d| d <= tolerance))
+            .collect::<Vec<usize>>();
+
+        hits.sort_by(|a, b| b.cmp(a));
+        return hits.into_iter().map(|i| self.to_draw[i].0).collect();
+    }
+
+    /// Returns the object closest to p and its distance from p, or None if the figure is empty
+    pub fn nearest(&self, p: Coordinates) -> Option<(ObjectId, f64)> {
+        self.with_index(|index| index.nearest(p, |i| self.to_draw[i].1.distance_to(p).unwrap_or(f64::INFINITY)))
+            .map(|(i, d)| (self.to_draw[i].0, d))
+    }
+
+    /// Returns the smallest bounding box of everything in the figure, or None if nothing is drawn
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.to_draw.iter()
+            .filter_map(|x| x.1.bounding_box())
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Collects every object in the figure into Tikz code, along with the named coordinates, the caption and the label
+    pub fn to_tikz(&self) -> TikzFigure {
+        let mut fig = self.coordinates.definitions()
+            .into_iter()
+            .fold(TikzFigure::new(), |fig, (name, at)| fig.coordinate(name, at));
+
+        fig = self.load_all(|x| x)
+            .into_iter()
+            .fold(fig, |fig, x| fig.draw(x));
+
+        if let Some(caption) = &self.caption {
+            fig = fig.caption(caption.as_str());
+        }
+
+        if let Some(label) = &self.label {
+            fig = fig.label(label.as_str());
+        }
+
+        return fig;
+    }
+
+    /// Collects every object in the figure into an svg
+    pub fn to_svg(&self) -> SvgFigure {
+        return self.load_all(|x| x)
+            .into_iter()
+            .fold(SvgFigure::new(), |fig, x| fig.draw(x));
+    }
+
+    /// Removes the object on top, which is the last one drawn
     pub fn undo(&mut self) -> Option<DrawableObject> {
-        let x = self.to_draw.pop();
-        (*self.newly_drawn.borrow_mut()).pop();
-        return x;
+        let (id, x) = self.to_draw.pop()?;
+        self.newly_drawn.get_mut().retain(|x| *x != id);
+        *self.index.get_mut() = None;
+        return Some(x);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounding_box() {
+        let mut fig = Figure::new();
+        assert_eq!(fig.bounding_box(), None);
+
+        let reg = CoordinateRegistry::new();
+        fig.draw(NamedPath::new(vec![reg.set("A", Coordinates::new(0, 0)), reg.set("B", Coordinates::new(1, 2))]));
+        fig.draw(Point::new(Coordinates::new(-1, 1)));
+
+        let r = 0.8 * PT;
+        let expected = BoundingBox::new(Coordinates::new(-1. - r, 0), Coordinates::new(1, 2));
+        assert_eq!(fig.bounding_box(), Some(expected));
+    }
+
+    #[test]
+    fn test_hit_test() {
+        let mut fig = Figure::new();
+        assert_eq!(fig.hit_test(Coordinates::new(0, 0), 1.), vec![]);
+        assert_eq!(fig.nearest(Coordinates::new(0, 0)), None);
+
+        // A grid of points, then a line through the bottom row
+        let mut points = vec![];
+        for i in 0..50 {
+            for j in 0..50 {
+                points.push(fig.draw(Point::new(Coordinates::new(i, j))));
+            }
+        }
+        let reg = CoordinateRegistry::new();
+        let line = fig.draw(NamedPath::new(vec![reg.set("A", Coordinates::new(0, 0)), reg.set("B", Coordinates::new(49, 0))]));
+
+        assert_eq!(fig.hit_test(Coordinates::new(3.5, 0.05), 0.2), vec![line]);
+        assert_eq!(fig.hit_test(Coordinates::new(3.01, 0.01), 0.2), vec![line, points[150]]);
+        assert_eq!(fig.hit_test(Coordinates::new(3.5, 3.5), 0.2), vec![]);
+
+        let (id, d) = fig.nearest(Coordinates::new(10.1, 20.2)).unwrap();
+        assert_eq!(id, points[10 * 50 + 20]);
+        assert!(calc::eq(&d, &(0.05f64.sqrt() - 0.8 * PT)));
+
+        // The index is rebuilt after the line is removed
+        fig.undo();
+        assert_eq!(fig.hit_test(Coordinates::new(3.5, 0.05), 0.2), vec![]);
+    }
+
+    #[test]
+    fn test_object_ids() {
+        let mut fig = Figure::new();
+        let p = Coordinates::new(1, 1);
+        let ids = [fig.draw(Point::new(p)), fig.draw(Point::new(p)), fig.draw(Point::new(p))];
+        assert!(ids[0] != ids[1] && ids[1] != ids[2]);
+
+        // Removing the one in the middle leaves the others where they are
+        assert!(fig.remove(ids[1]).is_some());
+        assert!(fig.remove(ids[1]).is_none());
+        assert_eq!(fig.iter().map(|x| x.0).collect::<Vec<ObjectId>>(), vec![ids[0], ids[2]]);
+
+        // Replacing keeps the id and the order
+        let old = fig.replace(ids[0], Point::new(Coordinates::new(2, 2))).unwrap();
+        assert_eq!(old.repr(), Point::new(p).repr());
+        assert_eq!(fig.get(ids[0]).unwrap().repr(), Point::new(Coordinates::new(2, 2)).repr());
+        assert_eq!(fig.iter().map(|x| x.0).collect::<Vec<ObjectId>>(), vec![ids[0], ids[2]]);
+        assert_eq!(fig.hit_test(Coordinates::new(2, 2), 0.1), vec![ids[0]]);
+        assert!(fig.replace(ids[1], Point::new(p)).is_none());
+
+        // Only what is still in the figure gets rendered
+        assert_eq!(fig.render(|x| x.repr()).len(), 2);
+        assert_eq!(fig.len(), 2);
+
+        // Inserting puts a removed object back where it was
+        fig.insert(1, ids[1], Point::new(p));
+        assert_eq!(fig.iter().map(|x| x.0).collect::<Vec<ObjectId>>(), vec![ids[0], ids[1], ids[2]]);
+        assert_eq!(fig.position(ids[2]), Some(2));
+    }
+
+    #[test]
+    fn test_named_coordinates() {
+        let mut fig = Figure::new();
+        let a = fig.define_coordinate("A", Coordinates::new(0, 0));
+        let b = fig.define_coordinate("B", Coordinates::new(1, 2));
+        fig.draw(NamedPath::new(vec![a, b]));
+
+        let tikz = fig.to_tikz().output();
+        assert_eq!(tikz, concat!(
+            "\\begin{tikzpicture}\n",
+            "    \\coordinate (A) at (0, 0);\n",
+            "    \\coordinate (B) at (1, 2);\n",
+            "    \\draw (A) -- (B);\n",
+            "\\end{tikzpicture}\n"
+        ));
+
+        // Moving B moves the end of the line the next time the figure is drawn
+        assert!(fig.move_coordinate("B", Coordinates::new(3, 3)));
+        assert!(!fig.move_coordinate("C", Coordinates::new(3, 3)));
+        let c = RecordingCanvas::new();
+        for x in fig.load_all(|x| x) {
+            x.draw_on_canvas(&c).unwrap();
+        }
+        assert!(c.calls().contains(&DrawCall::LineTo(Coordinates::new(3, 3))));
+        assert!(fig.to_tikz().output().contains("\\coordinate (B) at (3, 3);"));
+        assert_eq!(fig.coordinate("B").unwrap().get(), Coordinates::new(3, 3));
     }
 }
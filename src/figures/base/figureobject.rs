@@ -15,11 +15,24 @@ pub trait FigureObject {
     /// A unique string that represents this object. This will be used to define partial eq.
     fn repr(&self) -> String;
 
-    /// Define the construction of Tikz code from an object
-    fn tikzify(&self) -> (String, Option<String>);
+    /// Define the construction of Tikz code from an object. Numbers should be formatted with opts
+    fn tikzify(&self, opts: &TikzOutputOptions) -> (String, Preamble);
 
-    /// Define the logic for which we draw the object on an Html Canvas
-    fn draw_on_canvas(&self, c: HtmlCanvas) -> Result<(), DrawError>;
+    /// Define the construction of SVG code from an object. This returns the svg element and the region of the plane it covers
+    fn to_svg(&self) -> (String, BoundingBox);
+
+    /// Returns the smallest axis aligned rectangle containing the object, not counting the width of lines
+    fn bounding_box(&self) -> BoundingBox;
+
+    /// Returns the distance from p to the closest point of the object. Points inside filled shapes are at distance zero
+    fn distance_to(&self, p: Coordinates) -> f64;
+
+    /// Define the logic for which we draw the object on a canvas. This can be an Html Canvas or any other render target
+    fn draw_on_canvas(&self, c: &dyn RenderTarget) -> Result<(), DrawError>;
+
+    /// Returns the image of this object under the transform. Figure objects also have an inherent method of the
+    /// same name that returns the concrete type
+    fn transformed(&self, tf: &Affine2) -> PlottableObject;
 }
 
 #[derive(Clone)]
@@ -29,22 +42,44 @@ pub struct PlottableObject {
 }
 
 impl FigureObject for PlottableObject {
-    fn tikzify(&self) -> (String, Option<String>) {
-        return self.ptr.tikzify();
+    fn tikzify(&self, opts: &TikzOutputOptions) -> (String, Preamble) {
+        return self.ptr.tikzify(opts);
+    }
+
+    fn to_svg(&self) -> (String, BoundingBox) {
+        return self.ptr.to_svg();
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        return self.ptr.bounding_box();
     }
 
-    fn draw_on_canvas(&self, c: HtmlCanvas) -> Result<(), DrawError> {
+    fn distance_to(&self, p: Coordinates) -> f64 {
+        return self.ptr.distance_to(p);
+    }
+
+    fn draw_on_canvas(&self, c: &dyn RenderTarget) -> Result<(), DrawError> {
         return self.ptr.draw_on_canvas(c);
     }
 
     fn repr(&self) -> String {
         return self.ptr.repr();
     }
+
+    fn transformed(&self, tf: &Affine2) -> PlottableObject {
+        return self.ptr.transformed(tf);
+    }
 }
 
 impl TikzShape for PlottableObject {
-    fn draw(&self) -> (String, Option<String>) {
-        return self.tikzify();
+    fn draw(&self, opts: &TikzOutputOptions) -> (String, Preamble) {
+        return self.tikzify(opts);
+    }
+}
+
+impl SvgShape for PlottableObject {
+    fn draw(&self) -> (String, BoundingBox) {
+        return self.to_svg();
     }
 }
 
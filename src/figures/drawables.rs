@@ -1,2 +1,4 @@
 pub mod point;
 pub mod curve;
+pub mod label;
+pub mod named_path;
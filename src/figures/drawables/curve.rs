@@ -5,8 +5,6 @@ use std::rc::Rc;
 use crate::figures::*;
 use crate::core::*;
 
-use gloo::console::log;
-
 pub struct Curve {
     v: ScopedVec<Coordinates>,
 }
@@ -34,6 +32,15 @@ impl Drawable for Curve {
         }
         return s;
     }
+
+    fn transformed(&self, tf: &Affine2) -> DrawableObject {
+        let mut v = ScopedVec::new();
+        for x in self.v.iter() {
+            v.push(tf.apply(x));
+        }
+        v.close();
+        Curve::new(v).wrap()
+    }
 }
 
 impl Curve {
@@ -44,3 +51,39 @@ impl Curve {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canvas() {
+        let mut v = ScopedVec::new();
+        v.push(Coordinates::new(0, 0));
+        v.push(Coordinates::new(1, 1));
+        v.push(Coordinates::new(2, 0));
+        v.close();
+
+        let c = RecordingCanvas::new();
+        for x in Curve::new(v).draw() {
+            x.draw_on_canvas(&c).unwrap();
+        }
+
+        let style = DrawCall::SetStrokeStyle(StrokeStyle::new());
+        assert_eq!(c.calls(), vec![
+            style.clone(), DrawCall::BeginPath, DrawCall::MoveTo(Coordinates::new(0, 0)), DrawCall::LineTo(Coordinates::new(1, 1)), DrawCall::Stroke,
+            style.clone(), DrawCall::BeginPath, DrawCall::MoveTo(Coordinates::new(1, 1)), DrawCall::LineTo(Coordinates::new(2, 0)), DrawCall::Stroke,
+        ]);
+    }
+
+    #[test]
+    fn test_transformed() {
+        let mut v = ScopedVec::new();
+        v.push(Coordinates::new(0, 0));
+        v.push(Coordinates::new(1, 1));
+        v.close();
+
+        let curve = Curve::new(v).transformed(&Affine2::translate(Coordinates::new(1, 0)));
+        assert_eq!(curve.repr(), "curve(1, 0)(2, 1)");
+    }
+}
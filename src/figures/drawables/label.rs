@@ -0,0 +1,31 @@
+//! A piece of text on the figure, made out of a single FONode
+
+use crate::figures::*;
+use crate::core::*;
+
+pub struct Label {
+    node: FONode,
+}
+
+impl Label {
+    pub fn new<S: Into<String>>(x: Coordinates, text: S) -> Self {
+        Label {
+            node: FONode::new(x, text),
+        }
+    }
+}
+
+impl Drawable for Label {
+    fn draw(&self) -> Vec<PlottableObject> {
+        return vec![self.node.clone().wrap()];
+    }
+
+    fn repr(&self) -> String {
+        let p = self.node.position();
+        format!("label(\"{}\", {}, {})", self.node.text(), p[0], p[1])
+    }
+
+    fn transformed(&self, tf: &Affine2) -> DrawableObject {
+        Label { node: self.node.transformed(tf) }.wrap()
+    }
+}
@@ -0,0 +1,64 @@
+//! Straight lines through named coordinates, made out of a single FOPath. The lines follow the coordinates when they move
+
+use crate::figures::*;
+use crate::core::*;
+
+pub struct NamedPath {
+    points: Vec<NamedCoordinate>,
+}
+
+impl NamedPath {
+    /// Creates the lines through the points, in order. There has to be at least two of them
+    pub fn new(points: Vec<NamedCoordinate>) -> Self {
+        assert!(points.len() >= 2, "A named path needs at least two points");
+        NamedPath { points }
+    }
+}
+
+impl Drawable for NamedPath {
+    fn draw(&self) -> Vec<PlottableObject> {
+        let data = self.points[1..].iter().map(|x| PathType::Line { to: x.clone().into() }).collect();
+        return vec![FOPath::new(PathDrawStyle::Draw, self.points[0].clone(), data).wrap()];
+    }
+
+    fn repr(&self) -> String {
+        if self.points.len() == 2 {
+            return format!("line(\"{}\", \"{}\")", self.points[0].name(), self.points[1].name());
+        }
+
+        let names = self.points.iter().map(|x| x.name()).collect::<Vec<&str>>();
+        format!("path(\"{}\")", names.join(" -- "))
+    }
+
+    /// The transformed lines no longer follow the named coordinates, since they are not where the names are
+    fn transformed(&self, tf: &Affine2) -> DrawableObject {
+        let mut v = ScopedVec::new();
+        for x in self.points.iter() {
+            v.push(tf.apply(x.get()));
+        }
+        v.close();
+        Curve::new(v).wrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_path() {
+        let reg = CoordinateRegistry::new();
+        let (a, b, c) = (reg.set("A", Coordinates::new(0, 0)), reg.set("B", Coordinates::new(1, 2)), reg.set("C", Coordinates::new(3, 0)));
+        let line = NamedPath::new(vec![a.clone(), b.clone()]);
+        let path = NamedPath::new(vec![a, b, c]);
+        assert_eq!(line.repr(), "line(\"A\", \"B\")");
+        assert_eq!(path.repr(), "path(\"A -- B -- C\")");
+
+        // The path follows the coordinates
+        let opts = TikzOutputOptions::default();
+        assert_eq!(path.draw()[0].tikzify(&opts).0, "\\draw (A) -- (B) -- (C);");
+        reg.set("C", Coordinates::new(5, 0));
+        assert_eq!(path.bounding_box(), Some(BoundingBox::new(Coordinates::new(0, 0), Coordinates::new(5, 2))));
+        assert_eq!(path.transformed(&Affine2::translate(Coordinates::new(1, 0))).repr(), "curve(1, 0)(2, 2)(6, 0)");
+    }
+}
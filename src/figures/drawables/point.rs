@@ -24,4 +24,34 @@ impl Drawable for Point {
     fn repr(&self) -> String {
         format!("point{}", self.p.point().to_string())
     }
+
+    fn transformed(&self, tf: &Affine2) -> DrawableObject {
+        Point { p: self.p.transformed(tf) }.wrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canvas() {
+        // The point is 2 pixels wide on a canvas where 1 unit is 100 pixels
+        let c = RecordingCanvas::with_scale(100.);
+        for x in Point::new(Coordinates::new(3, 4)).draw() {
+            x.draw_on_canvas(&c).unwrap();
+        }
+
+        let calls = c.calls();
+        assert_eq!(calls[0], DrawCall::SetStrokeStyle(StrokeStyle::new()));
+        assert_eq!(calls[1], DrawCall::BeginPath);
+        assert_eq!(calls[3], DrawCall::Stroke);
+        if let DrawCall::Arc { center, radius, .. } = calls[2] {
+            assert_eq!(center, Coordinates::new(3, 4));
+            assert_eq!(radius, 0.02);
+        }
+        else {
+            panic!("Expected an arc, got {:?}", calls[2]);
+        }
+    }
 }
@@ -2,3 +2,5 @@ pub mod point;
 pub mod path;
 pub mod curve;
 pub mod line;
+pub mod node;
+pub mod scope;
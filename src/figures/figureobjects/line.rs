@@ -3,32 +3,105 @@
 use crate::figures::*;
 use crate::core::*;
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct FOLine {
-    a: Coordinates,
-    b: Coordinates
+    a: Position,
+    b: Position,
+    stroke: StrokeStyle
 }
 
 impl FOLine {
-    pub fn new(from: Coordinates, to: Coordinates) -> Self {
+    /// The end points can be named coordinates, in which case the line follows them when they move
+    pub fn new<A: Into<Position>, B: Into<Position>>(from: A, to: B) -> Self {
         Self {
-            a: from,
-            b: to
+            a: from.into(),
+            b: to.into(),
+            stroke: StrokeStyle::new()
         }
     }
+
+    pub fn stroke_style(mut self, stroke: StrokeStyle) -> Self {
+        self.stroke = stroke;
+        return self;
+    }
+
+    /// Returns the image of the line. The end points become plain coordinates, since the transform does not move the named coordinates
+    pub fn transformed(&self, tf: &Affine2) -> FOLine {
+        FOLine::new(tf.apply(self.a.get()), tf.apply(self.b.get())).stroke_style(self.stroke.clone())
+    }
 }
 
 impl FigureObject for FOLine {
-    fn tikzify(&self) -> (String, Option<String>) {
-        let a = format!("\\draw {} -- {};", self.a, self.b);
-        (a, None)
+    fn tikzify(&self, opts: &TikzOutputOptions) -> (String, Preamble) {
+        let (options, preamble) = self.stroke.tikz_options(opts);
+        let options = if options.len() > 0 { format!("[{}]", options.join(", ")) } else { String::new() };
+        let a = format!("\\draw{options} {} -- {};", self.a.tikzify(opts), self.b.tikzify(opts));
+        (a, preamble)
+    }
+
+    fn to_svg(&self) -> (String, BoundingBox) {
+        let (a, b) = (self.a.get(), self.b.get());
+        let (x1, y1) = svg_coords(a);
+        let (x2, y2) = svg_coords(b);
+        let svg = format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {}/>",
+            x1.to_svg(), y1.to_svg(), x2.to_svg(), y2.to_svg(), self.stroke.svgify());
+        (svg, BoundingBox::new(a, b))
     }
 
-    fn draw_on_canvas(&self, c: HtmlCanvas) -> Result<(), DrawError> {
-        c.draw_line(self.a, self.b)
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(self.a.get(), self.b.get())
+    }
+
+    fn distance_to(&self, p: Coordinates) -> f64 {
+        calc::distance_to_segment(p, self.a.get(), self.b.get())
+    }
+
+    fn draw_on_canvas(&self, c: &dyn RenderTarget) -> Result<(), DrawError> {
+        let (a, b) = (self.a.get(), self.b.get());
+        c.set_stroke_style(&self.stroke)?;
+        c.draw_line(a, b)?;
+
+        let w = self.stroke.local_width();
+        self.stroke.start_arrow.draw_on_canvas(c, a, a - b, w)?;
+        self.stroke.end_arrow.draw_on_canvas(c, b, b - a, w)
     }
 
     fn repr(&self) -> String {
         format!("line{}{}", self.a, self.b)
     }
+
+    fn transformed(&self, tf: &Affine2) -> PlottableObject {
+        FOLine::transformed(self, tf).wrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tikz_stroke() {
+        let line = FOLine::new(Coordinates::new(0, 0), Coordinates::new(1, 1));
+        assert_eq!(line.tikzify(&TikzOutputOptions::default()).0, "\\draw (0, 0) -- (1, 1);");
+
+        let line = line.stroke_style(StrokeStyle::new().dash(DashPattern::Dotted).arrows(ArrowTip::Latex, ArrowTip::None));
+        let (tikz, preamble) = line.tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, "\\draw[dotted, {Latex}-] (0, 0) -- (1, 1);");
+        assert_eq!(preamble.lines(), vec!["\\usetikzlibrary{arrows.meta}"]);
+    }
+
+    #[test]
+    fn test_canvas_stroke() {
+        let style = StrokeStyle::new().width(2.).arrows(ArrowTip::None, ArrowTip::To);
+        let line = FOLine::new(Coordinates::new(0, 0), Coordinates::new(1, 0)).stroke_style(style.clone());
+
+        let c = RecordingCanvas::new();
+        line.draw_on_canvas(&c).unwrap();
+        let calls = c.calls();
+        assert_eq!(calls[0], DrawCall::SetStrokeStyle(style));
+
+        // The line itself, then the tip at the end
+        assert_eq!(calls.iter().filter(|x| **x == DrawCall::Stroke).count(), 2);
+        assert_eq!(calls[calls.len() - 3], DrawCall::LineTo(Coordinates::new(1, 0)));
+    }
 }
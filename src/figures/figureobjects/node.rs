@@ -0,0 +1,386 @@
+//! The implementation of a Tikz node, which is a piece of text placed on the figure with an optional border.
+//! Sizes are in pt like in Tikz. We do not have LaTeX to typeset the text on the canvas, so the size of the
+//! text there is only an estimate
+
+use crate::figures::*;
+use crate::core::*;
+
+/// Which point of the node sits at its position
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Anchor {
+    Center,
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl Anchor {
+    pub fn tikz_name(&self) -> &'static str {
+        match self {
+            Anchor::Center => "center",
+            Anchor::North => "north",
+            Anchor::South => "south",
+            Anchor::East => "east",
+            Anchor::West => "west",
+            Anchor::NorthEast => "north east",
+            Anchor::NorthWest => "north west",
+            Anchor::SouthEast => "south east",
+            Anchor::SouthWest => "south west",
+        }
+    }
+
+    /// The direction of the anchor from the center of the node, before the node is rotated
+    fn direction(&self) -> Coordinates {
+        let (x, y) = match self {
+            Anchor::Center => (0, 0),
+            Anchor::North => (0, 1),
+            Anchor::South => (0, -1),
+            Anchor::East => (1, 0),
+            Anchor::West => (-1, 0),
+            Anchor::NorthEast => (1, 1),
+            Anchor::NorthWest => (-1, 1),
+            Anchor::SouthEast => (1, -1),
+            Anchor::SouthWest => (-1, -1),
+        };
+        Coordinates::new(x, y)
+    }
+}
+
+/// The border drawn around the text
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NodeShape {
+    None,
+    Rectangle,
+    Circle,
+}
+
+/// The font size of LaTeX documents by default, in pt
+pub const DEFAULT_FONT_SIZE: f64 = 10.;
+
+/// Rotates v counterclockwise by the angle in degrees
+fn rotate(v: Coordinates, angle: f64) -> Coordinates {
+    let (s, c) = angle.to_radians().sin_cos();
+    Coordinates::new(v[0] * c - v[1] * s, v[0] * s + v[1] * c)
+}
+
+#[derive(Clone)]
+pub struct FONode {
+    position: Coordinates,
+    text: String,
+    anchor: Anchor,
+
+    /// Font size in pt
+    font_size: f64,
+
+    /// Rotation in degrees, counterclockwise
+    rotation: f64,
+    shape: NodeShape,
+
+    /// The space between the text and the border in pt. None uses the default of Tikz, which is a third of the font size
+    inner_sep: Option<f64>,
+    stroke: StrokeStyle,
+}
+
+impl FONode {
+    /// The text can contain math between dollar signs, which is passed to LaTeX as is
+    pub fn new<S: Into<String>>(position: Coordinates, text: S) -> Self {
+        Self {
+            position,
+            text: text.into(),
+            anchor: Anchor::Center,
+            font_size: DEFAULT_FONT_SIZE,
+            rotation: 0.,
+            shape: NodeShape::None,
+            inner_sep: None,
+            stroke: StrokeStyle::new(),
+        }
+    }
+
+    pub fn anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        return self;
+    }
+
+    pub fn font_size(mut self, size: f64) -> Self {
+        self.font_size = size;
+        return self;
+    }
+
+    pub fn rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees;
+        return self;
+    }
+
+    pub fn shape(mut self, shape: NodeShape) -> Self {
+        self.shape = shape;
+        return self;
+    }
+
+    pub fn inner_sep(mut self, sep: f64) -> Self {
+        self.inner_sep = Some(sep);
+        return self;
+    }
+
+    /// Sets the style of the border. The color is also the color of the text
+    pub fn stroke_style(mut self, stroke: StrokeStyle) -> Self {
+        self.stroke = stroke;
+        return self;
+    }
+
+    /// Moves the node with the transform. The text keeps its size but turns with the transform
+    pub fn transformed(&self, tf: &Affine2) -> FONode {
+        let x_axis = tf.apply_vector(Coordinates::new(1, 0));
+        let mut node = self.clone();
+        node.position = tf.apply(self.position);
+        node.rotation = self.rotation + x_axis[1].atan2(x_axis[0]).to_degrees();
+        return node;
+    }
+
+    pub fn position(&self) -> Coordinates {
+        self.position
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The text as we show it without LaTeX, which is the text without the math delimiters
+    fn display_text(&self) -> String {
+        self.text.replace('$', "")
+    }
+
+    /// Estimates the width and height of the text in local coordinates. An average character is about half an em wide
+    fn text_size(&self) -> (f64, f64) {
+        let em = self.font_size * PT;
+        (self.display_text().chars().count() as f64 * 0.5 * em, em)
+    }
+
+    /// Half the width and height of the node including the inner sep, before the node is rotated
+    fn half_extents(&self) -> (f64, f64) {
+        let sep = self.inner_sep.unwrap_or(self.font_size / 3.) * PT;
+        let (w, h) = self.text_size();
+        (w / 2. + sep, h / 2. + sep)
+    }
+
+    /// The radius of the circle around the node
+    fn radius(&self) -> f64 {
+        let (hx, hy) = self.half_extents();
+        hx.hypot(hy)
+    }
+
+    /// The center of the node, which we get by going back from the position to the center along the anchor
+    pub fn center(&self) -> Coordinates {
+        let d = self.anchor.direction();
+        if d.magnitude() == 0. {
+            return self.position;
+        }
+
+        let offset = match self.shape {
+            NodeShape::Circle => d.normalize() * self.radius(),
+            _ => {
+                let (hx, hy) = self.half_extents();
+                Coordinates::new(d[0] * hx, d[1] * hy)
+            }
+        };
+        self.position - rotate(offset, self.rotation)
+    }
+
+    /// The corners of the rectangle around the node, counterclockwise from the bottom left
+    fn corners(&self) -> [Coordinates; 4] {
+        let (hx, hy) = self.half_extents();
+        let c = self.center();
+        [(-hx, -hy), (hx, -hy), (hx, hy), (-hx, hy)].map(|(x, y)| c + rotate(Coordinates::new(x, y), self.rotation))
+    }
+
+    /// Escapes the characters that mean something else in LaTeX, except inside math where the user wants LaTeX
+    fn tikz_text(&self) -> String {
+        let mut s = String::new();
+        let mut in_math = false;
+        for c in self.text.chars() {
+            match c {
+                '$' => in_math = !in_math,
+                '%' | '#' | '&' | '_' if !in_math => s.push('\\'),
+                _ => ()
+            }
+            s.push(c);
+        }
+        s
+    }
+}
+
+impl FigureObject for FONode {
+    fn tikzify(&self, opts: &TikzOutputOptions) -> (String, Preamble) {
+        let mut options = vec![];
+        if self.anchor != Anchor::Center {
+            options.push(format!("anchor={}", self.anchor.tikz_name()));
+        }
+
+        match self.shape {
+            NodeShape::None => (),
+            NodeShape::Rectangle => options.push("draw, rectangle".to_string()),
+            NodeShape::Circle => options.push("draw, circle".to_string()),
+        };
+
+        let (stroke, preamble) = self.stroke.tikz_options(opts);
+        options.extend(stroke);
+
+        if let Some(sep) = self.inner_sep {
+            options.push(format!("inner sep={}pt", opts.number(sep)));
+        }
+
+        if self.rotation != 0. {
+            options.push(format!("rotate={}", opts.number(self.rotation)));
+        }
+
+        if self.font_size != DEFAULT_FONT_SIZE {
+            let size = opts.number(self.font_size);
+            let skip = opts.number(self.font_size * 1.2);
+            options.push(format!("font=\\fontsize{{{size}}}{{{skip}}}\\selectfont"));
+        }
+
+        let options = if options.len() > 0 { format!("[{}]", options.join(", ")) } else { String::new() };
+        (format!("\\node{options} at {} {{{}}};", opts.coords(self.position), self.tikz_text()), preamble)
+    }
+
+    fn to_svg(&self) -> (String, BoundingBox) {
+        let (w, h) = self.text_size();
+        let (cx, cy) = svg_coords(self.center());
+        let color = self.stroke.effective_color().to_svg();
+
+        let text = self.display_text().replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+        let mut svg = format!("<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{color}\" transform=\"rotate({} {} {})\">{text}</text>",
+            (cx - w / 2.).to_svg(), (cy + h / 2.).to_svg(), (self.font_size * PT).to_svg(),
+            (-self.rotation).to_svg(), cx.to_svg(), cy.to_svg());
+
+        if self.shape == NodeShape::Circle {
+            svg.push_str(&format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" {}/>",
+                cx.to_svg(), cy.to_svg(), self.radius().to_svg(), self.stroke.svgify()));
+        } else if self.shape == NodeShape::Rectangle {
+            let (hx, hy) = self.half_extents();
+            svg.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" transform=\"rotate({} {} {})\" fill=\"none\" {}/>",
+                (cx - hx).to_svg(), (cy - hy).to_svg(), (2. * hx).to_svg(), (2. * hy).to_svg(),
+                (-self.rotation).to_svg(), cx.to_svg(), cy.to_svg(), self.stroke.svgify()));
+        }
+
+        (svg, self.bounding_box())
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        match self.shape {
+            NodeShape::Circle => BoundingBox::from_point(self.center()).expand(self.radius()),
+            // Safe unwrap - there are always four corners
+            _ => BoundingBox::from_points(&self.corners()).unwrap()
+        }
+    }
+
+    /// Nodes count as solid, so that clicking anywhere on the text hits them
+    fn distance_to(&self, p: Coordinates) -> f64 {
+        if self.shape == NodeShape::Circle {
+            return ((p - self.center()).magnitude() - self.radius()).max(0.);
+        }
+
+        let corners = self.corners();
+        if calc::winding_number(p, &corners) != 0 {
+            return 0.;
+        }
+        (0..4).map(|i| calc::distance_to_segment(p, corners[i], corners[(i + 1) % 4])).fold(f64::INFINITY, f64::min)
+    }
+
+    fn draw_on_canvas(&self, c: &dyn RenderTarget) -> Result<(), DrawError> {
+        c.set_stroke_style(&self.stroke)?;
+
+        match self.shape {
+            NodeShape::None => (),
+            NodeShape::Rectangle => {
+                let corners = self.corners();
+                c.begin_path()?;
+                c.move_to(corners[0])?;
+                for p in corners[1..].iter() {
+                    c.line_to(*p)?;
+                }
+                c.close_path()?;
+                c.stroke()?;
+            },
+            NodeShape::Circle => c.draw_circle(self.center(), self.radius())?,
+        };
+
+        let (w, h) = self.text_size();
+        let bottom_left = self.center() + rotate(Coordinates::new(-w / 2., -h / 2.), self.rotation);
+        c.set_font_size(self.font_size)?;
+        c.fill_text(&self.display_text(), bottom_left, self.rotation.to_radians())
+    }
+
+    fn transformed(&self, tf: &Affine2) -> PlottableObject {
+        FONode::transformed(self, tf).wrap()
+    }
+
+    fn repr(&self) -> String {
+        format!("node{}{{{}}}", self.position, self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tikz() {
+        let opts = TikzOutputOptions::default();
+        let node = FONode::new(Coordinates::new(1, 2), "$x_1$ & y_1");
+        assert_eq!(node.tikzify(&opts).0, "\\node at (1, 2) {$x_1$ \\& y\\_1};");
+
+        let node = FONode::new(Coordinates::new(0, 0), "A")
+            .anchor(Anchor::SouthWest)
+            .shape(NodeShape::Circle)
+            .inner_sep(2.)
+            .rotation(30.)
+            .font_size(12.)
+            .stroke_style(StrokeStyle::new().color(Color::named("red").unwrap()));
+        assert_eq!(node.tikzify(&opts).0,
+            "\\node[anchor=south west, draw, circle, color=red, inner sep=2pt, rotate=30, font=\\fontsize{12}{14.4}\\selectfont] at (0, 0) {A};");
+    }
+
+    #[test]
+    fn test_anchor() {
+        // Two characters at 10pt are 10pt wide and 10pt tall, and the inner sep is 2pt
+        let node = FONode::new(Coordinates::new(0, 0), "AB").inner_sep(2.).anchor(Anchor::NorthEast);
+        let c = node.center();
+        assert!((c - Coordinates::new(-7. * PT, -7. * PT)).magnitude() < 1e-10, "{c}");
+
+        let node = node.rotation(90.);
+        let c = node.center();
+        assert!((c - Coordinates::new(7. * PT, -7. * PT)).magnitude() < 1e-10, "{c}");
+
+        let node = FONode::new(Coordinates::new(1, 1), "AB").anchor(Anchor::Center);
+        assert_eq!(node.center(), Coordinates::new(1, 1));
+    }
+
+    #[test]
+    fn test_canvas() {
+        let node = FONode::new(Coordinates::new(0, 0), "$ab$").inner_sep(0.).shape(NodeShape::Rectangle);
+        let c = RecordingCanvas::new();
+        node.draw_on_canvas(&c).unwrap();
+
+        let calls = c.calls();
+        assert_eq!(calls[2], DrawCall::MoveTo(Coordinates::new(-5. * PT, -5. * PT)));
+        assert_eq!(calls[calls.len() - 2], DrawCall::SetFontSize(10.));
+        assert_eq!(calls[calls.len() - 1], DrawCall::FillText {
+            text: "ab".to_string(),
+            position: Coordinates::new(-5. * PT, -5. * PT),
+            angle: 0.
+        });
+    }
+
+    #[test]
+    fn test_svg() {
+        let node = FONode::new(Coordinates::new(0, 0), "a<b").shape(NodeShape::Circle);
+        let (svg, bb) = node.to_svg();
+        assert!(svg.contains(">a&lt;b</text><circle"), "{svg}");
+        assert_eq!(bb, BoundingBox::from_point(Coordinates::new(0, 0)).expand(node.radius()));
+    }
+}
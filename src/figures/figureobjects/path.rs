@@ -1,7 +1,10 @@
 //! A path created with the tikz path as reference. It will be capable of drawing circles and so many more stuff
+use std::fmt::Display;
+
 use crate::figures::*;
 use crate::core::*;
 
+#[derive(Clone, Copy)]
 pub enum PathDrawStyle {
     /// Draws the line
     Draw,
@@ -14,14 +17,53 @@ pub enum PathDrawStyle {
 }
 
 impl PathDrawStyle {
-    fn tikzify(&self) -> String {
+    /// Returns the command that starts the path, with the stroke options if the path is stroked
+    /// and the fill options if the path is filled
+    fn tikzify(&self, stroke: &StrokeStyle, fill: Option<&FillStyle>, rule: FillRule, opts: &TikzOutputOptions) -> (String, Preamble) {
         let s = match self {
-            PathDrawStyle::Draw => "\\draw ",
-            PathDrawStyle::Fill => "\\fill ",
-            PathDrawStyle::FillDraw => "\\filldraw "
+            PathDrawStyle::Draw => "\\draw",
+            PathDrawStyle::Fill => "\\fill",
+            PathDrawStyle::FillDraw => "\\filldraw"
         };
 
-        String::from(s)
+        let mut options = vec![];
+        let mut preamble = Preamble::new();
+
+        if let PathDrawStyle::Draw | PathDrawStyle::FillDraw = self {
+            let (v, p) = stroke.tikz_options(opts);
+            options.extend(v);
+            preamble.merge(&p);
+        }
+
+        if let (PathDrawStyle::Fill | PathDrawStyle::FillDraw, Some(fill)) = (self, fill) {
+            let (v, p) = fill.tikz_options(opts);
+            options.extend(v);
+            preamble.merge(&p);
+        }
+
+        if let (PathDrawStyle::Fill | PathDrawStyle::FillDraw, FillRule::EvenOdd) = (self, rule) {
+            options.push("even odd rule".to_string());
+        }
+
+        if options.len() > 0 {
+            return (format!("{s}[{}] ", options.join(", ")), preamble);
+        }
+
+        (format!("{s} "), preamble)
+    }
+
+    /// Returns the fill and stroke attributes of the svg path element
+    fn svgify(&self, stroke: &StrokeStyle, fill: &FillStyle, rule: FillRule) -> String {
+        let rule = if rule == FillRule::EvenOdd { " fill-rule=\"evenodd\"" } else { "" };
+        match self {
+            PathDrawStyle::Draw => format!("fill=\"none\" {}", stroke.svgify()),
+            PathDrawStyle::Fill => format!("{}{rule} stroke=\"none\"", fill.svgify()),
+            PathDrawStyle::FillDraw => format!("{}{rule} {}", fill.svgify(), stroke.svgify())
+        }
+    }
+
+    fn is_filled(&self) -> bool {
+        matches!(self, PathDrawStyle::Fill | PathDrawStyle::FillDraw)
     }
 
     fn repr(&self) -> &'static str {
@@ -33,135 +75,1028 @@ impl PathDrawStyle {
     }
 }
 
+/// How close the polygons used to work out the inside of a path are to the path, which is about a hundredth of a pt
+const FLATTEN_TOLERANCE: f64 = 1e-4;
+
+/// A point given either as (x, y) or in polar coordinates as (angle:radius)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Offset {
+    Cartesian(Coordinates),
+
+    /// The angle is in degrees, counterclockwise from the x-axis
+    Polar{angle: f64, radius: f64},
+}
+
+impl Offset {
+    pub fn get(&self) -> Coordinates {
+        match self {
+            Offset::Cartesian(x) => *x,
+            Offset::Polar { angle, radius } => {
+                let t = angle.to_radians();
+                Coordinates::new(radius * t.cos(), radius * t.sin())
+            }
+        }
+    }
+
+    /// Returns the offset after the transform. The translation does nothing to an offset. Polar offsets stay polar
+    /// if the transform does not stretch, shear or reflect
+    pub fn transformed(&self, tf: &Affine2) -> Offset {
+        if let (Offset::Polar { angle, radius }, Some((_, rotate, scale))) = (self, tf.linear().as_similarity()) {
+            return Offset::Polar { angle: angle + rotate.to_degrees(), radius: radius * scale };
+        }
+        Offset::Cartesian(tf.apply_vector(self.get()))
+    }
+
+    fn tikzify(&self, opts: &TikzOutputOptions) -> String {
+        match self {
+            Offset::Cartesian(x) => opts.coords(*x),
+            Offset::Polar { angle, radius } => format!("({}:{})", opts.number(*angle), opts.length(*radius))
+        }
+    }
+}
+
+impl Display for Offset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Offset::Cartesian(x) => write!(f, "{x}"),
+            Offset::Polar { angle, radius } => write!(f, "({angle}:{radius})")
+        }
+    }
+}
+
+/// Where a path segment goes. Like in Tikz, relative targets are measured from the last point
+/// that was not given with a single +
+#[derive(Clone, PartialEq)]
+pub enum PathTarget {
+    /// An absolute point, which may be a named coordinate
+    At(Position),
+
+    /// An absolute point in polar coordinates, like (30:2)
+    Polar{angle: f64, radius: f64},
+
+    /// +(x, y): goes to the point relative to the previous point, but later relative points are still measured from the previous point
+    Relative(Offset),
+
+    /// ++(x, y): goes to the point relative to the previous point, and later relative points are measured from here
+    RelativeMove(Offset),
+}
+
+impl PathTarget {
+    pub fn polar(angle: f64, radius: f64) -> Self {
+        PathTarget::Polar { angle, radius }
+    }
+
+    /// +(x, y)
+    pub fn relative(offset: Coordinates) -> Self {
+        PathTarget::Relative(Offset::Cartesian(offset))
+    }
+
+    /// ++(x, y)
+    pub fn relative_move(offset: Coordinates) -> Self {
+        PathTarget::RelativeMove(Offset::Cartesian(offset))
+    }
+
+    /// Returns the target after the transform, keeping relative targets relative. Named coordinates become
+    /// plain points since the transform does not move the named coordinate itself
+    pub fn transformed(&self, tf: &Affine2) -> PathTarget {
+        match self {
+            PathTarget::At(x) => PathTarget::At(tf.apply(x.get()).into()),
+            PathTarget::Polar { angle, radius } => {
+                // Polar coordinates are measured from the origin, so they only stay polar if the origin stays put
+                let polar = Offset::Polar { angle: *angle, radius: *radius };
+                match (tf.translation() == Coordinates::new(0, 0), polar.transformed(tf)) {
+                    (true, Offset::Polar { angle, radius }) => PathTarget::Polar { angle, radius },
+                    _ => PathTarget::At(tf.apply(polar.get()).into())
+                }
+            },
+            PathTarget::Relative(x) => PathTarget::Relative(x.transformed(tf)),
+            PathTarget::RelativeMove(x) => PathTarget::RelativeMove(x.transformed(tf)),
+        }
+    }
+
+    /// Writes the target in Tikz, keeping the relative and polar notation
+    pub fn tikzify(&self, opts: &TikzOutputOptions) -> String {
+        match self {
+            PathTarget::At(x) => x.tikzify(opts),
+            PathTarget::Polar { angle, radius } => Offset::Polar { angle: *angle, radius: *radius }.tikzify(opts),
+            PathTarget::Relative(x) => format!("+{}", x.tikzify(opts)),
+            PathTarget::RelativeMove(x) => format!("++{}", x.tikzify(opts)),
+        }
+    }
+}
+
+impl<T: Into<Position>> From<T> for PathTarget {
+    fn from(x: T) -> Self {
+        PathTarget::At(x.into())
+    }
+}
+
+impl Display for PathTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathTarget::At(x) => write!(f, "{x}"),
+            PathTarget::Polar { angle, radius } => write!(f, "({angle}:{radius})"),
+            PathTarget::Relative(x) => write!(f, "+{x}"),
+            PathTarget::RelativeMove(x) => write!(f, "++{x}"),
+        }
+    }
+}
+
+/// The state of the pen while a path is drawn: where it is, where relative targets are measured from,
+/// and where the current subpath started
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Pen {
+    position: Coordinates,
+    reference: Coordinates,
+    subpath: Coordinates,
+}
+
+impl Pen {
+    fn at(position: Coordinates) -> Self {
+        Self { position, reference: position, subpath: position }
+    }
+
+    /// Returns the point that the target refers to
+    fn resolve(&self, target: &PathTarget) -> Coordinates {
+        match target {
+            PathTarget::At(x) => x.get(),
+            PathTarget::Polar { angle, radius } => Offset::Polar { angle: *angle, radius: *radius }.get(),
+            PathTarget::Relative(x) | PathTarget::RelativeMove(x) => self.reference + x.get(),
+        }
+    }
+
+    /// Moves the pen to the target
+    fn advance(&self, target: &PathTarget) -> Pen {
+        let position = self.resolve(target);
+        match target {
+            PathTarget::Relative(_) => Pen { position, ..*self },
+            _ => Pen { position, reference: position, subpath: self.subpath }
+        }
+    }
+
+    /// Moves the pen to the target and starts a new subpath there
+    fn jump(&self, target: &PathTarget) -> Pen {
+        let pen = self.advance(target);
+        Pen { subpath: pen.position, ..pen }
+    }
+}
+
+/// A point as it is written in Tikz: a plain point plus named and polar coordinates, each some number of times.
+/// Points worked out from the ones in the path are written this way so that they keep following the names
+#[derive(Clone, PartialEq, Debug)]
+struct TikzPoint {
+    at: Coordinates,
+    terms: Vec<(i64, String)>,
+}
+
+impl TikzPoint {
+    fn plain(at: Coordinates) -> Self {
+        Self { at, terms: vec![] }
+    }
+
+    fn term(s: String) -> Self {
+        Self { at: Coordinates::new(0, 0), terms: vec![(1, s)] }
+    }
+
+    fn position(p: &Position, opts: &TikzOutputOptions) -> Self {
+        match p {
+            Position::At(x) => TikzPoint::plain(*x),
+            Position::Named(_) => TikzPoint::term(p.tikzify(opts))
+        }
+    }
+
+    fn offset(offset: &Offset, opts: &TikzOutputOptions) -> Self {
+        match offset {
+            Offset::Cartesian(x) => TikzPoint::plain(*x),
+            Offset::Polar { .. } => TikzPoint::term(offset.tikzify(opts)),
+        }
+    }
+
+    fn add(&self, other: &TikzPoint) -> Self {
+        let mut terms = self.terms.clone();
+        for (n, s) in other.terms.iter() {
+            match terms.iter_mut().find(|x| x.1 == *s) {
+                Some(x) => x.0 += n,
+                None => terms.push((*n, s.clone()))
+            }
+        }
+        Self { at: self.at + other.at, terms }
+    }
+
+    /// Writes the point a third of the way from b to a, which is where Bezier curves of degree 2 put the control
+    /// points of the same curve of degree 3. Plain points are written as they are and the rest with the calc library
+    fn tikzify_two_thirds(a: &TikzPoint, b: &TikzPoint, opts: &TikzOutputOptions) -> (String, Preamble) {
+        let at = (a.at + b.at * 2.) * (1. / 3.);
+        let doubled = TikzPoint { at: Coordinates::new(0, 0), terms: b.terms.iter().map(|(n, s)| (n * 2, s.clone())).collect() };
+        let terms = TikzPoint { at: Coordinates::new(0, 0), terms: a.terms.clone() }.add(&doubled).terms;
+
+        if terms.len() == 0 {
+            return (opts.coords(at), Preamble::new());
+        }
+
+        // Every multiple is some number of thirds
+        let mut parts = terms.iter().filter(|x| x.0 != 0).map(|(n, s)| {
+            match n % 3 {
+                0 if *n == 3 => s.clone(),
+                0 => format!("{}*{s}", n / 3),
+                _ => format!("{n}/3*{s}")
+            }
+        }).collect::<Vec<String>>();
+
+        if at != Coordinates::new(0, 0) {
+            parts.push(opts.coords(at));
+        }
+
+        (format!("($ {} $)", parts.join(" + ")), Preamble::new().library(TikzLibrary::Calc))
+    }
+}
+
+/// The pen while the Tikz code of a path is written, like Pen but with the points as they are written in Tikz
+#[derive(Clone, PartialEq, Debug)]
+struct TikzPen {
+    position: TikzPoint,
+    reference: TikzPoint,
+}
+
+impl TikzPen {
+    fn at(position: TikzPoint) -> Self {
+        Self { reference: position.clone(), position }
+    }
+
+    fn resolve(&self, target: &PathTarget, opts: &TikzOutputOptions) -> TikzPoint {
+        match target {
+            PathTarget::At(x) => TikzPoint::position(x, opts),
+            PathTarget::Polar { angle, radius } => TikzPoint::term(Offset::Polar { angle: *angle, radius: *radius }.tikzify(opts)),
+            PathTarget::Relative(x) | PathTarget::RelativeMove(x) => self.reference.add(&TikzPoint::offset(x, opts)),
+        }
+    }
+
+    fn advance(&self, target: &PathTarget, opts: &TikzOutputOptions) -> TikzPen {
+        let position = self.resolve(target, opts);
+        match target {
+            PathTarget::Relative(_) => TikzPen { position, reference: self.reference.clone() },
+            _ => TikzPen::at(position)
+        }
+    }
+}
+
+/// The image of an ellipse (or an arc of it) under a transform, which is another ellipse
+struct EllipseImage {
+    x_radius: f64,
+    y_radius: f64,
+    rotation: f64,
+
+    /// How much the parameter of the ellipse changes by (in degrees), and whether it goes the other way
+    angle_offset: f64,
+    reflected: bool,
+}
+
+impl EllipseImage {
+    fn new(tf: &Affine2, x_radius: f64, y_radius: f64, rotation: f64) -> Self {
+        // The ellipse is the image of the unit circle under m, and so the image of the ellipse is the image of the unit circle
+        // under tf * m. We read off the new ellipse from the singular value decomposition
+        let m = tf.linear() * Affine2::rotate(rotation.to_radians()) * Affine2::scale(x_radius, y_radius);
+        let (phi, s1, s2, theta) = m.decompose();
+        Self {
+            x_radius: s1,
+            y_radius: s2.abs(),
+            rotation: phi.to_degrees(),
+            angle_offset: theta.to_degrees(),
+            reflected: s2 < 0.
+        }
+    }
+
+    /// Returns the parameter on the new ellipse of the point at the angle (in degrees) on the old ellipse
+    fn angle(&self, angle: f64) -> f64 {
+        if self.reflected {
+            return -(angle + self.angle_offset);
+        }
+        angle + self.angle_offset
+    }
+}
+
 // Possible things to draw in a tikz path
+#[derive(Clone)]
 pub enum PathType {
     /// Straight line segment
-    Line{to: Coordinates},
+    Line{to: PathTarget},
 
-    /// Quadratic Bezier Curve
-    Quadratic{control: Coordinates, to: Coordinates},
+    /// Quadratic Bezier Curve. A relative control point is measured from the start of the curve
+    Quadratic{control: PathTarget, to: PathTarget},
 
-    /// Cubic Bezier Curve
-    Cubic{control_start: Coordinates, control_end: Coordinates, to: Coordinates},
+    /// Cubic Bezier Curve. Like in Tikz, a relative first control point is measured from the start of the curve
+    /// and a relative second control point is measured from the end of the curve
+    Cubic{control_start: PathTarget, control_end: PathTarget, to: PathTarget},
 
     /// Rectangle
-    Rectangle{to: Coordinates},
+    Rectangle{to: PathTarget},
 
     /// This path is made up of two segments: going the x direction first then the y direction
-    LineXY{to: Coordinates},
+    LineXY{to: PathTarget},
 
     /// This path is made up of two segments: going the y direction first then the x direction
-    LineYX{to: Coordinates},
+    LineYX{to: PathTarget},
+
+    /// Picks up the pen and puts it down at the target, which starts a new subpath
+    Move{to: PathTarget},
 
     /// Circle
     Circle{radius: f64},
 
-    /// Ellipse and general arcs
-    Arc{start_angle: f64, end_angle: f64, x_radius: f64, y_radius: f64}
+    /// Ellipse around the pen. The x-axis of the ellipse is rotated by 'rotation' degrees counterclockwise
+    Ellipse{x_radius: f64, y_radius: f64, rotation: f64},
+
+    /// Ellipse and general arcs. The angles are in degrees, and the x-axis of the ellipse is rotated by 'rotation' degrees counterclockwise
+    Arc{start_angle: f64, end_angle: f64, x_radius: f64, y_radius: f64, rotation: f64}
+}
+
+/// Returns the point on the ellipse centered at 'center' at the angle (in degrees)
+fn point_on_ellipse(center: Coordinates, x_radius: f64, y_radius: f64, rotation: f64, angle: f64) -> Coordinates {
+    let t = angle.to_radians();
+    center + Affine2::rotate(rotation.to_radians()).apply(Coordinates::new(x_radius * t.cos(), y_radius * t.sin()))
+}
+
+/// Returns the center of the arc that starts at 'from'
+fn arc_center(from: Coordinates, x_radius: f64, y_radius: f64, rotation: f64, start_angle: f64) -> Coordinates {
+    from - (point_on_ellipse(from, x_radius, y_radius, rotation, start_angle) - from)
+}
+
+/// Returns the bounding box of the whole ellipse
+fn ellipse_extent(center: Coordinates, x_radius: f64, y_radius: f64, rotation: f64) -> BoundingBox {
+    let (s, c) = rotation.to_radians().sin_cos();
+    let r = Coordinates::new((x_radius * c).hypot(y_radius * s), (x_radius * s).hypot(y_radius * c));
+    BoundingBox::new(center - r, center + r)
+}
+
+/// Returns the Tikz for an operation that needs a rotated coordinate system, which only lasts until the closing brace
+fn tikz_rotated(s: String, rotation: f64, opts: &TikzOutputOptions) -> String {
+    if rotation == 0. {
+        return s;
+    }
+    format!("{{[rotate={}] {s}}}", opts.number(rotation))
 }
 
 impl PathType {
-    /// Takes canvas and coordinates. Return new pen coordinates
-    fn draw_on_canvas(&self, c: HtmlCanvas, coord: Coordinates) -> Result<Coordinates, DrawError> {
-        let z = match self {
-            PathType::Arc { start_angle, end_angle, x_radius, y_radius } => {
-                todo!()
+    /// Returns the pen after drawing this segment. Following Tikz, circles and ellipses do not move the pen,
+    /// and arcs start at the current pen position
+    fn advance(&self, pen: Pen) -> Pen {
+        let from = pen.position;
+        match self {
+            PathType::Arc { start_angle, end_angle, x_radius, y_radius, rotation } => {
+                let center = arc_center(from, *x_radius, *y_radius, *rotation, *start_angle);
+                let position = point_on_ellipse(center, *x_radius, *y_radius, *rotation, *end_angle);
+                Pen { position, reference: position, subpath: pen.subpath }
+            },
+            PathType::Circle { .. } | PathType::Ellipse { .. } => Pen { subpath: from, ..pen },
+            PathType::Cubic { to, .. } => pen.advance(to),
+            PathType::Line { to } => pen.advance(to),
+            PathType::LineXY { to } => pen.advance(to),
+            PathType::LineYX { to } => pen.advance(to),
+            PathType::Move { to } => pen.jump(to),
+            PathType::Quadratic { to, .. } => pen.advance(to),
+            PathType::Rectangle { to } => pen.jump(to),
+        }
+    }
+
+    /// Returns the new pen coordinates after drawing this segment from the pen
+    fn end_point(&self, pen: Pen) -> Coordinates {
+        self.advance(pen).position
+    }
+
+    /// Returns the control points of a Bezier curve drawn from the pen, followed by its end point
+    fn control_points(&self, pen: Pen) -> Vec<Coordinates> {
+        match self {
+            PathType::Cubic { control_start, control_end, to } => {
+                let to = pen.resolve(to);
+                vec![Pen::at(pen.position).resolve(control_start), Pen::at(to).resolve(control_end), to]
+            },
+            PathType::Quadratic { control, to } => vec![Pen::at(pen.position).resolve(control), pen.resolve(to)],
+            _ => vec![]
+        }
+    }
+
+    /// Returns the direction of travel at the start and at the end of this segment when drawn from the pen.
+    /// Closed shapes return None since arrow tips do not go on them, and so do moves
+    fn tangents(&self, pen: Pen) -> Option<(Coordinates, Coordinates)> {
+        // Picks the first direction that is not zero
+        let nonzero = |a: Coordinates, b: Coordinates| if a.magnitude() > 0. { a } else { b };
+        let from = pen.position;
+
+        match self {
+            PathType::Arc { start_angle, end_angle, x_radius, y_radius, rotation } => {
+                let sign = if end_angle > start_angle { 1. } else { -1. };
+                let r = Affine2::rotate(rotation.to_radians());
+                let tangent = |angle: f64| {
+                    let t = angle.to_radians();
+                    r.apply(Coordinates::new(-x_radius * t.sin(), y_radius * t.cos())) * sign
+                };
+                Some((tangent(*start_angle), tangent(*end_angle)))
+            },
+            PathType::Circle { .. } | PathType::Ellipse { .. } | PathType::Rectangle { .. } | PathType::Move { .. } => None,
+            PathType::Cubic { .. } => {
+                let p = self.control_points(pen);
+                let to = p[2];
+                Some((nonzero(p[0] - from, to - from), nonzero(to - p[1], to - from)))
+            },
+            PathType::Line { to } => {
+                let to = pen.resolve(to);
+                Some((to - from, to - from))
+            },
+            PathType::LineXY { to } => {
+                let to = pen.resolve(to);
+                let h = Coordinates::new(to[0] - from[0], 0);
+                let v = Coordinates::new(0, to[1] - from[1]);
+                Some((nonzero(h, v), nonzero(v, h)))
+            },
+            PathType::LineYX { to } => {
+                let to = pen.resolve(to);
+                let h = Coordinates::new(to[0] - from[0], 0);
+                let v = Coordinates::new(0, to[1] - from[1]);
+                Some((nonzero(v, h), nonzero(h, v)))
+            },
+            PathType::Quadratic { .. } => {
+                let p = self.control_points(pen);
+                let (control, to) = (p[0], p[1]);
+                Some((nonzero(control - from, to - from), nonzero(to - control, to - from)))
             },
+        }
+    }
 
+    /// Returns the smallest bounding box of this segment when drawn from the pen, not counting the width of the line.
+    /// Circles and ellipses include their center, which is where the pen stays
+    fn bounding_box(&self, pen: Pen) -> BoundingBox {
+        let from = pen.position;
+        let bb = BoundingBox::from_point(from);
+        match self {
+            PathType::Arc { start_angle, end_angle, x_radius, y_radius, rotation } => {
+                let center = arc_center(from, *x_radius, *y_radius, *rotation, *start_angle);
+                EllipticArc::new(center, *x_radius, *y_radius, rotation.to_radians(), start_angle.to_radians(), end_angle.to_radians()).bounding_box()
+            },
+            PathType::Circle { radius } => bb.expand(radius.abs()),
+            PathType::Ellipse { x_radius, y_radius, rotation } => bb.union(&ellipse_extent(from, *x_radius, *y_radius, *rotation)),
+            PathType::Cubic { .. } => {
+                let p = self.control_points(pen);
+                BoundingBox::from_cubic(from, p[0], p[1], p[2])
+            },
+            PathType::Quadratic { .. } => {
+                let p = self.control_points(pen);
+                BoundingBox::from_quadratic(from, p[0], p[1])
+            },
+            PathType::Move { to } => BoundingBox::from_point(pen.resolve(to)),
+            PathType::Line { to } | PathType::LineXY { to } | PathType::LineYX { to } | PathType::Rectangle { to } => bb.include(pen.resolve(to)),
+        }
+    }
+
+    /// Returns the curves that make up this segment when drawn from the pen. Moves are made of nothing
+    fn pieces(&self, pen: Pen) -> Vec<Box<dyn ParametricCurve>> {
+        let from = pen.position;
+        let ellipse = |x_radius: f64, y_radius: f64, rotation: f64| -> Box<dyn ParametricCurve> {
+            Box::new(EllipticArc::new(from, x_radius, y_radius, rotation.to_radians(), 0., 2. * std::f64::consts::PI))
+        };
+        let polyline = |points: Vec<Coordinates>| points.windows(2)
+            .map(|w| Box::new(LineSegment::new(w[0], w[1])) as Box<dyn ParametricCurve>)
+            .collect::<Vec<_>>();
+
+        match self {
+            PathType::Arc { start_angle, end_angle, x_radius, y_radius, rotation } => {
+                let center = arc_center(from, *x_radius, *y_radius, *rotation, *start_angle);
+                vec![Box::new(EllipticArc::new(center, *x_radius, *y_radius, rotation.to_radians(), start_angle.to_radians(), end_angle.to_radians()))]
+            },
+            PathType::Circle { radius } => vec![ellipse(*radius, *radius, 0.)],
+            PathType::Ellipse { x_radius, y_radius, rotation } => vec![ellipse(*x_radius, *y_radius, *rotation)],
+            PathType::Cubic { .. } => {
+                let p = self.control_points(pen);
+                vec![Box::new(CubicBezier::new(from, p[0], p[1], p[2]))]
+            },
+            PathType::Quadratic { .. } => {
+                let p = self.control_points(pen);
+                vec![Box::new(QuadraticBezier::new(from, p[0], p[1]))]
+            },
+            PathType::Line { to } => polyline(vec![from, pen.resolve(to)]),
+            PathType::LineXY { to } => {
+                let to = pen.resolve(to);
+                polyline(vec![from, Coordinates::new(to[0], from[1]), to])
+            },
+            PathType::LineYX { to } => {
+                let to = pen.resolve(to);
+                polyline(vec![from, Coordinates::new(from[0], to[1]), to])
+            },
+            PathType::Move { .. } => vec![],
+            PathType::Rectangle { to } => {
+                let to = pen.resolve(to);
+                polyline(vec![from, Coordinates::new(to[0], from[1]), to, Coordinates::new(from[0], to[1]), from])
+            },
+        }
+    }
+
+    /// Adds this segment to the polygons that approximate the path. 'current' is the subpath being drawn, and finished
+    /// subpaths go into 'done'. Circles, ellipses and rectangles are subpaths of their own
+    fn flatten(&self, pen: Pen, tolerance: f64, current: &mut Vec<Coordinates>, done: &mut Vec<Vec<Coordinates>>) {
+        let points = self.pieces(pen).iter()
+            .flat_map(|x| x.flatten(tolerance).into_iter().skip(1))
+            .collect::<Vec<Coordinates>>();
+
+        match self {
+            PathType::Circle { .. } | PathType::Ellipse { .. } => done.push(points),
+            PathType::Rectangle { .. } => {
+                done.push(points);
+                done.push(std::mem::take(current));
+                current.push(self.end_point(pen));
+            },
+            PathType::Move { .. } => {
+                done.push(std::mem::take(current));
+                current.push(self.end_point(pen));
+            },
+            _ => current.extend(points)
+        }
+    }
+
+    /// Returns the svg path data of this segment when drawn from the pen
+    fn svgify(&self, pen: Pen) -> String {
+        let from = pen.position;
+        match self {
+            PathType::Arc { start_angle, end_angle, x_radius, y_radius, rotation } => {
+                // Svg arcs are specified by their endpoints, so we split the arc into pieces of at most
+                // 180 degrees to make the large arc flag unambiguous. The y-axis is flipped so counterclockwise arcs
+                // have a sweep flag of 0 and the rotation goes the other way
+                let center = arc_center(from, *x_radius, *y_radius, *rotation, *start_angle);
+                let sweep = if end_angle > start_angle { 0 } else { 1 };
+                let n = ((end_angle - start_angle).abs() / 180.).ceil().max(1.) as usize;
+                (1..=n).map(|i| {
+                    let angle = start_angle + (end_angle - start_angle) * i as f64 / n as f64;
+                    let p = point_on_ellipse(center, *x_radius, *y_radius, *rotation, angle);
+                    format!("A {} {} {} 0 {sweep} {}", x_radius.abs().to_svg(), y_radius.abs().to_svg(), (0. - rotation).to_svg(), svg_point(p))
+                }).collect::<Vec<String>>().join(" ")
+            },
             PathType::Circle { radius } => {
-                c.draw_circle(coord, *radius)?;
-                coord
+                let r = Coordinates::new(*radius, 0);
+                let rs = radius.abs().to_svg();
+                format!("M {} A {rs} {rs} 0 1 0 {} A {rs} {rs} 0 1 0 {} Z M {}", svg_point(from + r), svg_point(from - r), svg_point(from + r), svg_point(from))
             },
+            PathType::Ellipse { x_radius, y_radius, rotation } => {
+                let a = point_on_ellipse(from, *x_radius, *y_radius, *rotation, 0.);
+                let b = point_on_ellipse(from, *x_radius, *y_radius, *rotation, 180.);
+                let arc = format!("A {} {} {} 1 0", x_radius.abs().to_svg(), y_radius.abs().to_svg(), (0. - rotation).to_svg());
+                format!("M {} {arc} {} {arc} {} Z M {}", svg_point(a), svg_point(b), svg_point(a), svg_point(from))
+            },
+            PathType::Cubic { .. } => {
+                let p = self.control_points(pen);
+                format!("C {} {} {}", svg_point(p[0]), svg_point(p[1]), svg_point(p[2]))
+            },
+            PathType::Line { to } => format!("L {}", svg_point(pen.resolve(to))),
+            PathType::LineXY { to } => {
+                let to = pen.resolve(to);
+                format!("L {} L {}", svg_point(Coordinates::new(to[0], from[1])), svg_point(to))
+            },
+            PathType::LineYX { to } => {
+                let to = pen.resolve(to);
+                format!("L {} L {}", svg_point(Coordinates::new(from[0], to[1])), svg_point(to))
+            },
+            PathType::Move { to } => format!("M {}", svg_point(pen.resolve(to))),
+            PathType::Quadratic { .. } => {
+                let p = self.control_points(pen);
+                format!("Q {} {}", svg_point(p[0]), svg_point(p[1]))
+            },
+            PathType::Rectangle { to } => {
+                // A rectangle is a closed subpath on its own, and the pen ends up at the opposite corner
+                let to = pen.resolve(to);
+                let c1 = Coordinates::new(to[0], from[1]);
+                let c2 = Coordinates::new(from[0], to[1]);
+                format!("M {} L {} L {} L {} Z M {}", svg_point(from), svg_point(c1), svg_point(to), svg_point(c2), svg_point(to))
+            }
+        }
+    }
 
-            PathType::Cubic { control_start, control_end, to } => {
-                todo!()
+    /// Adds this segment to the current path of the canvas. Returns the new pen
+    fn draw_on_canvas(&self, c: &dyn RenderTarget, pen: Pen) -> Result<Pen, DrawError> {
+        let coord = pen.position;
+        match self {
+            PathType::Arc { start_angle, end_angle, x_radius, y_radius, rotation } => {
+                let center = arc_center(coord, *x_radius, *y_radius, *rotation, *start_angle);
+                c.ellipse(center, x_radius.abs(), y_radius.abs(), rotation.to_radians(), start_angle.to_radians(), end_angle.to_radians(), end_angle > start_angle)?;
+            },
+
+            PathType::Circle { radius } => {
+                // The circle is a subpath on its own and the pen goes back to the center afterwards
+                c.move_to(coord + Coordinates::new(radius.abs(), 0))?;
+                c.arc(coord, radius.abs(), 0., 2. * std::f64::consts::PI, false)?;
+                c.close_path()?;
+                c.move_to(coord)?;
+            },
+
+            PathType::Ellipse { x_radius, y_radius, rotation } => {
+                c.move_to(point_on_ellipse(coord, *x_radius, *y_radius, *rotation, 0.))?;
+                c.ellipse(coord, x_radius.abs(), y_radius.abs(), rotation.to_radians(), 0., 2. * std::f64::consts::PI, false)?;
+                c.close_path()?;
+                c.move_to(coord)?;
+            },
+
+            PathType::Cubic { .. } => {
+                let p = self.control_points(pen);
+                c.bezier_curve_to(p[0], p[1], p[2])?;
             },
 
             PathType::Line { to } => {
-                c.draw_line(coord, *to)?;
-                *to
+                c.line_to(pen.resolve(to))?;
             },
 
             PathType::LineXY { to } => {
-                let intermediate = Coordinates::new(to[0], coord[1]);
-                c.draw_line(coord, intermediate)?;
-                c.draw_line(intermediate, *to)?;
-                *to
+                let to = pen.resolve(to);
+                c.line_to(Coordinates::new(to[0], coord[1]))?;
+                c.line_to(to)?;
             },
 
             PathType::LineYX { to } => {
-                let intermediate = Coordinates::new(coord[0], to[1]);
-                c.draw_line(coord, intermediate)?;
-                c.draw_line(intermediate, *to)?;
-                *to
+                let to = pen.resolve(to);
+                c.line_to(Coordinates::new(coord[0], to[1]))?;
+                c.line_to(to)?;
             },
 
-            PathType::Quadratic { control, to } => {
-                todo!()
+            PathType::Move { to } => {
+                c.move_to(pen.resolve(to))?;
+            },
+
+            PathType::Quadratic { .. } => {
+                let p = self.control_points(pen);
+                c.quadratic_curve_to(p[0], p[1])?;
             },
 
             PathType::Rectangle { to } => {
-                c.draw_rectangles(coord, *to);
-                *to
+                // Same as in Tikz, the rectangle is a closed subpath and the pen ends up at the opposite corner
+                let to = pen.resolve(to);
+                c.move_to(coord)?;
+                c.line_to(Coordinates::new(to[0], coord[1]))?;
+                c.line_to(to)?;
+                c.line_to(Coordinates::new(coord[0], to[1]))?;
+                c.close_path()?;
+                c.move_to(to)?;
             }
         };
 
-        Ok(z)
+        Ok(self.advance(pen))
+    }
+
+    /// Returns the segments that draw the image of this segment under the transform, when drawn from the pen.
+    /// Most segments stay as one segment, but -|, |- and rectangles are made of lines that need not stay parallel to the axes
+    fn transformed(&self, tf: &Affine2, pen: Pen) -> Vec<PathType> {
+        let from = pen.position;
+
+        // The extra points that we add along the way. If the target is relative, we use + so that the point
+        // we measure the target from stays the same
+        let extra = |target: &PathTarget, p: Coordinates| match target {
+            PathTarget::At(_) | PathTarget::Polar { .. } => PathTarget::At(tf.apply(p).into()),
+            _ => PathTarget::relative(tf.apply_vector(p - pen.reference))
+        };
+
+        match self {
+            PathType::Arc { start_angle, end_angle, x_radius, y_radius, rotation } => {
+                let e = EllipseImage::new(tf, *x_radius, *y_radius, *rotation);
+                vec![PathType::Arc {
+                    start_angle: e.angle(*start_angle),
+                    end_angle: e.angle(*end_angle),
+                    x_radius: e.x_radius,
+                    y_radius: e.y_radius,
+                    rotation: e.rotation
+                }]
+            },
+            PathType::Circle { radius } => {
+                let e = EllipseImage::new(tf, *radius, *radius, 0.);
+                if calc::eq(&e.x_radius, &e.y_radius) {
+                    return vec![PathType::Circle { radius: e.x_radius }];
+                }
+                vec![PathType::Ellipse { x_radius: e.x_radius, y_radius: e.y_radius, rotation: e.rotation }]
+            },
+            PathType::Ellipse { x_radius, y_radius, rotation } => {
+                let e = EllipseImage::new(tf, *x_radius, *y_radius, *rotation);
+                vec![PathType::Ellipse { x_radius: e.x_radius, y_radius: e.y_radius, rotation: e.rotation }]
+            },
+            PathType::Cubic { control_start, control_end, to } => vec![PathType::Cubic {
+                control_start: control_start.transformed(tf),
+                control_end: control_end.transformed(tf),
+                to: to.transformed(tf)
+            }],
+            PathType::Line { to } => vec![PathType::Line { to: to.transformed(tf) }],
+            PathType::LineXY { to } | PathType::LineYX { to } => {
+                let xy = matches!(self, PathType::LineXY { .. });
+                if tf.preserves_axes() || tf.swaps_axes() {
+                    let to = to.transformed(tf);
+                    return match xy == tf.preserves_axes() {
+                        true => vec![PathType::LineXY { to }],
+                        false => vec![PathType::LineYX { to }]
+                    };
+                }
+
+                let p = pen.resolve(to);
+                let corner = if xy { Coordinates::new(p[0], from[1]) } else { Coordinates::new(from[0], p[1]) };
+                vec![PathType::Line { to: extra(to, corner) }, PathType::Line { to: to.transformed(tf) }]
+            },
+            PathType::Move { to } => vec![PathType::Move { to: to.transformed(tf) }],
+            PathType::Quadratic { control, to } => vec![PathType::Quadratic { control: control.transformed(tf), to: to.transformed(tf) }],
+            PathType::Rectangle { to } => {
+                if tf.preserves_axes() || tf.swaps_axes() {
+                    return vec![PathType::Rectangle { to: to.transformed(tf) }];
+                }
+
+                // The rectangle becomes a parallelogram, which is a closed subpath of its own
+                let p = pen.resolve(to);
+                vec![
+                    PathType::Move { to: extra(to, from) },
+                    PathType::Line { to: extra(to, Coordinates::new(p[0], from[1])) },
+                    PathType::Line { to: extra(to, p) },
+                    PathType::Line { to: extra(to, Coordinates::new(from[0], p[1])) },
+                    PathType::Line { to: extra(to, from) },
+                    PathType::Move { to: to.transformed(tf) },
+                ]
+            },
+        }
     }
 
     fn repr(&self) -> String {
         match self {
-            PathType::Arc { start_angle, end_angle, x_radius, y_radius } => format!("Arc({start_angle},{end_angle},{x_radius},{y_radius})"),
+            PathType::Arc { start_angle, end_angle, x_radius, y_radius, rotation } => format!("Arc({start_angle},{end_angle},{x_radius},{y_radius},{rotation})"),
             PathType::Circle { radius } => format!("Circ({radius})"),
             PathType::Cubic { control_start, control_end, to } => format!("Cb{control_start}{control_end}{to}"),
+            PathType::Ellipse { x_radius, y_radius, rotation } => format!("Ell({x_radius},{y_radius},{rotation})"),
             PathType::Line { to } => format!("L{to}"),
             PathType::LineXY { to } => format!("Lxy{to}"),
             PathType::LineYX { to } => format!("Lyx{to}"),
+            PathType::Move { to } => format!("M{to}"),
             PathType::Quadratic { control, to } => format!("Qbezier{control}{to}"),
             PathType::Rectangle { to } => format!("Rect{to}")
         }
     }
 
-    fn tikzify(&self) -> String {
+    /// Returns the pen of the Tikz code after this segment. 'next' is the pen after it, which is where arcs end up
+    fn advance_tikz(&self, pen: &TikzPen, next: Pen, opts: &TikzOutputOptions) -> TikzPen {
         match self {
-            PathType::Arc { start_angle, end_angle, x_radius, y_radius } => format!("arc ({start_angle}:{end_angle}:{x_radius} and {y_radius})"),
-            PathType::Circle { radius } => format!("circle ({radius})"),
-            PathType::Cubic { control_start, control_end, to } => format!(".. controls {control_start} and {control_end}..{to}"),
-            PathType::Line { to } => format!("-- {to}"),
-            PathType::LineXY { to } => format!("-| {to}"),
-            PathType::LineYX { to } => format!("|- {to}"),
-            PathType::Quadratic { control, to } => format!(".. controls {control} .. {to};"),
-            PathType::Rectangle { to } => format!("rectangle {to}")
+            PathType::Arc { .. } => TikzPen::at(TikzPoint::plain(next.position)),
+            PathType::Circle { .. } | PathType::Ellipse { .. } => pen.clone(),
+            PathType::Cubic { to, .. } | PathType::Line { to } | PathType::LineXY { to } | PathType::LineYX { to } |
+            PathType::Move { to } | PathType::Quadratic { to, .. } | PathType::Rectangle { to } => pen.advance(to, opts),
         }
     }
+
+    /// Returns the tikz path operation of this segment when drawn from the pen, and what it needs in the preamble
+    fn tikzify(&self, pen: &TikzPen, opts: &TikzOutputOptions) -> (String, Preamble) {
+        let s = match self {
+            PathType::Arc { start_angle, end_angle, x_radius, y_radius, rotation } => {
+                let arc = format!("arc ({}:{}:{} and {})", opts.number(*start_angle), opts.number(*end_angle), opts.length(*x_radius), opts.length(*y_radius));
+                tikz_rotated(arc, *rotation, opts)
+            },
+            PathType::Circle { radius } => format!("circle ({})", opts.length(*radius)),
+            PathType::Cubic { control_start, control_end, to } => {
+                format!(".. controls {} and {} .. {}", control_start.tikzify(opts), control_end.tikzify(opts), to.tikzify(opts))
+            },
+            PathType::Ellipse { x_radius, y_radius, rotation } => {
+                tikz_rotated(format!("ellipse ({} and {})", opts.length(*x_radius), opts.length(*y_radius)), *rotation, opts)
+            },
+            PathType::Line { to } => format!("-- {}", to.tikzify(opts)),
+            PathType::LineXY { to } => format!("-| {}", to.tikzify(opts)),
+            PathType::LineYX { to } => format!("|- {}", to.tikzify(opts)),
+            PathType::Move { to } => to.tikzify(opts),
+            PathType::Quadratic { control, to } => {
+                // Tikz only has cubic curves, so we elevate the degree of the curve. The control points are
+                // 2/3 of the way from the end points to the quadratic control point
+                let from = &pen.position;
+                let end = pen.resolve(to, opts);
+                let (control_start, p1) = match control {
+                    // Relative first control points are measured from the start in Tikz too
+                    PathTarget::Relative(x) | PathTarget::RelativeMove(x) => {
+                        let offset = match x {
+                            Offset::Cartesian(x) => Offset::Cartesian(*x * (2. / 3.)),
+                            Offset::Polar { angle, radius } => Offset::Polar { angle: *angle, radius: radius * (2. / 3.) }
+                        };
+                        (format!("+{}", offset.tikzify(opts)), Preamble::new())
+                    },
+                    _ => TikzPoint::tikzify_two_thirds(from, &TikzPen::at(from.clone()).resolve(control, opts), opts)
+                };
+                let control = TikzPen::at(from.clone()).resolve(control, opts);
+                let (control_end, p2) = TikzPoint::tikzify_two_thirds(&end, &control, opts);
+
+                let mut preamble = p1;
+                preamble.merge(&p2);
+                return (format!(".. controls {control_start} and {control_end} .. {}", to.tikzify(opts)), preamble);
+            },
+            PathType::Rectangle { to } => format!("rectangle {}", to.tikzify(opts))
+        };
+
+        (s, Preamble::new())
+    }
 }
 
 /// Implementation of a tikz path
+#[derive(Clone)]
 pub struct FOPath {
     style: PathDrawStyle,
-    start: Coordinates,
-    data: Vec<PathType>
+    start: Position,
+    data: Vec<PathType>,
+    stroke: StrokeStyle,
+    fill: Option<FillStyle>,
+    fill_rule: FillRule
 }
 
-impl FigureObject for FOPath {
-    fn draw_on_canvas(&self, c: HtmlCanvas) -> Result<(), DrawError> {
-        let mut current_coords = self.start;
+impl FOPath {
+    /// Creates a new path that starts at 'start' and draws every segment in 'data' in order
+    pub fn new<P: Into<Position>>(style: PathDrawStyle, start: P, data: Vec<PathType>) -> Self {
+        Self {
+            style,
+            start: start.into(),
+            data,
+            stroke: StrokeStyle::new(),
+            fill: None,
+            fill_rule: FillRule::NonZero
+        }
+    }
+
+    /// Sets the style of the outline. This does nothing for filled paths without outlines
+    pub fn stroke_style(mut self, stroke: StrokeStyle) -> Self {
+        self.stroke = stroke;
+        return self;
+    }
+
+    /// Sets what the inside is filled with. This does nothing for paths that are only drawn
+    pub fn fill_style(mut self, fill: FillStyle) -> Self {
+        self.fill = Some(fill);
+        return self;
+    }
+
+    /// Sets how the inside is worked out when the path crosses itself or has holes
+    pub fn fill_rule(mut self, rule: FillRule) -> Self {
+        self.fill_rule = rule;
+        return self;
+    }
+
+    /// Returns what the path is actually filled with. Without a fill style, Tikz fills with the
+    /// color of the path for \filldraw and black for \fill
+    fn effective_fill(&self) -> FillStyle {
+        if let Some(fill) = &self.fill {
+            return fill.clone();
+        }
+
+        match self.style {
+            PathDrawStyle::FillDraw => FillStyle::solid(self.stroke.effective_color()),
+            _ => FillStyle::solid(Color::rgb(0, 0, 0))
+        }
+    }
+
+    /// Approximates the path with polygons, one for each subpath, that never get further than 'tolerance' from the path
+    pub fn flatten(&self, tolerance: f64) -> Vec<Vec<Coordinates>> {
+        let mut current = vec![self.start.get()];
+        let mut done = vec![];
+        let mut pen = Pen::at(self.start.get());
+
+        for x in self.data.iter() {
+            x.flatten(pen, tolerance, &mut current, &mut done);
+            pen = x.advance(pen);
+        }
+
+        done.push(current);
+        done.retain(|x| x.len() > 1);
+        return done;
+    }
+
+    /// True if p is inside the region the path encloses, as it would be filled. This ignores whether the path is actually filled
+    pub fn contains(&self, p: Coordinates) -> bool {
+        let winding = self.flatten(FLATTEN_TOLERANCE).iter().map(|x| calc::winding_number(p, x)).sum();
+        self.fill_rule.contains(winding)
+    }
+
+    /// Returns the distance from p to the closest point on the path itself, ignoring the inside
+    pub fn distance_to_outline(&self, p: Coordinates) -> f64 {
+        let mut d = f64::INFINITY;
+        let mut pen = Pen::at(self.start.get());
+
+        for x in self.data.iter() {
+            d = x.pieces(pen).iter().fold(d, |d, c| d.min(c.closest_point(p).1));
+            pen = x.advance(pen);
+        }
+
+        return d;
+    }
+
+    /// Returns the image of the path under the transform
+    pub fn transformed(&self, tf: &Affine2) -> FOPath {
+        let mut data = vec![];
+        let mut pen = Pen::at(self.start.get());
 
         for x in self.data.iter() {
-            match self.style {
-                PathDrawStyle::Draw => {
-                    current_coords = x.draw_on_canvas(c.clone(), current_coords)?;
+            data.extend(x.transformed(tf, pen));
+            pen = x.advance(pen);
+        }
+
+        FOPath {
+            style: self.style,
+            start: tf.apply(self.start.get()).into(),
+            data,
+            stroke: self.stroke.clone(),
+            fill: self.fill.clone(),
+            fill_rule: self.fill_rule
+        }
+    }
+
+    /// Adds the whole path to the canvas as the current path without stroking or filling it, which is what clipping needs
+    pub fn trace(&self, c: &dyn RenderTarget) -> Result<(), DrawError> {
+        c.begin_path()?;
+        c.move_to(self.start.get())?;
+        let mut pen = Pen::at(self.start.get());
+
+        for x in self.data.iter() {
+            pen = x.draw_on_canvas(c, pen)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the path in Tikz without the command in front, like (0, 0) -- (1, 1), and what it needs in the preamble
+    pub fn tikz_path(&self, opts: &TikzOutputOptions) -> (String, Preamble) {
+        let mut s = self.start.tikzify(opts);
+        let mut preamble = Preamble::new();
+        let mut pen = Pen::at(self.start.get());
+        let mut tikz_pen = TikzPen::at(TikzPoint::position(&self.start, opts));
+
+        for x in self.data.iter() {
+            let (t, p) = x.tikzify(&tikz_pen, opts);
+            s.push_str(" ");
+            s.push_str(&t);
+            preamble.merge(&p);
+
+            let next = x.advance(pen);
+            tikz_pen = x.advance_tikz(&tikz_pen, next, opts);
+            pen = next;
+        }
+
+        return (s, preamble);
+    }
+
+    /// Returns the svg path data of the whole path
+    pub fn svg_path_data(&self) -> String {
+        let mut d = vec![format!("M {}", svg_point(self.start.get()))];
+        let mut pen = Pen::at(self.start.get());
+
+        for x in self.data.iter() {
+            d.push(x.svgify(pen));
+            pen = x.advance(pen);
+        }
+
+        d.join(" ")
+    }
+
+    /// Draws the arrow tips at the two ends of the path
+    fn draw_arrow_tips(&self, c: &dyn RenderTarget) -> Result<(), DrawError> {
+        let w = self.stroke.local_width();
+        let mut pen = Pen::at(self.start.get());
+
+        for (i, x) in self.data.iter().enumerate() {
+            if let Some((start_dir, end_dir)) = x.tangents(pen) {
+                if i == 0 {
+                    self.stroke.start_arrow.draw_on_canvas(c, pen.position, start_dir * -1., w)?;
                 }
 
-                _ => todo!()
+                if i == self.data.len() - 1 {
+                    self.stroke.end_arrow.draw_on_canvas(c, x.end_point(pen), end_dir, w)?;
+                }
             }
+            pen = x.advance(pen);
         }
 
         Ok(())
     }
+}
+
+impl FigureObject for FOPath {
+    fn draw_on_canvas(&self, c: &dyn RenderTarget) -> Result<(), DrawError> {
+        if let PathDrawStyle::Draw | PathDrawStyle::FillDraw = self.style {
+            c.set_stroke_style(&self.stroke)?;
+        }
+
+        if let PathDrawStyle::Fill | PathDrawStyle::FillDraw = self.style {
+            c.set_fill_style(&self.effective_fill(), self.bounding_box())?;
+        }
+
+        // Build the whole path before filling or stroking, so that fills cover the region enclosed by every segment
+        self.trace(c)?;
+
+        match self.style {
+            PathDrawStyle::Draw => c.stroke()?,
+            PathDrawStyle::Fill => return c.fill_with_rule(self.fill_rule),
+            PathDrawStyle::FillDraw => {
+                c.fill_with_rule(self.fill_rule)?;
+                c.stroke()?;
+            }
+        };
+
+        self.draw_arrow_tips(c)
+    }
+
+    fn transformed(&self, tf: &Affine2) -> PlottableObject {
+        FOPath::transformed(self, tf).wrap()
+    }
 
     fn repr(&self) -> String {
         let mut s = String::from(self.style.repr());
+        if self.fill_rule == FillRule::EvenOdd {
+            s.push_str("eo ");
+        }
         s.push_str(&self.start.to_string());
 
         for x in self.data.iter() {
@@ -171,16 +1106,564 @@ impl FigureObject for FOPath {
         return s
     }
 
-    fn tikzify(&self) -> (String, Option<String>) {
-        let mut s = self.style.tikzify();
-        s.push_str(&self.start.to_string());
+    fn to_svg(&self) -> (String, BoundingBox) {
+        let svg = format!("<path d=\"{}\" {}/>", self.svg_path_data(), self.style.svgify(&self.stroke, &self.effective_fill(), self.fill_rule));
+        (svg, self.bounding_box())
+    }
+
+    fn distance_to(&self, p: Coordinates) -> f64 {
+        if self.style.is_filled() && self.contains(p) {
+            return 0.;
+        }
+        self.distance_to_outline(p)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let mut bb = BoundingBox::from_point(self.start.get());
+        let mut pen = Pen::at(self.start.get());
 
         for x in self.data.iter() {
-            s.push_str(&x.repr());
+            bb = bb.union(&x.bounding_box(pen));
+            pen = x.advance(pen);
         }
 
+        return bb;
+    }
+
+    fn tikzify(&self, opts: &TikzOutputOptions) -> (String, Preamble) {
+        let (mut s, mut preamble) = self.style.tikzify(&self.stroke, self.fill.as_ref(), self.fill_rule, opts);
+        let (path, p) = self.tikz_path(opts);
+        s.push_str(&path);
         s.push_str(";");
+        preamble.merge(&p);
+
+        return (s, preamble)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        return (s, None)
+    fn path_data(data: Vec<PathType>) -> String {
+        let (svg, _) = FOPath::new(PathDrawStyle::Draw, Coordinates::new(0, 0), data).to_svg();
+        let start = svg.find("d=\"").unwrap() + 3;
+        let end = start + svg[start..].find('"').unwrap();
+        svg[start..end].to_string()
     }
-}
\ No newline at end of file
+
+    /// Compares the tikz code of a path with tests/golden/{name}.tikz
+    fn assert_golden(name: &str, path: FOPath) {
+        let file = format!("{}/tests/golden/{name}.tikz", env!("CARGO_MANIFEST_DIR"));
+        let expected = std::fs::read_to_string(&file).expect(&format!("Failed to read golden file {file}"));
+        let (tikz, preamble) = path.tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, expected.trim_end(), "Tikz code of {name} does not match {file}");
+        assert!(preamble.is_empty());
+    }
+
+    fn draw(data: Vec<PathType>) -> FOPath {
+        FOPath::new(PathDrawStyle::Draw, Coordinates::new(0, 0), data)
+    }
+
+    #[test]
+    fn test_tikz_golden_lines() {
+        assert_golden("path_line", draw(vec![PathType::Line { to: Coordinates::new(1, 2).into() }, PathType::Line { to: Coordinates::new(-0.5, 3).into() }]));
+        assert_golden("path_line_xy", draw(vec![PathType::LineXY { to: Coordinates::new(1, 2).into() }]));
+        assert_golden("path_line_yx", draw(vec![PathType::LineYX { to: Coordinates::new(1, 2).into() }]));
+        assert_golden("path_rectangle", draw(vec![PathType::Rectangle { to: Coordinates::new(2, 1).into() }]));
+    }
+
+    #[test]
+    fn test_tikz_golden_curves() {
+        assert_golden("path_quadratic", draw(vec![PathType::Quadratic { control: Coordinates::new(3, 3).into(), to: Coordinates::new(6, 0).into() }]));
+        assert_golden("path_cubic", draw(vec![PathType::Cubic { control_start: Coordinates::new(0, 1).into(), control_end: Coordinates::new(1, 1).into(), to: Coordinates::new(1, 0).into() }]));
+        assert_golden("path_circle", draw(vec![PathType::Circle { radius: 1.5 }]));
+        assert_golden("path_arc", draw(vec![PathType::Arc { start_angle: 0., end_angle: 90., x_radius: 2., y_radius: 1., rotation: 0. }]));
+    }
+
+    #[test]
+    fn test_tikz_golden_styles() {
+        let data = || vec![
+            PathType::Line { to: Coordinates::new(2, 0).into() },
+            PathType::Quadratic { control: Coordinates::new(2, 3).into(), to: Coordinates::new(-1, 0).into() },
+            PathType::Line { to: Coordinates::new(0, 0).into() },
+        ];
+        assert_golden("path_draw", FOPath::new(PathDrawStyle::Draw, Coordinates::new(0, 0), data()));
+        assert_golden("path_fill", FOPath::new(PathDrawStyle::Fill, Coordinates::new(0, 0), data()));
+        assert_golden("path_filldraw", FOPath::new(PathDrawStyle::FillDraw, Coordinates::new(0, 0), data()));
+    }
+
+    #[test]
+    fn test_tikz_golden_quadratic_symbolic() {
+        // Control points of quadratic curves follow named coordinates and keep relative and polar offsets
+        let reg = CoordinateRegistry::new();
+        let a = reg.set("A", Coordinates::new(0, 0));
+        let b = reg.set("B", Coordinates::new(1, 2));
+        let path = FOPath::new(PathDrawStyle::Draw, a, vec![
+            PathType::Quadratic { control: b.into(), to: Coordinates::new(3, 0).into() },
+            PathType::Quadratic { control: PathTarget::relative(Coordinates::new(1.5, 3)), to: PathTarget::relative_move(Coordinates::new(3, 0)) },
+            PathType::Quadratic { control: PathTarget::polar(90., 3.), to: Coordinates::new(9, 0).into() },
+        ]);
+
+        let file = format!("{}/tests/golden/path_quadratic_symbolic.tikz", env!("CARGO_MANIFEST_DIR"));
+        let (tikz, preamble) = path.tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, std::fs::read_to_string(&file).expect(&format!("Failed to read golden file {file}")).trim_end());
+        assert_eq!(preamble.lines(), vec!["\\usetikzlibrary{calc}"]);
+    }
+
+    #[test]
+    fn test_tikz_quadratic_thirds() {
+        // The control points of the elevated curve are not exact, which used to leak into the output
+        let path = draw(vec![PathType::Quadratic { control: Coordinates::new(1, 1).into(), to: Coordinates::new(1, 0).into() }]);
+        let (tikz, _) = path.tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, "\\draw (0, 0) .. controls (0.6667, 0.6667) and (1, 0.6667) .. (1, 0);");
+    }
+
+    #[test]
+    fn test_canvas_lines() {
+        let c = RecordingCanvas::new();
+        let path = FOPath::new(PathDrawStyle::Draw, Coordinates::new(0, 0), vec![
+            PathType::Line { to: Coordinates::new(1, 0).into() },
+            PathType::LineXY { to: Coordinates::new(2, 1).into() },
+        ]);
+        path.draw_on_canvas(&c).unwrap();
+
+        assert_eq!(c.calls(), vec![
+            DrawCall::SetStrokeStyle(StrokeStyle::new()),
+            DrawCall::BeginPath,
+            DrawCall::MoveTo(Coordinates::new(0, 0)),
+            DrawCall::LineTo(Coordinates::new(1, 0)),
+            DrawCall::LineTo(Coordinates::new(2, 0)),
+            DrawCall::LineTo(Coordinates::new(2, 1)),
+            DrawCall::Stroke,
+        ]);
+    }
+
+    #[test]
+    fn test_canvas_circle() {
+        let c = RecordingCanvas::new();
+        let path = FOPath::new(PathDrawStyle::Draw, Coordinates::new(1, 1), vec![PathType::Circle { radius: 2. }]);
+        path.draw_on_canvas(&c).unwrap();
+
+        let calls = c.calls();
+        assert_eq!(calls.len(), 8);
+        assert_eq!(calls[3], DrawCall::MoveTo(Coordinates::new(3, 1)));
+        if let DrawCall::Arc { center, radius, .. } = calls[4] {
+            assert_eq!(center, Coordinates::new(1, 1));
+            assert_eq!(radius, 2.);
+        }
+        else {
+            panic!("Expected an arc, got {:?}", calls[4]);
+        }
+        assert_eq!(calls[6], DrawCall::MoveTo(Coordinates::new(1, 1)));
+    }
+
+    #[test]
+    fn test_canvas_curves() {
+        let c = RecordingCanvas::new();
+        let path = FOPath::new(PathDrawStyle::Draw, Coordinates::new(0, 0), vec![
+            PathType::Quadratic { control: Coordinates::new(1, 1).into(), to: Coordinates::new(2, 0).into() },
+            PathType::Cubic { control_start: Coordinates::new(2, 1).into(), control_end: Coordinates::new(3, 1).into(), to: Coordinates::new(3, 0).into() },
+        ]);
+        path.draw_on_canvas(&c).unwrap();
+
+        assert_eq!(c.calls(), vec![
+            DrawCall::SetStrokeStyle(StrokeStyle::new()),
+            DrawCall::BeginPath,
+            DrawCall::MoveTo(Coordinates::new(0, 0)),
+            DrawCall::QuadraticCurveTo { control: Coordinates::new(1, 1), to: Coordinates::new(2, 0) },
+            DrawCall::BezierCurveTo { control_start: Coordinates::new(2, 1), control_end: Coordinates::new(3, 1), to: Coordinates::new(3, 0) },
+            DrawCall::Stroke,
+        ]);
+    }
+
+    #[test]
+    fn test_canvas_arc() {
+        // A quarter of an ellipse starting from the origin, so the center is at (-2, 0)
+        let c = RecordingCanvas::new();
+        let path = FOPath::new(PathDrawStyle::Draw, Coordinates::new(0, 0), vec![
+            PathType::Arc { start_angle: 0., end_angle: 90., x_radius: 2., y_radius: 1., rotation: 0. },
+            PathType::Line { to: Coordinates::new(0, 0).into() },
+        ]);
+        path.draw_on_canvas(&c).unwrap();
+
+        let calls = c.calls();
+        assert_eq!(calls[3], DrawCall::Ellipse {
+            center: Coordinates::new(-2, 0),
+            x_radius: 2.,
+            y_radius: 1.,
+            rotation: 0.,
+            start_angle: 0.,
+            end_angle: std::f64::consts::FRAC_PI_2,
+            anticlockwise: true
+        });
+        assert_eq!(calls[4], DrawCall::LineTo(Coordinates::new(0, 0)));
+    }
+
+    #[test]
+    fn test_canvas_fill() {
+        let data = || vec![
+            PathType::Line { to: Coordinates::new(1, 0).into() },
+            PathType::Line { to: Coordinates::new(1, 1).into() },
+        ];
+
+        let c = RecordingCanvas::new();
+        FOPath::new(PathDrawStyle::Fill, Coordinates::new(0, 0), data()).draw_on_canvas(&c).unwrap();
+        let calls = c.calls();
+        assert_eq!(calls.iter().filter(|x| **x == DrawCall::BeginPath).count(), 1);
+        assert_eq!(calls.last(), Some(&DrawCall::Fill));
+        assert!(!calls.contains(&DrawCall::Stroke));
+
+        let c = RecordingCanvas::new();
+        FOPath::new(PathDrawStyle::FillDraw, Coordinates::new(0, 0), data()).draw_on_canvas(&c).unwrap();
+        let calls = c.calls();
+        assert_eq!(calls.iter().filter(|x| **x == DrawCall::BeginPath).count(), 1);
+        assert_eq!(calls[calls.len() - 2..], [DrawCall::Fill, DrawCall::Stroke]);
+    }
+
+    #[test]
+    fn test_arrow_tips() {
+        let style = StrokeStyle::new().arrows(ArrowTip::Bar, ArrowTip::Bar);
+        let path = FOPath::new(PathDrawStyle::Draw, Coordinates::new(0, 0), vec![
+            PathType::LineXY { to: Coordinates::new(1, 1).into() },
+            PathType::Quadratic { control: Coordinates::new(2, 1).into(), to: Coordinates::new(2, 0).into() },
+        ]).stroke_style(style);
+
+        let (tikz, preamble) = path.tikzify(&TikzOutputOptions::default());
+        assert!(tikz.starts_with("\\draw[{Bar}-{Bar}] (0, 0) -|"), "{tikz}");
+        assert!(!preamble.is_empty());
+
+        // The bars are perpendicular to the path: vertical at the start and horizontal at the end
+        let c = RecordingCanvas::new();
+        path.draw_on_canvas(&c).unwrap();
+        let calls = c.calls();
+        let n = calls.len();
+        if let (DrawCall::MoveTo(a), DrawCall::LineTo(b)) = (&calls[n - 7], &calls[n - 6]) {
+            assert_eq!(a[0], 0.);
+            assert_eq!(b[0], 0.);
+        }
+        else {
+            panic!("Expected the start bar, got {:?}", &calls[n - 7..]);
+        }
+        if let (DrawCall::MoveTo(a), DrawCall::LineTo(b)) = (&calls[n - 3], &calls[n - 2]) {
+            assert_eq!(a[1], 0.);
+            assert_eq!(b[1], 0.);
+        }
+        else {
+            panic!("Expected the end bar, got {:?}", &calls[n - 3..]);
+        }
+
+        // Filled paths have no outline, so no arrows either
+        let c = RecordingCanvas::new();
+        FOPath::new(PathDrawStyle::Fill, Coordinates::new(0, 0), vec![PathType::Line { to: Coordinates::new(1, 1).into() }])
+            .stroke_style(StrokeStyle::new().arrows(ArrowTip::Bar, ArrowTip::Bar))
+            .draw_on_canvas(&c).unwrap();
+        assert_eq!(c.calls().last(), Some(&DrawCall::Fill));
+    }
+
+    #[test]
+    fn test_fill_style() {
+        let triangle = || vec![
+            PathType::Line { to: Coordinates::new(2, 0).into() },
+            PathType::Line { to: Coordinates::new(0, 1).into() },
+            PathType::Line { to: Coordinates::new(0, 0).into() },
+        ];
+        let fill = FillStyle::Pattern { pattern: FillPattern::Dots, color: Color::rgb(255, 0, 0) };
+
+        // Drawn paths ignore the fill style
+        let (tikz, _) = FOPath::new(PathDrawStyle::Draw, Coordinates::new(0, 0), triangle()).fill_style(fill.clone()).tikzify(&TikzOutputOptions::default());
+        assert!(tikz.starts_with("\\draw (0, 0)"), "{tikz}");
+
+        let path = FOPath::new(PathDrawStyle::FillDraw, Coordinates::new(0, 0), triangle())
+            .stroke_style(StrokeStyle::new().width(1.))
+            .fill_style(fill.clone());
+        let (tikz, preamble) = path.tikzify(&TikzOutputOptions::default());
+        assert!(tikz.starts_with("\\filldraw[line width=1pt, pattern=dots, pattern color="), "{tikz}");
+        assert_eq!(preamble.lines(), vec!["\\usetikzlibrary{patterns}"]);
+
+        let c = RecordingCanvas::new();
+        path.draw_on_canvas(&c).unwrap();
+        assert_eq!(c.calls()[1], DrawCall::SetFillStyle {
+            style: fill,
+            extent: BoundingBox::new(Coordinates::new(0, 0), Coordinates::new(2, 1))
+        });
+    }
+
+    #[test]
+    fn test_default_fill() {
+        // \filldraw fills with the color of the path
+        let red = Color::rgb(255, 0, 0);
+        let path = FOPath::new(PathDrawStyle::FillDraw, Coordinates::new(0, 0), vec![PathType::Circle { radius: 1. }])
+            .stroke_style(StrokeStyle::new().color(red.clone()));
+        let c = RecordingCanvas::new();
+        path.draw_on_canvas(&c).unwrap();
+        assert!(c.calls().contains(&DrawCall::SetFillStyle {
+            style: FillStyle::solid(red.clone()),
+            extent: BoundingBox::new(Coordinates::new(-1, -1), Coordinates::new(1, 1))
+        }));
+
+        let (svg, _) = FOPath::new(PathDrawStyle::Fill, Coordinates::new(0, 0), vec![PathType::Circle { radius: 1. }])
+            .fill_style(FillStyle::Solid { color: red, opacity: 0.5 })
+            .to_svg();
+        assert!(svg.contains("fill=\"rgb(255,0,0)\" fill-opacity=\"0.5\" stroke=\"none\""), "{svg}");
+    }
+
+    #[test]
+    fn test_svg_lines() {
+        assert_eq!(path_data(vec![PathType::Line { to: Coordinates::new(1, 2).into() }]), "M 0 0 L 1 -2");
+        assert_eq!(path_data(vec![PathType::LineXY { to: Coordinates::new(1, 2).into() }]), "M 0 0 L 1 0 L 1 -2");
+        assert_eq!(path_data(vec![PathType::LineYX { to: Coordinates::new(1, 2).into() }]), "M 0 0 L 0 -2 L 1 -2");
+        assert_eq!(path_data(vec![PathType::Rectangle { to: Coordinates::new(1, 2).into() }]), "M 0 0 M 0 0 L 1 0 L 1 -2 L 0 -2 Z M 1 -2");
+    }
+
+    #[test]
+    fn test_svg_bezier() {
+        let q = PathType::Quadratic { control: Coordinates::new(1, 1).into(), to: Coordinates::new(2, 0).into() };
+        assert_eq!(path_data(vec![q]), "M 0 0 Q 1 -1 2 0");
+
+        let c = PathType::Cubic { control_start: Coordinates::new(0, 1).into(), control_end: Coordinates::new(1, 1).into(), to: Coordinates::new(1, 0).into() };
+        assert_eq!(path_data(vec![c]), "M 0 0 C 0 -1 1 -1 1 0");
+    }
+
+    #[test]
+    fn test_svg_circle_and_arc() {
+        assert_eq!(path_data(vec![PathType::Circle { radius: 1. }]), "M 0 0 M 1 0 A 1 1 0 1 0 -1 0 A 1 1 0 1 0 1 0 Z M 0 0");
+
+        // A half circle starting from the origin going counterclockwise ends at (-2, 0)
+        let arc = PathType::Arc { start_angle: 0., end_angle: 180., x_radius: 1., y_radius: 1., rotation: 0. };
+        let d = path_data(vec![arc]);
+        assert!(d.starts_with("M 0 0 A 1 1 0 0 0 -2"), "{d}");
+        assert_eq!(PathType::Arc { start_angle: 0., end_angle: 180., x_radius: 1., y_radius: 1., rotation: 0. }.end_point(Pen::at(Coordinates::new(0, 0))), Coordinates::new(-2, 0));
+
+        // Full turns get split into two pieces
+        let arc = PathType::Arc { start_angle: 90., end_angle: -270., x_radius: 2., y_radius: 1., rotation: 0. };
+        assert_eq!(path_data(vec![arc]).matches('A').count(), 2);
+    }
+
+    #[test]
+    fn test_svg_extent() {
+        let path = FOPath::new(PathDrawStyle::Fill, Coordinates::new(0, 0), vec![
+            PathType::Line { to: Coordinates::new(1, 0).into() },
+            PathType::Circle { radius: 2. },
+        ]);
+        let (svg, bb) = path.to_svg();
+        assert!(svg.contains("fill=\"rgb(0,0,0)\" stroke=\"none\""));
+        assert_eq!(bb, BoundingBox::new(Coordinates::new(-1, -2), Coordinates::new(3, 2)));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        // Only the top half of the circle is drawn
+        let path = draw(vec![PathType::Arc { start_angle: 0., end_angle: 180., x_radius: 1., y_radius: 1., rotation: 0. }]);
+        assert_eq!(path.bounding_box(), BoundingBox::new(Coordinates::new(-2, 0), Coordinates::new(0, 1)));
+
+        // Clockwise arcs that go past 360 degrees, on a rotated ellipse
+        let path = draw(vec![PathType::Arc { start_angle: 90., end_angle: -300., x_radius: 2., y_radius: 1., rotation: 90. }]);
+        let center = arc_center(Coordinates::new(0, 0), 2., 1., 90., 90.);
+        assert_eq!(path.bounding_box(), ellipse_extent(center, 2., 1., 90.));
+
+        let path = draw(vec![
+            PathType::Cubic { control_start: Coordinates::new(0, 1).into(), control_end: Coordinates::new(1, 1).into(), to: Coordinates::new(1, 0).into() },
+            PathType::Quadratic { control: Coordinates::new(2, -2).into(), to: Coordinates::new(3, 0).into() },
+        ]);
+        assert_eq!(path.bounding_box(), BoundingBox::new(Coordinates::new(0, -1), Coordinates::new(3, 0.75)));
+    }
+
+    #[test]
+    fn test_fill_rule() {
+        // Two squares, one inside the other, drawn the same way round
+        let path = || FOPath::new(PathDrawStyle::Fill, Coordinates::new(0, 0), vec![
+            PathType::Rectangle { to: Coordinates::new(4, 4).into() },
+            PathType::Move { to: Coordinates::new(1, 1).into() },
+            PathType::Rectangle { to: Coordinates::new(3, 3).into() },
+        ]);
+
+        let inner = Coordinates::new(2, 2);
+        assert!(path().contains(inner));
+        assert!(!path().fill_rule(FillRule::EvenOdd).contains(inner));
+        assert_eq!(path().fill_rule(FillRule::EvenOdd).distance_to(inner), 1.);
+        assert_eq!(path().distance_to(inner), 0.);
+
+        let eo = path().fill_rule(FillRule::EvenOdd);
+        let (tikz, _) = eo.tikzify(&TikzOutputOptions::default());
+        assert!(tikz.starts_with("\\fill[even odd rule] (0, 0) rectangle"), "{tikz}");
+        assert!(eo.to_svg().0.contains("fill-rule=\"evenodd\""));
+        assert_ne!(eo.repr(), path().repr());
+
+        let c = RecordingCanvas::new();
+        eo.draw_on_canvas(&c).unwrap();
+        assert_eq!(c.calls().last(), Some(&DrawCall::FillEvenOdd));
+    }
+
+    #[test]
+    fn test_distance() {
+        let path = draw(vec![
+            PathType::LineXY { to: Coordinates::new(2, 2).into() },
+            PathType::Cubic { control_start: Coordinates::new(2, 3).into(), control_end: Coordinates::new(3, 3).into(), to: Coordinates::new(3, 2).into() },
+            PathType::Move { to: Coordinates::new(10, 0).into() },
+            PathType::Circle { radius: 1. },
+        ]);
+
+        assert!(calc::eq(&path.distance_to(Coordinates::new(1, 0.5)), &0.5));
+        assert!(calc::eq(&path.distance_to(Coordinates::new(2.5, 3.75)), &1.));
+        assert!(calc::eq(&path.distance_to(Coordinates::new(10, 0.5)), &0.5));
+
+        // The moved over gap is not part of the path
+        assert!(path.distance_to(Coordinates::new(6, 1)) > 2.);
+
+        // Filled circles are solid
+        let disk = FOPath::new(PathDrawStyle::FillDraw, Coordinates::new(0, 0), vec![PathType::Circle { radius: 1. }]);
+        assert_eq!(disk.distance_to(Coordinates::new(0.5, 0)), 0.);
+        assert!(calc::eq(&disk.distance_to(Coordinates::new(0, 3)), &2.));
+    }
+
+    #[test]
+    fn test_relative_targets() {
+        // A single + does not move the reference point, so every segment is measured from the origin
+        let path = FOPath::new(PathDrawStyle::Draw, Coordinates::new(1, 1), vec![
+            PathType::Line { to: PathTarget::relative(Coordinates::new(1, 0)) },
+            PathType::Line { to: PathTarget::relative(Coordinates::new(0, 1)) },
+            PathType::Line { to: PathTarget::relative_move(Coordinates::new(-1, 0)) },
+            PathType::Line { to: PathTarget::relative_move(Coordinates::new(0, -1)) },
+        ]);
+
+        let (tikz, _) = path.tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, "\\draw (1, 1) -- +(1, 0) -- +(0, 1) -- ++(-1, 0) -- ++(0, -1);");
+
+        let c = RecordingCanvas::new();
+        path.draw_on_canvas(&c).unwrap();
+        assert_eq!(c.calls()[2..6], [
+            DrawCall::MoveTo(Coordinates::new(1, 1)),
+            DrawCall::LineTo(Coordinates::new(2, 1)),
+            DrawCall::LineTo(Coordinates::new(1, 2)),
+            DrawCall::LineTo(Coordinates::new(0, 1)),
+        ]);
+        assert_eq!(c.calls()[6], DrawCall::LineTo(Coordinates::new(0, 0)));
+    }
+
+    #[test]
+    fn test_polar_targets() {
+        let path = draw(vec![
+            PathType::Line { to: PathTarget::polar(90., 2.) },
+            PathType::Rectangle { to: PathTarget::RelativeMove(Offset::Polar { angle: 0., radius: 1. }) },
+        ]);
+
+        let (tikz, _) = path.tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, "\\draw (0, 0) -- (90:2) rectangle ++(0:1);");
+        assert_eq!(path.repr(), "d (0, 0)L(90:2)Rect++(0:1)");
+
+        let d = path_data(vec![PathType::Line { to: PathTarget::polar(0., 2.) }]);
+        assert_eq!(d, "M 0 0 L 2 0");
+        assert!((Offset::Polar { angle: 150., radius: 2. }.get() - Coordinates::new(-(3f64).sqrt(), 1)).magnitude() < 1e-10);
+    }
+
+    #[test]
+    fn test_relative_controls() {
+        // The first control point is measured from the start and the second one from the end
+        let path = FOPath::new(PathDrawStyle::Draw, Coordinates::new(1, 0), vec![
+            PathType::Cubic {
+                control_start: PathTarget::relative(Coordinates::new(0, 1)),
+                control_end: PathTarget::relative(Coordinates::new(0, 1)),
+                to: PathTarget::relative_move(Coordinates::new(2, 0))
+            },
+        ]);
+
+        let (tikz, _) = path.tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, "\\draw (1, 0) .. controls +(0, 1) and +(0, 1) .. ++(2, 0);");
+
+        let c = RecordingCanvas::new();
+        path.draw_on_canvas(&c).unwrap();
+        assert_eq!(c.calls()[3], DrawCall::BezierCurveTo {
+            control_start: Coordinates::new(1, 1),
+            control_end: Coordinates::new(3, 1),
+            to: Coordinates::new(3, 0)
+        });
+    }
+
+    /// Returns where the pen is after each segment
+    fn pen_positions(path: &FOPath) -> Vec<Coordinates> {
+        let mut pen = Pen::at(path.start.get());
+        let mut v = vec![pen.position];
+        for x in path.data.iter() {
+            pen = x.advance(pen);
+            v.push(pen.position);
+        }
+        v
+    }
+
+    #[test]
+    fn test_transformed_targets() {
+        let path = FOPath::new(PathDrawStyle::Draw, Coordinates::new(1, 0), vec![
+            PathType::Line { to: PathTarget::relative(Coordinates::new(1, 0)) },
+            PathType::Line { to: PathTarget::polar(90., 1.) },
+            PathType::Line { to: PathTarget::RelativeMove(Offset::Polar { angle: 0., radius: 1. }) },
+        ]);
+
+        // Rotations keep relative and polar targets as they are
+        let (tikz, _) = path.transformed(&Affine2::rotate(std::f64::consts::FRAC_PI_2)).tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, "\\draw (0, 1) -- +(0, 1) -- (180:1) -- ++(90:1);");
+
+        // Polar points are measured from the origin, so they do not survive a shift
+        let (tikz, _) = path.transformed(&Affine2::translate(Coordinates::new(1, 1))).tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, "\\draw (2, 1) -- +(1, 0) -- (1, 2) -- ++(0:1);");
+
+        let (tikz, _) = path.transformed(&Affine2::scale(2., 1.)).tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, "\\draw (2, 0) -- +(2, 0) -- (0, 1) -- ++(2, 0);");
+    }
+
+    #[test]
+    fn test_transformed_lines() {
+        let path = FOPath::new(PathDrawStyle::Draw, Coordinates::new(0, 0), vec![
+            PathType::Line { to: PathTarget::relative(Coordinates::new(0, 1)) },
+            PathType::LineXY { to: PathTarget::relative(Coordinates::new(2, 1)) },
+            PathType::Rectangle { to: Coordinates::new(3, 3).into() },
+            PathType::Line { to: PathTarget::relative_move(Coordinates::new(1, 0)) },
+        ]);
+
+        // Mirroring in the diagonal swaps the axes, so -| becomes |-
+        let (tikz, _) = path.transformed(&Affine2::reflect(Coordinates::new(1, 1))).tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, "\\draw (0, 0) -- +(1, 0) |- +(1, 2) rectangle (3, 3) -- ++(0, 1);");
+
+        // Shears turn -| into two lines and the rectangle into a parallelogram
+        let tf = Affine2::shear(0.5, 0.).then(&Affine2::translate(Coordinates::new(1, 0)));
+        let image = path.transformed(&tf);
+        assert_eq!(image.data.len(), 10);
+        let expected = pen_positions(&path).into_iter().map(|p| tf.apply(p)).collect::<Vec<Coordinates>>();
+        let actual = pen_positions(&image);
+        assert_eq!([actual[0], actual[1], actual[3], actual[9], actual[10]], expected[..]);
+    }
+
+    #[test]
+    fn test_transformed_arcs() {
+        let arc = || draw(vec![
+            PathType::Arc { start_angle: 0., end_angle: 90., x_radius: 2., y_radius: 1., rotation: 0. },
+            PathType::Circle { radius: 1. },
+        ]);
+
+        // Circles turn into ellipses when stretched
+        let image = arc().transformed(&Affine2::scale(1., 3.));
+        let (tikz, _) = image.tikzify(&TikzOutputOptions::default());
+        assert!(tikz.ends_with("{[rotate=90] ellipse (3 and 1)};"), "{tikz}");
+
+        let c = RecordingCanvas::new();
+        image.draw_on_canvas(&c).unwrap();
+        assert!(c.calls().iter().any(|x| matches!(x, DrawCall::Ellipse { rotation, .. } if calc::eq(rotation, &std::f64::consts::FRAC_PI_2))));
+
+        // Every point on the arc goes to where the transform takes it, including after a reflection
+        for tf in [Affine2::rotate(1.), Affine2::shear(0.3, -0.2), Affine2::reflect(Coordinates::new(1, 2)).then(&Affine2::scale(1., 2.))] {
+            let image = arc().transformed(&tf);
+            let expected = pen_positions(&arc()).into_iter().map(|p| tf.apply(p)).collect::<Vec<Coordinates>>();
+            assert_eq!(pen_positions(&image), expected);
+
+            if let PathType::Arc { start_angle, end_angle, x_radius, y_radius, rotation } = image.data[0] {
+                let center = arc_center(tf.apply(Coordinates::new(0, 0)), x_radius, y_radius, rotation, start_angle);
+                let mid = point_on_ellipse(center, x_radius, y_radius, rotation, (start_angle + end_angle) / 2.);
+                let t = 45f64.to_radians();
+                assert_eq!(mid, tf.apply(Coordinates::new(-2. + 2. * t.cos(), t.sin())));
+            }
+            else {
+                panic!("Expected an arc");
+            }
+        }
+    }
+}
@@ -7,6 +7,7 @@ use crate::core::*;
 #[derive(Clone)]
 pub struct FOPoint {
     point: Coordinates,
+    stroke: StrokeStyle,
 }
 
 impl FOPoint {
@@ -19,23 +20,68 @@ impl FOPoint {
     pub fn new(x: Coordinates) -> Self {
         Self {
             point: x,
+            stroke: StrokeStyle::new(),
+        }
+    }
+
+    /// Sets the style of the outline of the point
+    pub fn stroke_style(mut self, stroke: StrokeStyle) -> Self {
+        self.stroke = stroke;
+        return self;
+    }
+
+    /// Moves the point with the transform. The point stays the same size
+    pub fn transformed(&self, tf: &Affine2) -> FOPoint {
+        Self {
+            point: tf.apply(self.point),
+            stroke: self.stroke.clone()
         }
     }
 }
 
 impl FigureObject for FOPoint {
-    fn tikzify(&self) -> (String, Option<String>) {
+    fn tikzify(&self, opts: &TikzOutputOptions) -> (String, Preamble) {
+        // Points are always given a color explicitly
+        let (mut options, preamble) = self.stroke.tikz_options(opts);
+        if self.stroke.color.is_none() {
+            options.insert(0, "color=black".to_string());
+        }
+
         return (
-            format!("\\filldraw[color=black] {} circle (0.8pt);", self.point),
-            None
+            format!("\\filldraw[{}] {} circle (0.8pt);", options.join(", "), opts.coords(self.point)),
+            preamble
         )
     }
 
-    fn draw_on_canvas(&self, c: HtmlCanvas) -> Result<(), DrawError> {
-        return c.draw_circle(self.point, 2.);
+    fn to_svg(&self) -> (String, BoundingBox) {
+        let (x, y) = svg_coords(self.point);
+        let r = 0.8 * PT;
+        let color = self.stroke.effective_color().to_svg();
+        let svg = format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{color}\" {}/>",
+            x.to_svg(), y.to_svg(), r.to_svg(), self.stroke.svgify());
+        (svg, self.bounding_box())
+    }
+
+    /// The dot is 0.8pt in radius, like in the Tikz output
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::from_point(self.point).expand(0.8 * PT)
+    }
+
+    fn distance_to(&self, p: Coordinates) -> f64 {
+        ((p - self.point).magnitude() - 0.8 * PT).max(0.)
+    }
+
+    fn draw_on_canvas(&self, c: &dyn RenderTarget) -> Result<(), DrawError> {
+        // Points are 2 pixels wide regardless of the zoom level
+        c.set_stroke_style(&self.stroke)?;
+        return c.draw_circle(self.point, 2. / c.scale());
     }
 
     fn repr(&self) -> String {
         format!("point{}", self.point)
     }
+
+    fn transformed(&self, tf: &Affine2) -> PlottableObject {
+        FOPoint::transformed(self, tf).wrap()
+    }
 }
@@ -0,0 +1,310 @@
+//! A group of objects, like a scope in Tikz. Everything in the scope can be shifted, rotated and scaled together,
+//! and clipped to the inside of a path
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::figures::*;
+use crate::core::*;
+
+pub struct FOScope {
+    children: Vec<PlottableObject>,
+    shift: Coordinates,
+
+    /// In degrees, counterclockwise
+    rotate: f64,
+    scale: f64,
+
+    /// The clip path is in the coordinates of the scope, so it moves with everything else in it
+    clip: Option<FOPath>
+}
+
+impl FOScope {
+    pub fn new(children: Vec<PlottableObject>) -> Self {
+        Self {
+            children,
+            shift: Coordinates::new(0, 0),
+            rotate: 0.,
+            scale: 1.,
+            clip: None
+        }
+    }
+
+    pub fn shift(mut self, shift: Coordinates) -> Self {
+        self.shift = shift;
+        return self;
+    }
+
+    /// Rotates everything in the scope around the origin, in degrees counterclockwise
+    pub fn rotate(mut self, angle: f64) -> Self {
+        self.rotate = angle;
+        return self;
+    }
+
+    /// Scales everything in the scope about the origin. Like in Tikz, line widths and text do not get scaled
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        return self;
+    }
+
+    /// Only the parts of the scope inside the path are drawn. The style of the path is ignored
+    pub fn clip(mut self, path: FOPath) -> Self {
+        self.clip = Some(path);
+        return self;
+    }
+
+    /// Returns the transform from the coordinates of the scope to the outside. Same as Tikz, points are scaled first, then rotated, then shifted
+    pub fn affine(&self) -> Affine2 {
+        Affine2::scale(self.scale, self.scale)
+            .then(&Affine2::rotate(self.rotate.to_radians()))
+            .then(&Affine2::translate(self.shift))
+    }
+
+    /// Returns the image of the scope under the transform. If the scope together with the transform only shifts, rotates
+    /// and scales, the scope keeps its children and changes its options. Otherwise, the children and the clip path get transformed
+    pub fn transformed(&self, tf: &Affine2) -> FOScope {
+        let total = self.affine().then(tf);
+        if let Some((shift, rotate, scale)) = total.as_similarity() {
+            return FOScope {
+                children: self.children.clone(),
+                shift,
+                rotate: rotate.to_degrees(),
+                scale,
+                clip: self.clip.clone()
+            };
+        }
+
+        FOScope {
+            children: self.children.iter().map(|x| x.transformed(&total)).collect(),
+            shift: Coordinates::new(0, 0),
+            rotate: 0.,
+            scale: 1.,
+            clip: self.clip.as_ref().map(|x| x.transformed(&total))
+        }
+    }
+
+    /// Returns the Tikz options of the scope, leaving out the ones that do nothing
+    fn tikz_options(&self, opts: &TikzOutputOptions) -> Vec<String> {
+        let mut v = vec![];
+
+        if self.shift != Coordinates::new(0, 0) {
+            v.push(format!("shift={{{}}}", opts.coords(self.shift)));
+        }
+
+        if self.rotate != 0. {
+            v.push(format!("rotate={}", opts.number(self.rotate)));
+        }
+
+        if self.scale != 1. {
+            v.push(format!("scale={}", opts.number(self.scale)));
+        }
+
+        return v;
+    }
+
+    fn draw_inside(&self, c: &dyn RenderTarget) -> Result<(), DrawError> {
+        c.transform(self.shift, self.rotate.to_radians(), self.scale)?;
+
+        if let Some(clip) = &self.clip {
+            clip.trace(c)?;
+            c.clip()?;
+        }
+
+        for x in self.children.iter() {
+            x.draw_on_canvas(c)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FigureObject for FOScope {
+    fn tikzify(&self, opts: &TikzOutputOptions) -> (String, Preamble) {
+        let options = self.tikz_options(opts);
+        let mut s = if options.len() > 0 {
+            format!("\\begin{{scope}}[{}]\n", options.join(", "))
+        } else {
+            String::from("\\begin{scope}\n")
+        };
+
+        let mut preamble = Preamble::new();
+
+        if let Some(clip) = &self.clip {
+            let (path, p) = clip.tikz_path(opts);
+            s.push_str(&format!("    \\clip {path};\n"));
+            preamble.merge(&p);
+        }
+
+        // Children can be scopes themselves, so every line gets indented
+        for x in self.children.iter() {
+            let (b, p) = x.tikzify(opts);
+            for line in b.lines() {
+                s.push_str(&format!("    {line}\n"));
+            }
+            preamble.merge(&p);
+        }
+
+        s.push_str("\\end{scope}");
+        (s, preamble)
+    }
+
+    /// The children are transformed instead of the group, since an svg transform would scale the line widths too
+    fn to_svg(&self) -> (String, BoundingBox) {
+        let tf = self.affine();
+        let mut body = self.children.iter().map(|x| x.transformed(&tf).to_svg().0).collect::<String>();
+
+        if let Some(clip) = &self.clip {
+            let d = clip.transformed(&tf).svg_path_data();
+            let mut hasher = DefaultHasher::new();
+            d.hash(&mut hasher);
+            let id = format!("clip-{:x}", hasher.finish());
+
+            body = format!("<clipPath id=\"{id}\"><path d=\"{d}\"/></clipPath><g clip-path=\"url(#{id})\">{body}</g>");
+        }
+
+        let svg = format!("<g>{body}</g>");
+        (svg, self.bounding_box())
+    }
+
+    /// Transforming the children first gives a tighter box than transforming the box of the children
+    fn bounding_box(&self) -> BoundingBox {
+        let tf = self.affine();
+        let extent = self.children.iter()
+            .map(|x| x.transformed(&tf).bounding_box())
+            .reduce(|a, b| a.union(&b));
+
+        // Clipped scopes cannot go outside the clip path
+        let clip = self.clip.as_ref().map(|x| x.transformed(&tf).bounding_box());
+        match (extent, clip) {
+            (Some(e), Some(c)) => e.intersection(&c).unwrap_or(BoundingBox::from_point(tf.apply(Coordinates::new(0, 0)))),
+            (Some(e), None) => e,
+            (None, Some(c)) => c,
+            (None, None) => BoundingBox::from_point(tf.apply(Coordinates::new(0, 0)))
+        }
+    }
+
+    /// With a clip path, this is the larger of the distances to the children and to the inside of the clip path. That is
+    /// exact unless p is near both but the closest child is clipped away
+    fn distance_to(&self, p: Coordinates) -> f64 {
+        let tf = self.affine();
+        let d = self.children.iter().map(|x| x.transformed(&tf).distance_to(p)).fold(f64::INFINITY, f64::min);
+
+        match &self.clip {
+            Some(clip) => {
+                let clip = clip.transformed(&tf);
+                if clip.contains(p) { d } else { d.max(clip.distance_to_outline(p)) }
+            },
+            None => d
+        }
+    }
+
+    fn draw_on_canvas(&self, c: &dyn RenderTarget) -> Result<(), DrawError> {
+        // Always restore, even if something inside fails, so the transform does not leak onto everything drawn after
+        c.save()?;
+        let res = self.draw_inside(c);
+        c.restore()?;
+        res
+    }
+
+    fn transformed(&self, tf: &Affine2) -> PlottableObject {
+        FOScope::transformed(self, tf).wrap()
+    }
+
+    fn repr(&self) -> String {
+        let clip = self.clip.as_ref().map(|x| x.repr()).unwrap_or_default();
+        let children = self.children.iter().map(|x| x.repr()).collect::<Vec<String>>().join(";");
+        format!("scope{}{}{}[{clip}]{{{children}}}", self.shift, self.rotate, self.scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> FOPath {
+        FOPath::new(PathDrawStyle::Draw, Coordinates::new(0, 0), vec![PathType::Rectangle { to: Coordinates::new(1, 1).into() }])
+    }
+
+    #[test]
+    fn test_tikz() {
+        let scope = FOScope::new(vec![FOLine::new(Coordinates::new(0, 0), Coordinates::new(1, 2)).wrap()])
+            .shift(Coordinates::new(1, 0))
+            .rotate(30.)
+            .clip(square());
+
+        let (tikz, _) = scope.tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, concat!(
+            "\\begin{scope}[shift={(1, 0)}, rotate=30]\n",
+            "    \\clip (0, 0) rectangle (1, 1);\n",
+            "    \\draw (0, 0) -- (1, 2);\n",
+            "\\end{scope}"
+        ));
+
+        // Nested scopes are indented further
+        let outer = FOScope::new(vec![scope.wrap()]).scale(2.);
+        let fig = TikzFigure::new().draw(outer.wrap());
+        assert_eq!(fig.output(), concat!(
+            "\\begin{tikzpicture}\n",
+            "    \\begin{scope}[scale=2]\n",
+            "        \\begin{scope}[shift={(1, 0)}, rotate=30]\n",
+            "            \\clip (0, 0) rectangle (1, 1);\n",
+            "            \\draw (0, 0) -- (1, 2);\n",
+            "        \\end{scope}\n",
+            "    \\end{scope}\n",
+            "\\end{tikzpicture}\n"
+        ));
+    }
+
+    #[test]
+    fn test_canvas() {
+        let scope = FOScope::new(vec![FOLine::new(Coordinates::new(0, 0), Coordinates::new(1, 0)).wrap()])
+            .rotate(90.)
+            .scale(2.)
+            .clip(square());
+
+        let c = RecordingCanvas::new();
+        scope.draw_on_canvas(&c).unwrap();
+        let calls = c.calls();
+        assert_eq!(calls[0], DrawCall::Save);
+        assert_eq!(calls[1], DrawCall::Transform { shift: Coordinates::new(0, 0), rotate: std::f64::consts::FRAC_PI_2, scale: 2. });
+
+        // The clip path is traced but not drawn
+        let clip = calls.iter().position(|x| *x == DrawCall::Clip).unwrap();
+        assert!(!calls[..clip].contains(&DrawCall::Stroke));
+        assert!(calls[clip..].contains(&DrawCall::LineTo(Coordinates::new(1, 0))));
+        assert_eq!(calls.last(), Some(&DrawCall::Restore));
+    }
+
+    #[test]
+    fn test_svg() {
+        let scope = FOScope::new(vec![FOLine::new(Coordinates::new(0, 0), Coordinates::new(1, 0)).wrap()])
+            .shift(Coordinates::new(1, 1))
+            .rotate(90.);
+
+        let (svg, bb) = scope.to_svg();
+        assert_eq!(svg, format!("<g>{}</g>", FOLine::new(Coordinates::new(1, 1), Coordinates::new(1, 2)).to_svg().0));
+        assert_eq!(bb, BoundingBox::new(Coordinates::new(1, 1), Coordinates::new(1, 2)));
+
+        // Like in Tikz, scaling the scope leaves the line widths alone
+        let (svg, _) = FOScope::new(vec![FOLine::new(Coordinates::new(0, 0), Coordinates::new(1, 0)).wrap()]).scale(3.).to_svg();
+        assert_eq!(svg, format!("<g>{}</g>", FOLine::new(Coordinates::new(0, 0), Coordinates::new(3, 0)).to_svg().0));
+
+        let (svg, bb) = scope.clip(square()).to_svg();
+        assert!(svg.contains("<clipPath id=\"clip-") && svg.contains("clip-path=\"url(#clip-"), "{svg}");
+        assert_eq!(bb, BoundingBox::new(Coordinates::new(1, 1), Coordinates::new(1, 2)));
+    }
+
+    #[test]
+    fn test_transformed() {
+        let line = || FOLine::new(Coordinates::new(0, 0), Coordinates::new(1, 0)).wrap();
+        let scope = FOScope::new(vec![line()]).shift(Coordinates::new(1, 0)).scale(2.);
+
+        // Rotating the scope only changes its options
+        let (tikz, _) = scope.transformed(&Affine2::rotate(std::f64::consts::FRAC_PI_2)).tikzify(&TikzOutputOptions::default());
+        assert!(tikz.starts_with("\\begin{scope}[shift={(0, 1)}, rotate=90, scale=2]\n    \\draw (0, 0) -- (1, 0);"), "{tikz}");
+
+        // Stretching goes into the children instead
+        let (tikz, _) = scope.transformed(&Affine2::scale(1., 3.)).tikzify(&TikzOutputOptions::default());
+        assert_eq!(tikz, "\\begin{scope}\n    \\draw (1, 0) -- (3, 0);\n\\end{scope}");
+    }
+}
@@ -1,15 +1,21 @@
 #![allow(unused)]
 //! Base module for Tikzpaint-rs. We try to structure the app such that
-//! the "figures" module can also work as a CLI tool for Tikz figure generation
+//! the "figures" module can also work as a CLI tool for Tikz figure generation.
+//! Everything that needs a browser lives behind the "web" feature
 
 pub mod figures;
+#[cfg(feature = "web")]
 pub mod app;
 pub mod core;
+pub mod engine;
+
+#[cfg(feature = "web")]
 use yew::prelude::*;
+#[cfg(feature = "web")]
 use app::{CanvasManager, initialize_app};
-use yew::suspense::{Suspension, SuspensionResult};
 
 /// This component should be injected on the base app.
+#[cfg(feature = "web")]
 #[function_component(App)]
 pub fn app() -> Html {
     html!{